@@ -0,0 +1,159 @@
+use crate::error::{AppError, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+pub type ActivityState = Arc<ActivityRegistry>;
+
+/// How operations show their target: `s3://bucket/prefix`
+pub fn s3_target(bucket: &str, prefix: &str) -> String {
+    format!("s3://{}/{}", bucket, prefix)
+}
+
+/// A long-running listing, stats, export or bulk-change command that is still going
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveOperation {
+    pub id: String,
+    pub kind: String,
+    /// What it works on, usually an [`s3_target`]
+    pub target: String,
+    pub started_at: i64, // Timestamp (ms)
+}
+
+struct Registered {
+    operation: ActiveOperation,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Every long-running operation other than transfers, with a cancel flag the
+/// operation polls between requests. Transfers have their own queue in
+/// `TransferManager`.
+#[derive(Default)]
+pub struct ActivityRegistry {
+    operations: Mutex<HashMap<String, Registered>>,
+}
+
+impl ActivityRegistry {
+    /// Register an operation under `id`, replacing any earlier one with that id.
+    /// It stays listed until the returned handle is dropped.
+    pub fn register(self: &Arc<Self>, kind: &'static str, id: String, target: String) -> OperationHandle {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        if let Ok(mut operations) = self.operations.lock() {
+            operations.insert(
+                id.clone(),
+                Registered {
+                    operation: ActiveOperation {
+                        id: id.clone(),
+                        kind: kind.to_string(),
+                        target,
+                        started_at: chrono::Utc::now().timestamp_millis(),
+                    },
+                    cancelled: cancelled.clone(),
+                },
+            );
+        }
+        OperationHandle {
+            registry: self.clone(),
+            kind,
+            id,
+            cancelled,
+        }
+    }
+
+    /// Running operations, oldest first
+    pub fn list(&self) -> Vec<ActiveOperation> {
+        let Ok(operations) = self.operations.lock() else {
+            return Vec::new();
+        };
+        let mut active: Vec<ActiveOperation> = operations.values().map(|entry| entry.operation.clone()).collect();
+        active.sort_by(|a, b| a.started_at.cmp(&b.started_at).then_with(|| a.id.cmp(&b.id)));
+        active
+    }
+
+    /// Flag an operation as cancelled. Returns false when none with that id is
+    /// running.
+    pub fn cancel(&self, id: &str) -> Result<bool> {
+        let operations = self
+            .operations
+            .lock()
+            .map_err(|_| AppError::ConfigError("Operation registry poisoned".into()))?;
+        match operations.get(id) {
+            Some(entry) => {
+                entry.cancelled.store(true, Ordering::Relaxed);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+/// A registered operation; unregisters it however the command returns
+pub struct OperationHandle {
+    registry: Arc<ActivityRegistry>,
+    kind: &'static str,
+    id: String,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl OperationHandle {
+    /// Fail with `Cancelled` once the operation has been cancelled
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(AppError::Cancelled(format!("{} {}", self.kind, self.id)));
+        }
+        Ok(())
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// The cancel flag, for helpers that poll it without the handle
+    pub fn cancel_flag(&self) -> &AtomicBool {
+        &self.cancelled
+    }
+}
+
+impl Drop for OperationHandle {
+    fn drop(&mut self) {
+        if let Ok(mut operations) = self.registry.operations.lock() {
+            // A later operation may have taken over the id
+            if operations
+                .get(&self.id)
+                .is_some_and(|entry| Arc::ptr_eq(&entry.cancelled, &self.cancelled))
+            {
+                operations.remove(&self.id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ActivityRegistry;
+    use std::sync::Arc;
+
+    #[test]
+    fn operations_are_listed_until_dropped_and_cancel_by_id() {
+        let registry = Arc::new(ActivityRegistry::default());
+        let first = registry.register("Scan", "a".into(), "s3://b/".into());
+        let second = registry.register("Export", "b".into(), "s3://b/x/".into());
+        assert_eq!(registry.list().len(), 2);
+
+        assert!(registry.cancel("a").unwrap());
+        assert!(first.check().is_err());
+        assert!(second.check().is_ok());
+        assert!(!registry.cancel("missing").unwrap());
+
+        // Reusing an id doesn't let the old handle unregister the new operation
+        let replacement = registry.register("Scan", "a".into(), "s3://b/".into());
+        drop(first);
+        assert_eq!(registry.list().len(), 2);
+        assert!(!replacement.is_cancelled());
+
+        drop(second);
+        drop(replacement);
+        assert!(registry.list().is_empty());
+    }
+}
@@ -0,0 +1,718 @@
+use crate::activity::{s3_target, ActivityState};
+use crate::commands::buckets::require_active_profile;
+use crate::commands::operations::ObjectPages;
+use crate::commands::profiles::ProfileState;
+use crate::error::Result;
+use crate::s3::S3State;
+use aws_sdk_s3::Client;
+use futures::StreamExt;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, State};
+
+#[derive(Debug, serde::Serialize)]
+pub struct PrefixDigest {
+    /// Hex SHA-256 root over every object's leaf hash, in key order
+    pub digest: String,
+    pub object_count: u64,
+    pub total_bytes: u64,
+}
+
+/// Running Merkle-style digest: each object contributes the SHA-256 of its
+/// `(relative key, size, ETag)` and the root hashes those leaves in key order, so
+/// objects can be fed in one at a time without holding the listing in memory.
+struct PrefixHasher {
+    root: Sha256,
+    object_count: u64,
+    total_bytes: u64,
+}
+
+impl PrefixHasher {
+    fn new() -> Self {
+        Self {
+            root: Sha256::new(),
+            object_count: 0,
+            total_bytes: 0,
+        }
+    }
+
+    fn add(&mut self, relative_key: &str, size: u64, e_tag: &str) {
+        let mut leaf = Sha256::new();
+        leaf.update(relative_key.as_bytes());
+        leaf.update([0u8]);
+        leaf.update(size.to_be_bytes());
+        leaf.update([0u8]);
+        // Some providers quote ETags in listings and others don't
+        leaf.update(e_tag.trim_matches('"').as_bytes());
+        self.root.update(leaf.finalize());
+        self.object_count += 1;
+        self.total_bytes += size;
+    }
+
+    fn finish(self) -> PrefixDigest {
+        PrefixDigest {
+            digest: self
+                .root
+                .finalize()
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect(),
+            object_count: self.object_count,
+            total_bytes: self.total_bytes,
+        }
+    }
+}
+
+/// A single digest for everything under a prefix, for checking that it still
+/// matches a known-good snapshot without downloading anything. Keys are hashed
+/// relative to the prefix, so a copy under another prefix or bucket gives the same
+/// digest as long as the ETags match (objects copied with a different part size
+/// get different ETags). S3 lists keys in sorted order, so pages are hashed as
+/// they arrive.
+#[tauri::command]
+pub async fn compute_prefix_digest(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    prefix: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    activity_state: State<'_, ActivityState>,
+) -> Result<PrefixDigest> {
+    let operation = activity_state.register("Prefix digest", uuid::Uuid::new_v4().to_string(), s3_target(&bucket_name, &prefix));
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let mut pages = ObjectPages::new(&s3_state, &active_profile, &bucket_name, bucket_region, &prefix).await?;
+
+    let mut hasher = PrefixHasher::new();
+    loop {
+        operation.check()?;
+        let Some(page) = pages.next_page().await? else {
+            break;
+        };
+
+        for object in page.contents() {
+            let Some(key) = object.key() else { continue };
+            let relative_key = key.strip_prefix(prefix.as_str()).unwrap_or(key);
+            hasher.add(
+                relative_key,
+                object.size().unwrap_or(0).max(0) as u64,
+                object.e_tag().unwrap_or_default(),
+            );
+        }
+    }
+
+    Ok(hasher.finish())
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct LargeObject {
+    pub key: String,
+    pub size: u64,
+    pub size_formatted: String,
+    pub last_modified: Option<String>,
+    pub storage_class: Option<String>,
+}
+
+/// Most objects `top_n_largest` reports
+const MAX_LARGEST_OBJECTS: usize = 10_000;
+
+/// Size, key, last modified and storage class
+type SizedEntry = (u64, String, Option<String>, Option<String>);
+
+/// Keeps the `n` largest objects seen so far in a min-heap, so the smallest of
+/// them is the one evicted when a bigger object turns up
+struct LargestObjects {
+    limit: usize,
+    heap: std::collections::BinaryHeap<std::cmp::Reverse<SizedEntry>>,
+}
+
+impl LargestObjects {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            heap: std::collections::BinaryHeap::with_capacity(limit + 1),
+        }
+    }
+
+    fn add(&mut self, key: &str, size: u64, last_modified: Option<String>, storage_class: Option<String>) {
+        if self.limit == 0 {
+            return;
+        }
+        if self.heap.len() == self.limit {
+            match self.heap.peek() {
+                Some(std::cmp::Reverse((smallest, _, _, _))) if size <= *smallest => return,
+                _ => {}
+            }
+            self.heap.pop();
+        }
+        self.heap
+            .push(std::cmp::Reverse((size, key.to_string(), last_modified, storage_class)));
+    }
+
+    /// Largest first
+    fn finish(self) -> Vec<LargeObject> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|std::cmp::Reverse((size, key, last_modified, storage_class))| LargeObject {
+                key,
+                size,
+                size_formatted: crate::s3::format_size(size),
+                last_modified,
+                storage_class,
+            })
+            .collect()
+    }
+}
+
+/// The `n` largest objects under a prefix, at most `MAX_LARGEST_OBJECTS`, found
+/// in a single pass over the listing without keeping it in memory
+#[tauri::command]
+pub async fn top_n_largest(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    prefix: String,
+    n: usize,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    activity_state: State<'_, ActivityState>,
+) -> Result<Vec<LargeObject>> {
+    if n == 0 {
+        return Err(crate::error::AppError::InvalidContent("n must be at least 1".into()));
+    }
+    let n = n.min(MAX_LARGEST_OBJECTS);
+    let operation = activity_state.register("Largest objects", uuid::Uuid::new_v4().to_string(), s3_target(&bucket_name, &prefix));
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let mut pages = ObjectPages::new(&s3_state, &active_profile, &bucket_name, bucket_region, &prefix).await?;
+
+    let mut largest = LargestObjects::new(n);
+    loop {
+        operation.check()?;
+        let Some(page) = pages.next_page().await? else {
+            break;
+        };
+
+        for object in page.contents() {
+            let Some(key) = object.key() else { continue };
+            largest.add(
+                key,
+                object.size().unwrap_or(0).max(0) as u64,
+                object.last_modified().map(|date| date.to_string()),
+                object.storage_class().map(|class| class.as_str().to_string()),
+            );
+        }
+    }
+
+    Ok(largest.finish())
+}
+
+/// Label for keys without an extension in `extension_breakdown`
+const NO_EXTENSION: &str = "(none)";
+
+#[derive(Debug, serde::Serialize)]
+pub struct ExtensionStats {
+    pub extension: String,
+    pub count: u64,
+    pub total_size: u64,
+    pub total_size_formatted: String,
+}
+
+/// Lowercased extension of a key's file name. Dotfiles such as `.env` and names
+/// ending in a dot have none.
+fn key_extension(key: &str) -> &str {
+    let name = key.rsplit('/').next().unwrap_or(key);
+    match name.rfind('.') {
+        Some(idx) if idx > 0 && idx + 1 < name.len() => &name[idx + 1..],
+        _ => NO_EXTENSION,
+    }
+}
+
+/// Largest total size first, then most objects, then by name
+fn sorted_extension_stats(totals: HashMap<String, (u64, u64)>) -> Vec<ExtensionStats> {
+    let mut stats: Vec<ExtensionStats> = totals
+        .into_iter()
+        .map(|(extension, (count, total_size))| ExtensionStats {
+            extension,
+            count,
+            total_size,
+            total_size_formatted: crate::s3::format_size(total_size),
+        })
+        .collect();
+    stats.sort_by(|a, b| {
+        b.total_size
+            .cmp(&a.total_size)
+            .then(b.count.cmp(&a.count))
+            .then_with(|| a.extension.cmp(&b.extension))
+    });
+    stats
+}
+
+/// Object count and total size per file extension under a prefix, streamed from
+/// the listing. Folder markers are skipped.
+#[tauri::command]
+pub async fn extension_breakdown(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    prefix: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    activity_state: State<'_, ActivityState>,
+) -> Result<Vec<ExtensionStats>> {
+    let operation = activity_state.register("Extension breakdown", uuid::Uuid::new_v4().to_string(), s3_target(&bucket_name, &prefix));
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let mut pages = ObjectPages::new(&s3_state, &active_profile, &bucket_name, bucket_region, &prefix).await?;
+
+    // extension -> (count, total size)
+    let mut totals: HashMap<String, (u64, u64)> = HashMap::new();
+    loop {
+        operation.check()?;
+        let Some(page) = pages.next_page().await? else {
+            break;
+        };
+
+        for object in page.contents() {
+            let Some(key) = object.key() else { continue };
+            if key.ends_with('/') {
+                continue;
+            }
+            let entry = totals.entry(key_extension(key).to_lowercase()).or_default();
+            entry.0 += 1;
+            entry.1 += object.size().unwrap_or(0).max(0) as u64;
+        }
+    }
+
+    Ok(sorted_extension_stats(totals))
+}
+
+const DUPLICATE_HASH_CONCURRENCY: usize = 4;
+
+#[derive(Debug, serde::Serialize)]
+pub struct DuplicateGroup {
+    pub size: u64,
+    pub size_formatted: String,
+    pub keys: Vec<String>,
+    /// Bytes that would be freed by keeping only one copy
+    pub wasted_bytes: u64,
+}
+
+/// An object left out of a duplicate scan because its content couldn't be read
+#[derive(Debug, serde::Serialize)]
+pub struct SkippedObject {
+    pub key: String,
+    pub error: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct DuplicateScan {
+    pub groups: Vec<DuplicateGroup>,
+    pub skipped: Vec<SkippedObject>,
+}
+
+/// A single-part upload's ETag is the MD5 of its content; multipart ETags
+/// (`<hash>-<parts>`) depend on the part size and say nothing about equality
+fn is_content_md5(e_tag: &str) -> bool {
+    e_tag.len() == 32 && e_tag.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Group listed `(key, size, ETag)` entries into sets that might be identical:
+/// same size, and the same ETag when every ETag in the size group is a content
+/// MD5. Returns the sets settled by ETag and the size groups that still need
+/// their content hashed.
+fn candidate_duplicate_groups(objects: Vec<(String, u64, String)>) -> (Vec<DuplicateGroup>, Vec<(u64, Vec<String>)>) {
+    let mut by_size: BTreeMap<u64, Vec<(String, String)>> = BTreeMap::new();
+    for (key, size, e_tag) in objects {
+        by_size.entry(size).or_default().push((key, e_tag));
+    }
+
+    let mut settled = Vec::new();
+    let mut to_hash = Vec::new();
+    for (size, entries) in by_size {
+        if entries.len() < 2 {
+            continue;
+        }
+        if entries.iter().all(|(_, e_tag)| is_content_md5(e_tag)) {
+            let mut by_e_tag: BTreeMap<String, Vec<String>> = BTreeMap::new();
+            for (key, e_tag) in entries {
+                by_e_tag.entry(e_tag).or_default().push(key);
+            }
+            settled.extend(
+                by_e_tag
+                    .into_values()
+                    .filter(|keys| keys.len() > 1)
+                    .map(|keys| duplicate_group(size, keys)),
+            );
+        } else {
+            to_hash.push((size, entries.into_iter().map(|(key, _)| key).collect()));
+        }
+    }
+    (settled, to_hash)
+}
+
+fn duplicate_group(size: u64, mut keys: Vec<String>) -> DuplicateGroup {
+    keys.sort();
+    DuplicateGroup {
+        size,
+        size_formatted: crate::s3::format_size(size),
+        wasted_bytes: size * (keys.len() as u64 - 1),
+        keys,
+    }
+}
+
+async fn hash_object_content(client: &Client, bucket: &str, key: &str, cancelled: &AtomicBool) -> Result<[u8; 32]> {
+    let mut output = client
+        .get_object()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await
+        .map_err(|e| crate::error::AppError::S3Error(format!("Failed to read '{}': {}", key, e)))?;
+
+    let mut hasher = Sha256::new();
+    while let Some(chunk) = output
+        .body
+        .try_next()
+        .await
+        .map_err(|e| crate::error::AppError::S3Error(format!("Failed to read '{}': {}", key, e)))?
+    {
+        if cancelled.load(Ordering::Relaxed) {
+            return Err(crate::error::AppError::Cancelled(format!("Hashing '{}'", key)));
+        }
+        hasher.update(&chunk);
+    }
+    Ok(hasher.finalize().into())
+}
+
+/// Find byte-identical objects under a prefix. Objects are grouped by size
+/// first; single-part ETags settle equality for free and only size groups with
+/// multipart ETags are downloaded and hashed. Zero-byte objects and folder
+/// markers are ignored, and objects whose content can't be read are reported
+/// as skipped. Pass the same `scan_id` to `cancel_operation` to stop it.
+#[tauri::command]
+pub async fn find_duplicates(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    prefix: String,
+    scan_id: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    activity_state: State<'_, ActivityState>,
+) -> Result<DuplicateScan> {
+    let scan = activity_state.register("Duplicate scan", scan_id, s3_target(&bucket_name, &prefix));
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let mut pages = ObjectPages::new(&s3_state, &active_profile, &bucket_name, bucket_region, &prefix).await?;
+
+    let mut objects = Vec::new();
+    loop {
+        scan.check()?;
+        let Some(page) = pages.next_page().await? else {
+            break;
+        };
+
+        for object in page.contents() {
+            let Some(key) = object.key() else { continue };
+            let size = object.size().unwrap_or(0).max(0) as u64;
+            if size == 0 || key.ends_with('/') {
+                continue;
+            }
+            objects.push((
+                key.to_string(),
+                size,
+                object.e_tag().unwrap_or_default().trim_matches('"').to_string(),
+            ));
+        }
+    }
+
+    let (mut groups, to_hash) = candidate_duplicate_groups(objects);
+
+    let client_ref = pages.client();
+    let bucket_ref = bucket_name.as_str();
+    let cancelled = scan.cancel_flag();
+    let mut hashes = futures::stream::iter(
+        to_hash
+            .into_iter()
+            .flat_map(|(size, keys)| keys.into_iter().map(move |key| (size, key))),
+    )
+    .map(|(size, key)| async move {
+        let digest = hash_object_content(client_ref, bucket_ref, &key, cancelled).await;
+        (size, key, digest)
+    })
+    .buffer_unordered(DUPLICATE_HASH_CONCURRENCY);
+
+    let mut by_content: HashMap<(u64, [u8; 32]), Vec<String>> = HashMap::new();
+    let mut skipped = Vec::new();
+    while let Some((size, key, digest)) = hashes.next().await {
+        scan.check()?;
+        match digest {
+            Ok(digest) => by_content.entry((size, digest)).or_default().push(key),
+            Err(err) => {
+                log::warn!("Skipping '{}' in duplicate scan: {}", key, err);
+                skipped.push(SkippedObject { key, error: err.to_string() });
+            }
+        }
+    }
+    drop(hashes);
+
+    groups.extend(
+        by_content
+            .into_iter()
+            .filter(|(_, keys)| keys.len() > 1)
+            .map(|((size, _), keys)| duplicate_group(size, keys)),
+    );
+    groups.sort_by(|a, b| b.wasted_bytes.cmp(&a.wasted_bytes).then_with(|| a.keys.cmp(&b.keys)));
+    skipped.sort_by(|a, b| a.key.cmp(&b.key));
+
+    Ok(DuplicateScan { groups, skipped })
+}
+
+const INVENTORY_HEAD_CONCURRENCY: usize = 8;
+const INVENTORY_COLUMNS: [&str; 5] = ["key", "size", "last_modified", "storage_class", "etag"];
+
+#[derive(Clone, serde::Serialize)]
+pub struct InventoryProgress {
+    pub export_id: String,
+    pub objects_written: u64,
+    pub bytes_listed: u64,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct InventoryExport {
+    pub output_path: String,
+    pub objects_written: u64,
+    pub total_size: u64,
+    /// True when `max_objects` ended the export before the listing did
+    pub truncated: bool,
+}
+
+#[derive(Debug)]
+struct InventoryRow {
+    key: String,
+    size: u64,
+    last_modified: Option<String>,
+    storage_class: Option<String>,
+    e_tag: Option<String>,
+}
+
+fn write_inventory_rows<W: std::io::Write>(writer: &mut csv::Writer<W>, rows: &[InventoryRow]) -> Result<()> {
+    for row in rows {
+        writer
+            .write_record([
+                row.key.as_str(),
+                &row.size.to_string(),
+                row.last_modified.as_deref().unwrap_or_default(),
+                row.storage_class.as_deref().unwrap_or_default(),
+                row.e_tag.as_deref().map(|e_tag| e_tag.trim_matches('"')).unwrap_or_default(),
+            ])
+            .map_err(|e| crate::error::AppError::IoError(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Write a CSV inventory of every object under a prefix to `output_path`, a page
+/// at a time so the listing is never held in memory. ETags come from the listing,
+/// or from HeadObject per object with `head_objects`. Emits `inventory-progress`
+/// after each page and can be stopped by passing `export_id` to
+/// `cancel_operation`; the file only appears at `output_path` once the export is
+/// complete.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn export_inventory(
+    app_handle: AppHandle,
+    bucket_name: String,
+    bucket_region: Option<String>,
+    prefix: String,
+    output_path: String,
+    export_id: String,
+    head_objects: Option<bool>,
+    max_objects: Option<u64>,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    activity_state: State<'_, ActivityState>,
+) -> Result<InventoryExport> {
+    let export = activity_state.register("Inventory export", export_id.clone(), s3_target(&bucket_name, &prefix));
+    let head_objects = head_objects.unwrap_or(false);
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let mut pages = ObjectPages::new(&s3_state, &active_profile, &bucket_name, bucket_region, &prefix).await?;
+
+    let partial_path = format!("{}.partial", output_path);
+    let mut writer = csv::Writer::from_path(&partial_path)
+        .map_err(|e| crate::error::AppError::IoError(format!("Failed to create '{}': {}", partial_path, e)))?;
+
+    let mut progress = InventoryProgress {
+        export_id,
+        objects_written: 0,
+        bytes_listed: 0,
+    };
+    let mut truncated = false;
+
+    let outcome: Result<()> = async {
+        writer
+            .write_record(INVENTORY_COLUMNS)
+            .map_err(|e| crate::error::AppError::IoError(e.to_string()))?;
+
+        loop {
+            export.check()?;
+            let Some(page) = pages.next_page().await? else {
+                break;
+            };
+
+            let mut rows: Vec<InventoryRow> = page
+                .contents()
+                .iter()
+                .filter_map(|object| {
+                    Some(InventoryRow {
+                        key: object.key()?.to_string(),
+                        size: object.size().unwrap_or(0).max(0) as u64,
+                        last_modified: object.last_modified().map(|date| date.to_string()),
+                        storage_class: object.storage_class().map(|class| class.as_str().to_string()),
+                        e_tag: object.e_tag().map(str::to_string),
+                    })
+                })
+                .collect();
+            if let Some(max_objects) = max_objects {
+                let room = max_objects.saturating_sub(progress.objects_written) as usize;
+                if rows.len() > room {
+                    rows.truncate(room);
+                    truncated = true;
+                }
+            }
+
+            if head_objects {
+                let client_ref = pages.client();
+                let bucket_ref = bucket_name.as_str();
+                rows = futures::stream::iter(rows)
+                    .map(|mut row| async move {
+                        match client_ref.head_object().bucket(bucket_ref).key(&row.key).send().await {
+                            Ok(head) => row.e_tag = head.e_tag().map(str::to_string).or(row.e_tag),
+                            Err(e) => log::warn!("HeadObject failed for '{}', keeping the listed ETag: {}", row.key, e),
+                        }
+                        row
+                    })
+                    .buffered(INVENTORY_HEAD_CONCURRENCY)
+                    .collect()
+                    .await;
+            }
+
+            write_inventory_rows(&mut writer, &rows)?;
+            progress.objects_written += rows.len() as u64;
+            progress.bytes_listed += rows.iter().map(|row| row.size).sum::<u64>();
+            let _ = app_handle.emit("inventory-progress", progress.clone());
+
+            if truncated {
+                break;
+            }
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+    .await;
+
+    drop(writer);
+    let finished = match outcome {
+        Ok(()) => tokio::fs::rename(&partial_path, &output_path).await.map_err(|e| {
+            crate::error::AppError::IoError(format!("Failed to write '{}': {}", output_path, e))
+        }),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = finished {
+        let _ = tokio::fs::remove_file(&partial_path).await;
+        return Err(e);
+    }
+
+    Ok(InventoryExport {
+        output_path,
+        objects_written: progress.objects_written,
+        total_size: progress.bytes_listed,
+        truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        candidate_duplicate_groups, key_extension, sorted_extension_stats, write_inventory_rows, InventoryRow,
+        LargestObjects, PrefixHasher,
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn prefix_digest_ignores_etag_quoting_and_depends_on_order() {
+        let digest = |entries: &[(&str, u64, &str)]| {
+            let mut hasher = PrefixHasher::new();
+            for (key, size, e_tag) in entries {
+                hasher.add(key, *size, e_tag);
+            }
+            hasher.finish().digest
+        };
+        let a = digest(&[("a.txt", 3, "\"abc\""), ("b.txt", 4, "def")]);
+        assert_eq!(a.len(), 64);
+        assert_eq!(a, digest(&[("a.txt", 3, "abc"), ("b.txt", 4, "\"def\"")]));
+        assert_ne!(a, digest(&[("a.txt", 3, "abc"), ("b.txt", 5, "def")]));
+        assert_ne!(a, digest(&[("b.txt", 4, "def"), ("a.txt", 3, "abc")]));
+    }
+
+    #[test]
+    fn largest_objects_keeps_only_the_biggest() {
+        let mut largest = LargestObjects::new(2);
+        for (key, size) in [("a", 5), ("b", 50), ("c", 1), ("d", 20), ("e", 20)] {
+            largest.add(key, size, None, None);
+        }
+        let keys: Vec<String> = largest.finish().into_iter().map(|object| object.key).collect();
+        assert_eq!(keys, ["b", "d"]);
+    }
+
+    #[test]
+    fn inventory_rows_are_quoted_and_etags_unwrapped() {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        write_inventory_rows(&mut writer, &[InventoryRow {
+            key: "reports/q1, final.csv".to_string(),
+            size: 42,
+            last_modified: Some("2024-01-02T03:04:05Z".to_string()),
+            storage_class: None,
+            e_tag: Some("\"abc\"".to_string()),
+        }])
+        .unwrap();
+        let written = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(written, "\"reports/q1, final.csv\",42,2024-01-02T03:04:05Z,,abc\n");
+    }
+
+    #[test]
+    fn extensions_come_from_the_file_name() {
+        assert_eq!(key_extension("photos/2024/IMG_1.JPG"), "JPG");
+        assert_eq!(key_extension("archive.tar.gz"), "gz");
+        assert_eq!(key_extension("v1.2/README"), "(none)");
+        assert_eq!(key_extension("config/.env"), "(none)");
+        assert_eq!(key_extension("trailing."), "(none)");
+
+        let mut totals = HashMap::new();
+        totals.insert("txt".to_string(), (10, 100));
+        totals.insert("jpg".to_string(), (2, 5_000));
+        totals.insert("csv".to_string(), (1, 100));
+        let order: Vec<String> = sorted_extension_stats(totals).into_iter().map(|stats| stats.extension).collect();
+        assert_eq!(order, ["jpg", "txt", "csv"]);
+    }
+
+    #[test]
+    fn duplicate_candidates_use_md5_etags_and_defer_multipart() {
+        let md5 = "0123456789abcdef0123456789abcdef".to_string();
+        let (settled, to_hash) = candidate_duplicate_groups(vec![
+            ("a".into(), 10, md5.clone()),
+            ("b".into(), 10, md5.clone()),
+            ("c".into(), 10, "fedcba9876543210fedcba9876543210".into()),
+            ("d".into(), 20, format!("{}-2", md5)),
+            ("e".into(), 20, md5),
+            ("f".into(), 30, "ffffffffffffffffffffffffffffffff".into()),
+        ]);
+        assert_eq!(settled.len(), 1);
+        assert_eq!(settled[0].keys, ["a", "b"]);
+        assert_eq!(settled[0].wasted_bytes, 10);
+        assert_eq!(to_hash, vec![(20, vec!["d".to_string(), "e".to_string()])]);
+    }
+}
@@ -1,6 +1,8 @@
 use crate::commands::profiles::ProfileState;
+use crate::s3::client::AccelerateStatus;
 use crate::s3::{self, BucketInfo, S3State};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tauri::State;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -37,9 +39,14 @@ pub async fn list_buckets(
         .map_err(|e| e.to_string())?;
 
     // List buckets
-    let buckets = s3::client::list_buckets(client)
-        .await
-        .map_err(|e| e.to_string())?;
+    let buckets = s3::with_operation_timeout(
+        active_profile.operation_timeout(),
+        "ListBuckets",
+        s3::client::list_buckets(client),
+    )
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
 
     Ok(buckets)
 }
@@ -49,6 +56,42 @@ pub async fn list_buckets(
 pub async fn list_buckets_with_regions(
     profile_state: State<'_, ProfileState>,
     s3_state: State<'_, S3State>,
+) -> Result<Vec<BucketWithRegion>, String> {
+    buckets_with_regions(&profile_state, &s3_state).await
+}
+
+/// List buckets grouped by region, each group sorted by bucket name
+#[tauri::command]
+pub async fn list_buckets_by_region(
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<HashMap<String, Vec<BucketInfo>>, String> {
+    let buckets = buckets_with_regions(&profile_state, &s3_state).await?;
+
+    let mut grouped: HashMap<String, Vec<BucketInfo>> = HashMap::new();
+    for bucket in buckets {
+        grouped
+            .entry(bucket.region.clone())
+            .or_default()
+            .push(BucketInfo {
+                name: bucket.name,
+                region: Some(bucket.region),
+                creation_date: bucket.creation_date,
+                object_count: bucket.object_count,
+                total_size: bucket.total_size,
+                total_size_formatted: bucket.total_size_formatted,
+            });
+    }
+    for group in grouped.values_mut() {
+        group.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    Ok(grouped)
+}
+
+async fn buckets_with_regions(
+    profile_state: &ProfileState,
+    s3_state: &S3State,
 ) -> Result<Vec<BucketWithRegion>, String> {
     // Get active profile
     let profile_manager = profile_state.read().await;
@@ -68,25 +111,28 @@ pub async fn list_buckets_with_regions(
         .map_err(|e| e.to_string())?;
 
     // List buckets
-    let buckets = s3::client::list_buckets(client)
-        .await
-        .map_err(|e| e.to_string())?;
+    let buckets = s3::with_operation_timeout(
+        active_profile.operation_timeout(),
+        "ListBuckets",
+        s3::client::list_buckets(client),
+    )
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
 
-    // For custom endpoints (non-AWS providers like Linode, DigitalOcean, MinIO, etc.),
-    // the GetBucketLocation API is often unsupported and causes "dispatch failure" errors.
-    // Use the profile's configured region directly instead of querying per-bucket.
-    let is_custom_endpoint = matches!(
-        &active_profile.credential_type,
-        crate::credentials::CredentialType::CustomEndpoint { .. }
-    );
+    // Many S3-compatible providers (Linode, R2, B2, ...) don't support GetBucketLocation
+    // and fail with "dispatch failure" errors. Their quirk profile says to use the
+    // profile's configured region directly instead of querying per-bucket.
+    let skip_bucket_location = active_profile.skips_bucket_location();
     let profile_region = active_profile
         .region
         .clone()
         .unwrap_or_else(|| "us-east-1".to_string());
 
-    if is_custom_endpoint {
-        // Skip GetBucketLocation entirely for custom endpoints
+    if skip_bucket_location {
+        // Skip GetBucketLocation entirely for these providers
         s3_manager.set_bucket_regions(
+            &active_profile.id,
             buckets.iter().map(|bucket| bucket.name.as_str()),
             &profile_region,
         );
@@ -112,10 +158,11 @@ pub async fn list_buckets_with_regions(
         .into_iter()
         .map(|bucket| {
             let client_ref = client_clone.clone();
+            let profile_ref = &active_profile;
             let bucket_name = bucket.name.clone();
             let fallback = fallback_region.clone();
             async move {
-                let region = match s3::client::get_bucket_region(&client_ref, &bucket_name).await {
+                let region = match s3::client::resolve_bucket_region(&client_ref, profile_ref, &bucket_name).await {
                     Ok(r) => r,
                     Err(_) => fallback,
                 };
@@ -153,16 +200,13 @@ pub async fn get_bucket_region(
 
     drop(profile_manager);
 
-    if matches!(
-        &active_profile.credential_type,
-        crate::credentials::CredentialType::CustomEndpoint { .. }
-    ) {
+    if active_profile.skips_bucket_location() {
         let region = active_profile
             .region
             .clone()
             .unwrap_or_else(|| "us-east-1".to_string());
         let mut s3_manager = s3_state.write().await;
-        s3_manager.set_bucket_region(&bucket_name, region.clone());
+        s3_manager.set_bucket_region(&active_profile.id, &bucket_name, region.clone());
         return Ok(region);
     }
 
@@ -173,10 +217,47 @@ pub async fn get_bucket_region(
         .await
         .map_err(|e| e.to_string())?;
 
-    // Get region
-    s3::client::get_bucket_region(client, &bucket_name)
+    let client = client.clone();
+    drop(s3_manager);
+
+    let region = s3::client::resolve_bucket_region(&client, &active_profile, &bucket_name)
+        .await
+        .map_err(|e| e.to_string())?;
+    s3_state.write().await.set_bucket_region(&active_profile.id, &bucket_name, region.clone());
+    Ok(region)
+}
+
+/// Re-resolve a bucket's region via HeadBucket and cache it, ignoring both the
+/// cached value and the provider's skip-GetBucketLocation quirk
+#[tauri::command]
+pub async fn resolve_bucket_region(
+    bucket_name: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<String, String> {
+    let profile_manager = profile_state.read().await;
+    let active_profile = profile_manager
+        .get_active_profile()
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "No active profile selected".to_string())?;
+
+    drop(profile_manager);
+
+    let client = {
+        let mut s3_manager = s3_state.write().await;
+        s3_manager
+            .get_client(&active_profile)
+            .await
+            .map_err(|e| e.to_string())?
+            .clone()
+    };
+
+    let region = s3::client::resolve_bucket_region(&client, &active_profile, &bucket_name)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    s3_state.write().await.set_bucket_region(&active_profile.id, &bucket_name, region.clone());
+    Ok(region)
 }
 
 /// Refresh the S3 client (clear cache)
@@ -186,3 +267,544 @@ pub async fn refresh_s3_client(s3_state: State<'_, S3State>) -> Result<(), Strin
     s3_manager.clear_cache();
     Ok(())
 }
+
+/// Rebuild S3 clients after a settings change without discarding cached
+/// listings or bucket regions
+#[tauri::command]
+pub async fn rebuild_clients(s3_state: State<'_, S3State>) -> Result<usize, String> {
+    let mut s3_manager = s3_state.write().await;
+    let dropped = s3_manager.clear_clients();
+    log::info!("Dropped {} cached S3 clients; they will be rebuilt on next use", dropped);
+    Ok(dropped)
+}
+
+/// Check that a bucket can be used with Transfer Acceleration, failing with an
+/// explanation when it can't. The bucket's setting is read fresh.
+#[tauri::command]
+pub async fn check_transfer_acceleration(
+    bucket_name: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> crate::error::Result<()> {
+    use crate::error::AppError;
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    if active_profile.provider_quirks().is_some() {
+        return Err(AppError::ConfigError("Transfer Acceleration is only available on AWS".to_string()));
+    }
+    if bucket_name.contains('.') {
+        return Err(AppError::ConfigError(format!(
+            "Bucket '{}' has dots in its name, which Transfer Acceleration doesn't support",
+            bucket_name
+        )));
+    }
+
+    // The setting is read through the regional endpoint, whatever the profile uses
+    let client = {
+        let mut s3_manager = s3_state.write().await;
+        let region = s3_manager
+            .get_bucket_region(&active_profile.id, &bucket_name)
+            .or_else(|| active_profile.region.clone())
+            .unwrap_or_else(|| "us-east-1".to_string());
+        s3_manager.forget_accelerate_status(&active_profile.id, &bucket_name);
+        s3_manager.get_client_for_region(&active_profile, &region).await?.clone()
+    };
+    let status = match s3::client::read_accelerate_status(&client, &bucket_name).await {
+        Ok(status) => status,
+        Err(err) => {
+            log::warn!("check_transfer_acceleration failed, attempting region discovery: {}", err);
+            let retry = s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, None, |client| {
+                let bucket_name = &bucket_name;
+                async move { s3::client::read_accelerate_status(&client, bucket_name).await }
+            })
+            .await?;
+            match retry {
+                Some(retry) => retry.result?,
+                None => return Err(err),
+            }
+        }
+    };
+    s3_state
+        .write()
+        .await
+        .set_accelerate_status(&active_profile.id, &bucket_name, status);
+
+    match status {
+        AccelerateStatus::Enabled => Ok(()),
+        AccelerateStatus::Disabled => Err(s3::client::acceleration_disabled_error(&active_profile, &bucket_name)),
+        AccelerateStatus::Unknown => Err(AppError::AccessDenied(format!(
+            "This profile isn't allowed to read the Transfer Acceleration setting of bucket '{}'. \
+             Transfers to it will use the regional endpoint.",
+            bucket_name
+        ))),
+    }
+}
+
+/// The active profile, failing with `ProfileNotFound` when none is selected
+pub(crate) async fn require_active_profile(
+    profile_state: &ProfileState,
+) -> crate::error::Result<crate::credentials::Profile> {
+    profile_state
+        .read()
+        .await
+        .get_active_profile()
+        .await?
+        .ok_or_else(|| crate::error::AppError::ProfileNotFound("No active profile".into()))
+}
+
+/// Client for the bucket's cached region, falling back to `bucket_region` and
+/// then the profile's region. The client is addressed for the bucket, keeping
+/// path-style requests for dotted names and Transfer Acceleration. The region
+/// used, if known, is returned with it.
+pub(crate) async fn bucket_client(
+    s3_state: &S3State,
+    profile: &crate::credentials::Profile,
+    bucket_name: &str,
+    bucket_region: Option<String>,
+) -> crate::error::Result<(Option<String>, aws_sdk_s3::Client)> {
+    let bucket_region = s3_state
+        .read()
+        .await
+        .get_bucket_region(&profile.id, bucket_name)
+        .or(bucket_region);
+
+    let region = bucket_region
+        .clone()
+        .or_else(|| profile.region.clone())
+        .unwrap_or_else(|| "us-east-1".to_string());
+    let client = crate::s3::client_for_bucket(s3_state, profile, bucket_name, &region).await?;
+    Ok((bucket_region, client))
+}
+
+/// Send a bucket-level request with [`bucket_client`], repeating it in the
+/// bucket's own region when it went to the wrong one. The client that got the
+/// final answer is returned with it, for follow-up requests.
+async fn send_in_bucket_region<T, E, F, Fut>(
+    s3_state: &S3State,
+    profile: &crate::credentials::Profile,
+    bucket_name: &str,
+    mut request: F,
+) -> crate::error::Result<(aws_sdk_s3::Client, std::result::Result<T, BucketRequestError<E>>)>
+where
+    F: FnMut(aws_sdk_s3::Client) -> Fut,
+    Fut: std::future::Future<Output = std::result::Result<T, BucketRequestError<E>>>,
+    E: aws_sdk_s3::error::ProvideErrorMetadata + std::error::Error + 'static,
+{
+    let (_, client) = bucket_client(s3_state, profile, bucket_name, None).await?;
+    let err = match request(client.clone()).await {
+        Err(err) if s3::is_region_mismatch(&err) => err,
+        result => return Ok((client, result)),
+    };
+
+    log::warn!("Request to bucket '{}' went to the wrong region, retrying: {}", bucket_name, err);
+    let retry = s3::retry_in_bucket_region(s3_state, profile, bucket_name, s3::region_from_redirect(&err), request).await?;
+    Ok(match retry {
+        Some(retry) => (retry.client, retry.result),
+        None => (client, Err(err)),
+    })
+}
+
+type BucketRequestError<E> = aws_sdk_s3::error::SdkError<E, aws_sdk_s3::config::http::HttpResponse>;
+
+const ALL_USERS_GROUP: &str = "http://acs.amazonaws.com/groups/global/AllUsers";
+const AUTHENTICATED_USERS_GROUP: &str = "http://acs.amazonaws.com/groups/global/AuthenticatedUsers";
+
+#[derive(Debug, Default, Serialize)]
+pub struct PublicAccessReport {
+    pub bucket: String,
+    /// Anyone can read (or list) the bucket after the access block is applied
+    pub public_read: bool,
+    /// Anyone can write to the bucket or its ACL after the access block is applied
+    pub public_write: bool,
+    pub is_public: bool,
+    /// ACL grants to everyone or to any authenticated AWS user, e.g. "AllUsers: READ"
+    pub public_grants: Vec<String>,
+    /// Whether S3 evaluates the bucket policy as public; `None` when it couldn't be read
+    pub policy_is_public: Option<bool>,
+    pub block_public_acls: bool,
+    pub ignore_public_acls: bool,
+    pub block_public_policy: bool,
+    pub restrict_public_buckets: bool,
+    /// Checks that failed for reasons other than the configuration being absent
+    pub warnings: Vec<String>,
+}
+
+impl PublicAccessReport {
+    /// Fold the raw ACL and policy findings into the effective read/write flags,
+    /// honouring the public access block
+    fn summarize(&mut self, acl_read: bool, acl_write: bool) {
+        let acl_counts = !self.ignore_public_acls;
+        let policy_counts = self.policy_is_public.unwrap_or(false) && !self.restrict_public_buckets;
+        self.public_read = (acl_read && acl_counts) || policy_counts;
+        self.public_write = acl_write && acl_counts;
+        self.is_public = self.public_read || self.public_write;
+    }
+}
+
+/// Whether an S3 error just means the configuration was never set
+fn is_missing_configuration<E: aws_sdk_s3::error::ProvideErrorMetadata>(err: &E) -> bool {
+    matches!(
+        err.code(),
+        Some("NoSuchPublicAccessBlockConfiguration")
+            | Some("NoSuchBucketPolicy")
+            | Some("NoSuchBucketPolicyStatus")
+            | Some("ServerSideEncryptionConfigurationNotFoundError")
+    )
+}
+
+/// Check whether a bucket is publicly readable or writable, from its ACL, public
+/// access block and policy status. Missing configurations count as not public.
+#[tauri::command]
+pub async fn check_public_access(
+    bucket_name: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> crate::error::Result<PublicAccessReport> {
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    // The ACL read settles the bucket's region for the other two
+    let (client, acl) = send_in_bucket_region(&s3_state, &active_profile, &bucket_name, |client| {
+        let bucket_name = &bucket_name;
+        async move { client.get_bucket_acl().bucket(bucket_name).send().await }
+    })
+    .await?;
+
+    let (access_block, policy_status) = tokio::join!(
+        client.get_public_access_block().bucket(&bucket_name).send(),
+        client.get_bucket_policy_status().bucket(&bucket_name).send(),
+    );
+
+    let mut report = PublicAccessReport {
+        bucket: bucket_name.clone(),
+        ..Default::default()
+    };
+    let (mut acl_read, mut acl_write) = (false, false);
+
+    match acl {
+        Ok(output) => {
+            for grant in output.grants() {
+                let Some(uri) = grant.grantee().and_then(|g| g.uri()) else {
+                    continue;
+                };
+                let group = match uri {
+                    ALL_USERS_GROUP => "AllUsers",
+                    AUTHENTICATED_USERS_GROUP => "AuthenticatedUsers",
+                    _ => continue,
+                };
+                let permission = grant.permission().map(|p| p.as_str()).unwrap_or("UNKNOWN");
+                match permission {
+                    "READ" => acl_read = true,
+                    "WRITE" | "WRITE_ACP" => acl_write = true,
+                    "FULL_CONTROL" => {
+                        acl_read = true;
+                        acl_write = true;
+                    }
+                    _ => {}
+                }
+                report.public_grants.push(format!("{}: {}", group, permission));
+            }
+        }
+        Err(e) if is_missing_configuration(&e) => {}
+        Err(e) => report.warnings.push(format!("Could not read bucket ACL: {}", e)),
+    }
+
+    match access_block {
+        Ok(output) => {
+            if let Some(config) = output.public_access_block_configuration() {
+                report.block_public_acls = config.block_public_acls().unwrap_or(false);
+                report.ignore_public_acls = config.ignore_public_acls().unwrap_or(false);
+                report.block_public_policy = config.block_public_policy().unwrap_or(false);
+                report.restrict_public_buckets = config.restrict_public_buckets().unwrap_or(false);
+            }
+        }
+        Err(e) if is_missing_configuration(&e) => {}
+        Err(e) => report.warnings.push(format!("Could not read public access block: {}", e)),
+    }
+
+    match policy_status {
+        Ok(output) => {
+            report.policy_is_public = Some(
+                output
+                    .policy_status()
+                    .and_then(|status| status.is_public())
+                    .unwrap_or(false),
+            );
+        }
+        Err(e) if is_missing_configuration(&e) => report.policy_is_public = Some(false),
+        Err(e) => report.warnings.push(format!("Could not read bucket policy status: {}", e)),
+    }
+
+    report.summarize(acl_read, acl_write);
+    if report.is_public {
+        log::warn!("Bucket '{}' is publicly accessible: {:?}", bucket_name, report.public_grants);
+    }
+
+    Ok(report)
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotificationFilterRule {
+    /// `prefix` or `suffix`
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotificationTarget {
+    pub id: Option<String>,
+    pub arn: String,
+    pub events: Vec<String>,
+    pub filter_rules: Vec<NotificationFilterRule>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct BucketNotification {
+    pub bucket: String,
+    pub topics: Vec<NotificationTarget>,
+    pub queues: Vec<NotificationTarget>,
+    pub lambda_functions: Vec<NotificationTarget>,
+    pub event_bridge_enabled: bool,
+}
+
+fn notification_target(
+    id: Option<&str>,
+    arn: &str,
+    events: &[aws_sdk_s3::types::Event],
+    filter: Option<&aws_sdk_s3::types::NotificationConfigurationFilter>,
+) -> NotificationTarget {
+    let filter_rules = filter
+        .and_then(|f| f.key())
+        .map(|key| key.filter_rules())
+        .unwrap_or_default()
+        .iter()
+        .map(|rule| NotificationFilterRule {
+            name: rule.name().map(|n| n.as_str().to_string()).unwrap_or_default(),
+            value: rule.value().unwrap_or_default().to_string(),
+        })
+        .collect();
+
+    NotificationTarget {
+        id: id.map(str::to_string),
+        arn: arn.to_string(),
+        events: events.iter().map(|e| e.as_str().to_string()).collect(),
+        filter_rules,
+    }
+}
+
+/// Read a bucket's event notification targets (SNS, SQS, Lambda, EventBridge).
+/// A bucket without notifications returns an empty configuration.
+#[tauri::command]
+pub async fn get_bucket_notification(
+    bucket_name: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> crate::error::Result<BucketNotification> {
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let (_, output) = send_in_bucket_region(&s3_state, &active_profile, &bucket_name, |client| {
+        let bucket_name = &bucket_name;
+        async move { client.get_bucket_notification_configuration().bucket(bucket_name).send().await }
+    })
+    .await?;
+    let output = output.map_err(|e| {
+        crate::error::AppError::S3Error(format!(
+            "Failed to read notification configuration for '{}': {}",
+            bucket_name, e
+        ))
+    })?;
+
+    Ok(BucketNotification {
+        bucket: bucket_name,
+        topics: output
+            .topic_configurations()
+            .iter()
+            .map(|c| notification_target(c.id(), c.topic_arn(), c.events(), c.filter()))
+            .collect(),
+        queues: output
+            .queue_configurations()
+            .iter()
+            .map(|c| notification_target(c.id(), c.queue_arn(), c.events(), c.filter()))
+            .collect(),
+        lambda_functions: output
+            .lambda_function_configurations()
+            .iter()
+            .map(|c| notification_target(c.id(), c.lambda_function_arn(), c.events(), c.filter()))
+            .collect(),
+        event_bridge_enabled: output.event_bridge_configuration().is_some(),
+    })
+}
+
+/// Default encryption applied to new objects in a bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EncryptionMode {
+    /// S3-managed keys (`AES256`)
+    SseS3,
+    /// KMS keys (`aws:kms`)
+    SseKms,
+    /// Dual-layer KMS encryption (`aws:kms:dsse`)
+    DsseKms,
+}
+
+impl EncryptionMode {
+    fn algorithm(self) -> aws_sdk_s3::types::ServerSideEncryption {
+        use aws_sdk_s3::types::ServerSideEncryption;
+        match self {
+            EncryptionMode::SseS3 => ServerSideEncryption::Aes256,
+            EncryptionMode::SseKms => ServerSideEncryption::AwsKms,
+            EncryptionMode::DsseKms => ServerSideEncryption::AwsKmsDsse,
+        }
+    }
+
+    fn from_algorithm(algorithm: &aws_sdk_s3::types::ServerSideEncryption) -> Option<Self> {
+        use aws_sdk_s3::types::ServerSideEncryption;
+        match algorithm {
+            ServerSideEncryption::Aes256 => Some(EncryptionMode::SseS3),
+            ServerSideEncryption::AwsKms => Some(EncryptionMode::SseKms),
+            ServerSideEncryption::AwsKmsDsse => Some(EncryptionMode::DsseKms),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BucketEncryption {
+    pub bucket: String,
+    /// `None` when the bucket has no default encryption
+    pub mode: Option<EncryptionMode>,
+    /// KMS key ID or ARN; `None` with a KMS mode means the AWS managed `aws/s3` key
+    pub kms_key_id: Option<String>,
+    /// Whether S3 Bucket Keys cut down KMS requests for new objects
+    pub bucket_key_enabled: bool,
+}
+
+/// Read a bucket's default encryption. A bucket without one returns `mode: None`.
+#[tauri::command]
+pub async fn get_bucket_encryption(
+    bucket_name: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> crate::error::Result<BucketEncryption> {
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let (_, output) = send_in_bucket_region(&s3_state, &active_profile, &bucket_name, |client| {
+        let bucket_name = &bucket_name;
+        async move { client.get_bucket_encryption().bucket(bucket_name).send().await }
+    })
+    .await?;
+    let output = match output {
+        Ok(output) => output,
+        Err(err) => {
+            let err = err.into_service_error();
+            if is_missing_configuration(&err) {
+                return Ok(BucketEncryption {
+                    bucket: bucket_name,
+                    mode: None,
+                    kms_key_id: None,
+                    bucket_key_enabled: false,
+                });
+            }
+            return Err(crate::error::AppError::S3Error(format!(
+                "Failed to read default encryption for '{}': {}",
+                bucket_name, err
+            )));
+        }
+    };
+
+    let rule = output
+        .server_side_encryption_configuration()
+        .and_then(|config| config.rules().first());
+    let default = rule.and_then(|rule| rule.apply_server_side_encryption_by_default());
+
+    Ok(BucketEncryption {
+        bucket: bucket_name,
+        mode: default.and_then(|default| EncryptionMode::from_algorithm(default.sse_algorithm())),
+        kms_key_id: default.and_then(|default| default.kms_master_key_id()).map(str::to_string),
+        bucket_key_enabled: rule.and_then(|rule| rule.bucket_key_enabled()).unwrap_or(false),
+    })
+}
+
+/// Set a bucket's default encryption. A KMS key ID is only accepted with a KMS
+/// mode; leaving it out uses the AWS managed `aws/s3` key.
+#[tauri::command]
+pub async fn put_bucket_encryption(
+    bucket_name: String,
+    mode: EncryptionMode,
+    kms_key_id: Option<String>,
+    bucket_key_enabled: Option<bool>,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> crate::error::Result<()> {
+    use crate::error::AppError;
+    use aws_sdk_s3::types::{ServerSideEncryptionByDefault, ServerSideEncryptionConfiguration, ServerSideEncryptionRule};
+
+    let kms_key_id = kms_key_id
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty());
+    if mode == EncryptionMode::SseS3 && kms_key_id.is_some() {
+        return Err(AppError::InvalidContent("A KMS key can only be set with KMS encryption".to_string()));
+    }
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let default = ServerSideEncryptionByDefault::builder()
+        .sse_algorithm(mode.algorithm())
+        .set_kms_master_key_id(kms_key_id)
+        .build()
+        .map_err(|e| AppError::InvalidContent(e.to_string()))?;
+    let rule = ServerSideEncryptionRule::builder()
+        .apply_server_side_encryption_by_default(default)
+        .bucket_key_enabled(bucket_key_enabled.unwrap_or(false))
+        .build();
+    let configuration = ServerSideEncryptionConfiguration::builder()
+        .rules(rule)
+        .build()
+        .map_err(|e| AppError::InvalidContent(e.to_string()))?;
+
+    let (_, output) = send_in_bucket_region(&s3_state, &active_profile, &bucket_name, |client| {
+        let (bucket_name, configuration) = (&bucket_name, configuration.clone());
+        async move {
+            client
+                .put_bucket_encryption()
+                .bucket(bucket_name)
+                .server_side_encryption_configuration(configuration)
+                .send()
+                .await
+        }
+    })
+    .await?;
+    output.map_err(|e| {
+        AppError::S3Error(format!(
+            "Failed to set default encryption for '{}': {}",
+            bucket_name,
+            e.into_service_error()
+        ))
+    })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PublicAccessReport;
+
+    #[test]
+    fn access_block_overrides_public_acls_and_policy() {
+        let mut report = PublicAccessReport {
+            policy_is_public: Some(true),
+            ..Default::default()
+        };
+        report.summarize(true, true);
+        assert!(report.public_read && report.public_write && report.is_public);
+
+        let mut blocked = PublicAccessReport {
+            policy_is_public: Some(true),
+            ignore_public_acls: true,
+            restrict_public_buckets: true,
+            ..Default::default()
+        };
+        blocked.summarize(true, true);
+        assert!(!blocked.is_public);
+    }
+}
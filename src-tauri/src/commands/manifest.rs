@@ -0,0 +1,598 @@
+use crate::activity::{s3_target, ActivityState};
+use crate::commands::buckets::{bucket_client, require_active_profile};
+use crate::commands::operations::{build_tagging, copy_within_bucket, parse_storage_class};
+use crate::commands::profiles::ProfileState;
+use crate::error::Result;
+use crate::s3::S3State;
+use aws_sdk_s3::Client;
+use aws_sdk_s3::types::{StorageClass, Tagging};
+use futures::StreamExt;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Emitter, State};
+use tokio::io::AsyncWriteExt;
+
+type TagManifest = BTreeMap<String, BTreeMap<String, String>>;
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum JsonTagManifest {
+    Map(TagManifest),
+    List(Vec<JsonTagManifestEntry>),
+}
+
+#[derive(serde::Deserialize)]
+struct JsonTagManifestEntry {
+    key: String,
+    tags: BTreeMap<String, String>,
+}
+
+fn parse_json_tag_manifest(content: &str) -> Result<TagManifest> {
+    let manifest: JsonTagManifest = serde_json::from_str(content)?;
+    Ok(match manifest {
+        JsonTagManifest::Map(map) => map,
+        JsonTagManifest::List(entries) => {
+            let mut map = TagManifest::new();
+            for entry in entries {
+                map.entry(entry.key).or_default().extend(entry.tags);
+            }
+            map
+        }
+    })
+}
+
+/// Parse a CSV tag manifest. The first column is the object key. Either the header
+/// is `key,tags` with `name=value` pairs separated by `;`, or every other header is
+/// a tag name and its cells are the values (empty cells are skipped).
+fn parse_csv_tag_manifest(content: &str) -> Result<TagManifest> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|e| crate::error::AppError::InvalidContent(format!("Invalid manifest header: {}", e)))?
+        .clone();
+    if headers.len() < 2 {
+        return Err(crate::error::AppError::InvalidContent(
+            "Manifest needs a key column and at least one tag column".into(),
+        ));
+    }
+    let packed_tags = headers.len() == 2 && headers[1].eq_ignore_ascii_case("tags");
+
+    let mut manifest = TagManifest::new();
+    for (index, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| {
+            crate::error::AppError::InvalidContent(format!("Invalid manifest row {}: {}", index + 2, e))
+        })?;
+        let Some(key) = record.get(0).filter(|key| !key.is_empty()) else {
+            continue;
+        };
+        let tags = manifest.entry(key.to_string()).or_default();
+
+        if packed_tags {
+            for pair in record.get(1).unwrap_or_default().split(';').filter(|pair| !pair.trim().is_empty()) {
+                let (name, value) = pair.split_once('=').ok_or_else(|| {
+                    crate::error::AppError::InvalidContent(format!(
+                        "Invalid tag '{}' on row {}, expected name=value",
+                        pair, index + 2
+                    ))
+                })?;
+                tags.insert(name.trim().to_string(), value.trim().to_string());
+            }
+        } else {
+            for (name, value) in headers.iter().zip(record.iter()).skip(1) {
+                if !name.is_empty() && !value.is_empty() {
+                    tags.insert(name.to_string(), value.to_string());
+                }
+            }
+        }
+    }
+
+    Ok(manifest)
+}
+
+#[derive(serde::Serialize)]
+pub struct TagManifestResult {
+    pub success: bool,
+    pub tag_count: usize,
+    pub error: Option<String>,
+}
+
+const TAG_MANIFEST_CONCURRENCY: usize = 8;
+
+/// Apply tag sets from a local CSV or JSON manifest, replacing each object's tags.
+#[tauri::command]
+pub async fn apply_tags_from_manifest(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    manifest_path: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<HashMap<String, TagManifestResult>> {
+    let content = tokio::fs::read_to_string(&manifest_path).await?;
+    let is_json = Path::new(&manifest_path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let manifest = if is_json {
+        parse_json_tag_manifest(&content)?
+    } else {
+        parse_csv_tag_manifest(&content)?
+    };
+
+    let mut results = HashMap::new();
+    let mut entries = Vec::with_capacity(manifest.len());
+    for (key, tags) in manifest {
+        // Reject bad tag sets up front so they don't cost a request
+        match build_tagging(tags.iter()) {
+            Ok(tagging) => entries.push((key, tags.len(), tagging)),
+            Err(err) => {
+                results.insert(key, TagManifestResult {
+                    success: false,
+                    tag_count: tags.len(),
+                    error: Some(err.to_string()),
+                });
+            }
+        }
+    }
+    if entries.is_empty() {
+        return Ok(results);
+    }
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let (_, mut client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
+    // Tag the first object on its own so a region mismatch is fixed once,
+    // before the rest go out concurrently.
+    let mut remaining = entries.into_iter();
+    if let Some((key, tag_count, tagging)) = remaining.next() {
+        let first = client
+            .put_object_tagging()
+            .bucket(&bucket_name)
+            .key(&key)
+            .tagging(tagging.clone())
+            .send()
+            .await;
+
+        let outcome = match first {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                log::warn!("put_object_tagging failed, attempting region discovery: {}", err);
+                let redirect_region = crate::s3::region_from_redirect(&err);
+                let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, redirect_region, |client| {
+                    client
+                        .put_object_tagging()
+                        .bucket(&bucket_name)
+                        .key(&key)
+                        .tagging(tagging.clone())
+                        .send()
+                }).await?;
+
+                match retry {
+                    Some(retry) => {
+                        client = retry.client;
+                        retry.result.map(|_| ()).map_err(|e| e.to_string())
+                    }
+                    None => Err(err.to_string()),
+                }
+            }
+        };
+
+        results.insert(key, TagManifestResult {
+            success: outcome.is_ok(),
+            tag_count,
+            error: outcome.err(),
+        });
+    }
+
+    let outcomes: Vec<(String, TagManifestResult)> = futures::stream::iter(remaining)
+        .map(|(key, tag_count, tagging)| {
+            let client = client.clone();
+            let bucket_name = bucket_name.clone();
+            async move {
+                let result = client
+                    .put_object_tagging()
+                    .bucket(&bucket_name)
+                    .key(&key)
+                    .tagging(tagging)
+                    .send()
+                    .await;
+                let error = result.err().map(|e| e.to_string());
+                (key, TagManifestResult { success: error.is_none(), tag_count, error })
+            }
+        })
+        .buffer_unordered(TAG_MANIFEST_CONCURRENCY)
+        .collect()
+        .await;
+    results.extend(outcomes);
+
+    Ok(results)
+}
+
+/// Read the object keys from a CSV manifest: the first column of every row, with
+/// an optional `key` header. Duplicates are dropped, keeping the first occurrence.
+fn parse_key_manifest(content: &str) -> Result<Vec<String>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+
+    // Keys are used as written: S3 keys may start or end with spaces
+    let mut seen = HashSet::new();
+    let mut keys = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| {
+            crate::error::AppError::InvalidContent(format!("Invalid manifest row {}: {}", index + 1, e))
+        })?;
+        let Some(key) = record.get(0).filter(|key| !key.is_empty()) else {
+            continue;
+        };
+        if index == 0 && key.trim().eq_ignore_ascii_case("key") {
+            continue;
+        }
+        if seen.insert(key.to_string()) {
+            keys.push(key.to_string());
+        }
+    }
+    Ok(keys)
+}
+
+enum ManifestAction {
+    /// Download into a local folder, replacing existing files only with `overwrite`
+    Download { root: std::path::PathBuf, overwrite: bool },
+    Delete,
+    Tag(Tagging),
+    ChangeClass(StorageClass),
+}
+
+impl ManifestAction {
+    fn mutates_bucket(&self) -> bool {
+        !matches!(self, ManifestAction::Download { .. })
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct KeyManifestResult {
+    pub success: bool,
+    /// The key was left alone, e.g. a download whose local file already exists
+    pub skipped: bool,
+    pub error: Option<String>,
+}
+
+impl KeyManifestResult {
+    fn from_outcome(outcome: std::result::Result<bool, String>) -> Self {
+        Self {
+            success: outcome.is_ok(),
+            skipped: outcome == Ok(false),
+            error: outcome.err(),
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct KeyManifestProgress {
+    pub action: String,
+    pub total: usize,
+    pub processed: usize,
+    pub succeeded: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+impl KeyManifestProgress {
+    fn record(&mut self, outcome: &std::result::Result<bool, String>) {
+        match outcome {
+            Ok(true) => self.succeeded += 1,
+            Ok(false) => self.skipped += 1,
+            Err(_) => self.failed += 1,
+        }
+        self.processed += 1;
+    }
+}
+
+const KEY_MANIFEST_CONCURRENCY: usize = 8;
+
+/// Why an action failed on one key
+#[derive(Debug, PartialEq)]
+struct ActionFailure {
+    message: String,
+    /// The request went to the wrong region, so repeating it in the bucket's own
+    /// region could succeed
+    wrong_region: bool,
+    /// The bucket's region, when the failed request named it
+    redirect_region: Option<String>,
+}
+
+impl ActionFailure {
+    fn other(err: impl ToString) -> Self {
+        Self {
+            message: err.to_string(),
+            wrong_region: false,
+            redirect_region: None,
+        }
+    }
+
+    fn request<E>(err: aws_sdk_s3::error::SdkError<E, aws_sdk_s3::config::http::HttpResponse>) -> Self
+    where
+        E: aws_sdk_s3::error::ProvideErrorMetadata + std::error::Error + 'static,
+    {
+        Self {
+            wrong_region: crate::s3::is_region_mismatch(&err),
+            redirect_region: crate::s3::region_from_redirect(&err),
+            message: err.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ActionFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Run `action` on one key. Returns false when the key was skipped.
+async fn run_manifest_action(
+    client: &Client,
+    bucket_name: &str,
+    key: &str,
+    action: &ManifestAction,
+) -> std::result::Result<bool, ActionFailure> {
+    match action {
+        ManifestAction::Download { root, overwrite } => {
+            let relative = Path::new(key.trim_start_matches('/'));
+            if relative.components().any(|c| !matches!(c, std::path::Component::Normal(_))) {
+                return Err(ActionFailure::other(format!("Key '{}' can't be mapped to a local path", key)));
+            }
+            let local_path = root.join(relative);
+            if !overwrite && tokio::fs::try_exists(&local_path).await.unwrap_or(false) {
+                return Ok(false);
+            }
+            if let Some(parent) = local_path.parent() {
+                tokio::fs::create_dir_all(parent).await.map_err(ActionFailure::other)?;
+            }
+
+            let mut output = client
+                .get_object()
+                .bucket(bucket_name)
+                .key(key)
+                .send()
+                .await
+                .map_err(ActionFailure::request)?;
+            // Without `overwrite`, a file that appeared since the check above is still kept
+            let opened = tokio::fs::OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .create_new(!overwrite)
+                .open(&local_path)
+                .await;
+            let mut file = match opened {
+                Ok(file) => file,
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => return Ok(false),
+                Err(e) => return Err(ActionFailure::other(e)),
+            };
+            while let Some(bytes) = output.body.try_next().await.map_err(ActionFailure::other)? {
+                file.write_all(&bytes).await.map_err(ActionFailure::other)?;
+            }
+            file.flush().await.map_err(ActionFailure::other)?;
+            Ok(true)
+        }
+        ManifestAction::Delete => client
+            .delete_object()
+            .bucket(bucket_name)
+            .key(key)
+            .send()
+            .await
+            .map(|_| true)
+            .map_err(ActionFailure::request),
+        ManifestAction::Tag(tagging) => client
+            .put_object_tagging()
+            .bucket(bucket_name)
+            .key(key)
+            .tagging(tagging.clone())
+            .send()
+            .await
+            .map(|_| true)
+            .map_err(ActionFailure::request),
+        ManifestAction::ChangeClass(storage_class) => copy_within_bucket(client, bucket_name, key, key, Some(storage_class.clone()))
+            .await
+            .map(|_| true)
+            .map_err(ActionFailure::other),
+    }
+}
+
+/// Run one action over every key listed in a local CSV manifest. `action` is
+/// `download` (into `local_path`, skipping files that already exist there unless
+/// `overwrite` is set), `delete`, `tag` (replacing each object's tags
+/// with `tags`) or `change-class` (to `storage_class`). Progress is emitted as
+/// `key-manifest-progress` after every key. Cancelling through `cancel_operation`
+/// fails the keys not yet started.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn run_key_manifest(
+    app_handle: AppHandle,
+    bucket_name: String,
+    bucket_region: Option<String>,
+    manifest_path: String,
+    action: String,
+    local_path: Option<String>,
+    overwrite: Option<bool>,
+    tags: Option<HashMap<String, String>>,
+    storage_class: Option<String>,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    activity_state: State<'_, ActivityState>,
+) -> Result<HashMap<String, KeyManifestResult>> {
+    let action_name = action.trim().to_ascii_lowercase().replace('_', "-");
+    let action = match action_name.as_str() {
+        "download" => {
+            let local_path = local_path.ok_or_else(|| {
+                crate::error::AppError::InvalidContent("local_path is required for download".into())
+            })?;
+            let root = std::path::PathBuf::from(local_path);
+            if root.components().any(|c| matches!(c, std::path::Component::ParentDir)) {
+                return Err(crate::error::AppError::IoError(
+                    "Invalid path: contains parent directory reference".to_string(),
+                ));
+            }
+            ManifestAction::Download { root, overwrite: overwrite.unwrap_or(false) }
+        }
+        "delete" => ManifestAction::Delete,
+        "tag" => {
+            // An empty tag set would wipe every tag on every listed key
+            let tags: BTreeMap<String, String> = tags
+                .filter(|tags| !tags.is_empty())
+                .ok_or_else(|| crate::error::AppError::InvalidContent("tags are required for tag".into()))?
+                .into_iter()
+                .collect();
+            ManifestAction::Tag(build_tagging(tags.iter())?)
+        }
+        "change-class" => {
+            let storage_class = storage_class.ok_or_else(|| {
+                crate::error::AppError::InvalidContent("storage_class is required for change-class".into())
+            })?;
+            ManifestAction::ChangeClass(parse_storage_class(&storage_class)?)
+        }
+        other => {
+            return Err(crate::error::AppError::InvalidContent(format!(
+                "Unknown action '{}'. Use download, delete, tag or change-class.",
+                other
+            )))
+        }
+    };
+
+    let content = tokio::fs::read_to_string(&manifest_path).await?;
+    let keys = parse_key_manifest(&content)?;
+    let mut results = HashMap::new();
+    if keys.is_empty() {
+        return Ok(results);
+    }
+    let operation = activity_state.register("Key manifest", uuid::Uuid::new_v4().to_string(), s3_target(&bucket_name, ""));
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let (_, mut client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
+    let mut progress = KeyManifestProgress {
+        action: action_name,
+        total: keys.len(),
+        processed: 0,
+        succeeded: 0,
+        skipped: 0,
+        failed: 0,
+    };
+
+    // Run the first key on its own so a region mismatch is fixed once, before
+    // the rest go out concurrently.
+    let mut remaining = keys.into_iter();
+    if let Some(key) = remaining.next() {
+        let mut outcome = run_manifest_action(&client, &bucket_name, &key, &action).await;
+        if let Some(failure) = outcome.as_ref().err().filter(|failure| failure.wrong_region) {
+            log::warn!("Manifest action failed on '{}', attempting region discovery: {}", key, failure);
+            let redirect_region = failure.redirect_region.clone();
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, redirect_region, |client| {
+                let (bucket_name, key, action) = (&bucket_name, &key, &action);
+                async move { run_manifest_action(&client, bucket_name, key, action).await }
+            }).await?;
+            if let Some(retry) = retry {
+                client = retry.client;
+                outcome = retry.result;
+            }
+        }
+        let outcome = outcome.map_err(|failure| failure.message);
+
+        progress.record(&outcome);
+        let _ = app_handle.emit("key-manifest-progress", progress.clone());
+        results.insert(key, KeyManifestResult::from_outcome(outcome));
+    }
+
+    let action_ref = &action;
+    let client_ref = &client;
+    let bucket_ref = bucket_name.as_str();
+    let cancelled = operation.cancel_flag();
+    let mut outcomes = futures::stream::iter(remaining)
+        .map(|key| async move {
+            if cancelled.load(Ordering::Relaxed) {
+                return (key, Err("Cancelled before running".to_string()));
+            }
+            let outcome = run_manifest_action(client_ref, bucket_ref, &key, action_ref).await;
+            (key, outcome.map_err(|failure| failure.message))
+        })
+        .buffer_unordered(KEY_MANIFEST_CONCURRENCY);
+
+    while let Some((key, outcome)) = outcomes.next().await {
+        progress.record(&outcome);
+        let _ = app_handle.emit("key-manifest-progress", progress.clone());
+        results.insert(key, KeyManifestResult::from_outcome(outcome));
+    }
+    drop(outcomes);
+
+    if action.mutates_bucket() {
+        let mut s3_manager = s3_state.write().await;
+        s3_manager.remove_bucket_cache(&active_profile.id, &bucket_name);
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_csv_tag_manifest, parse_json_tag_manifest, parse_key_manifest, run_manifest_action, ManifestAction};
+    use crate::s3::test_server::TestS3;
+
+    #[test]
+    fn csv_manifest_supports_tag_columns_and_packed_tags() {
+        let columns = parse_csv_tag_manifest("key,team,env\na.txt,data,prod\nb.txt,,dev\n").unwrap();
+        assert_eq!(columns["a.txt"].len(), 2);
+        assert_eq!(columns["b.txt"].get("env").map(String::as_str), Some("dev"));
+        assert!(!columns["b.txt"].contains_key("team"));
+
+        let packed = parse_csv_tag_manifest("key,tags\n\"dir/a b.txt\",team=data; env=prod\n").unwrap();
+        assert_eq!(packed["dir/a b.txt"].get("env").map(String::as_str), Some("prod"));
+    }
+
+    #[test]
+    fn json_manifest_accepts_map_or_list() {
+        let map = parse_json_tag_manifest(r#"{"a.txt": {"team": "data"}}"#).unwrap();
+        assert_eq!(map["a.txt"]["team"], "data");
+
+        let list = parse_json_tag_manifest(r#"[{"key": "a.txt", "tags": {"env": "prod"}}]"#).unwrap();
+        assert_eq!(list["a.txt"]["env"], "prod");
+    }
+
+    #[test]
+    fn key_manifest_reads_first_column_and_skips_header_and_duplicates() {
+        let keys = parse_key_manifest("key,note\nlogs/a.txt,old\n\nlogs/b.txt\nlogs/a.txt,again\n").unwrap();
+        assert_eq!(keys, vec!["logs/a.txt", "logs/b.txt"]);
+
+        let keys = parse_key_manifest("\"a,b.txt\"\nc.txt\n").unwrap();
+        assert_eq!(keys, vec!["a,b.txt", "c.txt"]);
+
+        let keys = parse_key_manifest(" key \n padded.txt \n").unwrap();
+        assert_eq!(keys, vec![" padded.txt "]);
+    }
+
+    #[tokio::test]
+    async fn manifest_downloads_keep_existing_files_unless_overwriting() {
+        let server = TestS3::start(|_, _| (200, "fresh".to_string())).await;
+        let client = server.client();
+        let root = std::env::temp_dir().join(format!("brows3-manifest-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("logs")).unwrap();
+        std::fs::write(root.join("logs/a.txt"), "local").unwrap();
+
+        let keep = ManifestAction::Download { root: root.clone(), overwrite: false };
+        assert_eq!(run_manifest_action(&client, "b", "logs/a.txt", &keep).await, Ok(false));
+        assert_eq!(run_manifest_action(&client, "b", "logs/b.txt", &keep).await, Ok(true));
+        assert_eq!(std::fs::read_to_string(root.join("logs/a.txt")).unwrap(), "local");
+        assert_eq!(std::fs::read_to_string(root.join("logs/b.txt")).unwrap(), "fresh");
+
+        let replace = ManifestAction::Download { root: root.clone(), overwrite: true };
+        assert_eq!(run_manifest_action(&client, "b", "logs/a.txt", &replace).await, Ok(true));
+        assert_eq!(std::fs::read_to_string(root.join("logs/a.txt")).unwrap(), "fresh");
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
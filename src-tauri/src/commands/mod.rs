@@ -2,4 +2,8 @@ pub mod profiles;
 pub mod buckets;
 pub mod objects;
 pub mod operations;
+pub mod analytics;
+pub mod object_lock;
+pub mod manifest;
 pub mod transfer;
+pub mod settings;
@@ -0,0 +1,227 @@
+use crate::activity::{s3_target, ActivityState};
+use crate::commands::buckets::require_active_profile;
+use crate::commands::operations::ObjectPages;
+use crate::commands::profiles::ProfileState;
+use crate::error::Result;
+use crate::s3::S3State;
+use aws_sdk_s3::Client;
+use aws_sdk_s3::types::{ObjectLockLegalHold, ObjectLockLegalHoldStatus, ObjectLockRetention, ObjectLockRetentionMode};
+use futures::StreamExt;
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter, State};
+
+#[derive(serde::Serialize)]
+pub struct ObjectLockChangeResult {
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct ObjectLockProgress {
+    pub bucket: String,
+    pub prefix: String,
+    pub processed: usize,
+    pub failed: usize,
+}
+
+const OBJECT_LOCK_CONCURRENCY: usize = 8;
+
+#[derive(Clone)]
+enum ObjectLockChange {
+    Retention {
+        mode: ObjectLockRetentionMode,
+        retain_until: aws_sdk_s3::primitives::DateTime,
+        bypass_governance: bool,
+    },
+    LegalHold(ObjectLockLegalHoldStatus),
+}
+
+impl ObjectLockChange {
+    async fn apply(&self, client: &Client, bucket_name: &str, key: &str) -> std::result::Result<(), String> {
+        match self {
+            ObjectLockChange::Retention { mode, retain_until, bypass_governance } => client
+                .put_object_retention()
+                .bucket(bucket_name)
+                .key(key)
+                .retention(
+                    ObjectLockRetention::builder()
+                        .mode(mode.clone())
+                        .retain_until_date(*retain_until)
+                        .build(),
+                )
+                .bypass_governance_retention(*bypass_governance)
+                .send()
+                .await
+                .map(|_| ())
+                .map_err(|e| e.into_service_error().to_string()),
+            ObjectLockChange::LegalHold(status) => client
+                .put_object_legal_hold()
+                .bucket(bucket_name)
+                .key(key)
+                .legal_hold(ObjectLockLegalHold::builder().status(status.clone()).build())
+                .send()
+                .await
+                .map(|_| ())
+                .map_err(|e| e.into_service_error().to_string()),
+        }
+    }
+}
+
+fn parse_retention_mode(mode: &str) -> Result<ObjectLockRetentionMode> {
+    match mode.trim().to_ascii_uppercase().as_str() {
+        "GOVERNANCE" => Ok(ObjectLockRetentionMode::Governance),
+        "COMPLIANCE" => Ok(ObjectLockRetentionMode::Compliance),
+        other => Err(crate::error::AppError::InvalidContent(format!(
+            "Unsupported retention mode '{}'. Use GOVERNANCE or COMPLIANCE.",
+            other
+        ))),
+    }
+}
+
+/// Parse an RFC 3339 retain-until date, which must be later than `now`
+fn parse_retain_until(
+    value: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<chrono::DateTime<chrono::FixedOffset>> {
+    let retain_until = chrono::DateTime::parse_from_rfc3339(value.trim()).map_err(|e| {
+        crate::error::AppError::InvalidContent(format!("Invalid retain-until date '{}': {}", value, e))
+    })?;
+    if retain_until <= now {
+        return Err(crate::error::AppError::InvalidContent(format!(
+            "Retain-until date '{}' is in the past",
+            value
+        )));
+    }
+    Ok(retain_until)
+}
+
+/// Apply an object-lock change to the current version of every object under a
+/// prefix, emitting `object-lock-progress` after each listing page
+#[allow(clippy::too_many_arguments)]
+async fn apply_object_lock_to_prefix(
+    app_handle: &AppHandle,
+    bucket_name: &str,
+    bucket_region: Option<String>,
+    prefix: &str,
+    change: ObjectLockChange,
+    profile_state: &State<'_, ProfileState>,
+    s3_state: &State<'_, S3State>,
+    activity_state: &State<'_, ActivityState>,
+) -> Result<HashMap<String, ObjectLockChangeResult>> {
+    let kind = match change {
+        ObjectLockChange::Retention { .. } => "Retention change",
+        ObjectLockChange::LegalHold(_) => "Legal hold change",
+    };
+    let operation = activity_state.register(kind, uuid::Uuid::new_v4().to_string(), s3_target(bucket_name, prefix));
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let mut pages = ObjectPages::new(s3_state, &active_profile, bucket_name, bucket_region, prefix).await?;
+
+    let mut results = HashMap::new();
+    let mut progress = ObjectLockProgress {
+        bucket: bucket_name.to_string(),
+        prefix: prefix.to_string(),
+        processed: 0,
+        failed: 0,
+    };
+    loop {
+        operation.check()?;
+        let Some(page) = pages.next_page().await? else {
+            break;
+        };
+
+        let keys: Vec<String> = page
+            .contents()
+            .iter()
+            .filter_map(|object| object.key())
+            .filter(|key| !key.ends_with('/'))
+            .map(str::to_string)
+            .collect();
+
+        let outcomes: Vec<(String, Option<String>)> = futures::stream::iter(keys)
+            .map(|key| {
+                let client = pages.client().clone();
+                let change = change.clone();
+                async move {
+                    let error = change.apply(&client, bucket_name, &key).await.err();
+                    (key, error)
+                }
+            })
+            .buffer_unordered(OBJECT_LOCK_CONCURRENCY)
+            .collect()
+            .await;
+
+        for (key, error) in outcomes {
+            progress.processed += 1;
+            if error.is_some() {
+                progress.failed += 1;
+            }
+            results.insert(key, ObjectLockChangeResult {
+                success: error.is_none(),
+                error,
+            });
+        }
+        let _ = app_handle.emit("object-lock-progress", progress.clone());
+    }
+
+    Ok(results)
+}
+
+/// Put every object under a prefix under retention until `retain_until`
+/// (RFC 3339, in the future). The bucket must have object lock enabled.
+/// Shortening or removing GOVERNANCE retention needs `bypass_governance`;
+/// COMPLIANCE retention can only be extended.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn set_retention_for_prefix(
+    app_handle: AppHandle,
+    bucket_name: String,
+    bucket_region: Option<String>,
+    prefix: String,
+    mode: String,
+    retain_until: String,
+    bypass_governance: Option<bool>,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    activity_state: State<'_, ActivityState>,
+) -> Result<HashMap<String, ObjectLockChangeResult>> {
+    let mode = parse_retention_mode(&mode)?;
+    let retain_until = parse_retain_until(&retain_until, chrono::Utc::now())?;
+    let change = ObjectLockChange::Retention {
+        mode,
+        retain_until: aws_sdk_s3::primitives::DateTime::from_secs(retain_until.timestamp()),
+        bypass_governance: bypass_governance.unwrap_or(false),
+    };
+
+    apply_object_lock_to_prefix(&app_handle, &bucket_name, bucket_region, &prefix, change, &profile_state, &s3_state, &activity_state).await
+}
+
+/// Release the legal hold on every object under a prefix
+#[tauri::command]
+pub async fn clear_legal_hold_for_prefix(
+    app_handle: AppHandle,
+    bucket_name: String,
+    bucket_region: Option<String>,
+    prefix: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    activity_state: State<'_, ActivityState>,
+) -> Result<HashMap<String, ObjectLockChangeResult>> {
+    let change = ObjectLockChange::LegalHold(ObjectLockLegalHoldStatus::Off);
+    apply_object_lock_to_prefix(&app_handle, &bucket_name, bucket_region, &prefix, change, &profile_state, &s3_state, &activity_state).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_retain_until;
+    use crate::error::AppError;
+
+    #[test]
+    fn retain_until_must_be_in_the_future() {
+        let now = chrono::DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z").unwrap().to_utc();
+        assert!(parse_retain_until("2026-06-01T00:00:00+02:00", now).is_ok());
+        assert!(matches!(parse_retain_until("2025-12-31T23:59:59Z", now), Err(AppError::InvalidContent(_))));
+        assert!(matches!(parse_retain_until("2026-01-01T00:00:00Z", now), Err(AppError::InvalidContent(_))));
+        assert!(matches!(parse_retain_until("next week", now), Err(AppError::InvalidContent(_))));
+    }
+}
@@ -1,9 +1,12 @@
+use crate::activity::{s3_target, ActivityState, OperationHandle};
+use crate::commands::buckets::{bucket_client, require_active_profile};
 use crate::commands::profiles::ProfileState;
 use crate::s3::{FolderContent, S3Object, S3State};
 use crate::error::Result;
 use aws_sdk_s3::Client;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
+use std::io::{Read, Write};
 use tauri::State;
 
 fn is_likely_binary_text_mismatch(bytes: &[u8]) -> bool {
@@ -25,6 +28,55 @@ fn is_likely_binary_text_mismatch(bytes: &[u8]) -> bool {
     control_count.saturating_mul(100) > bytes.len().saturating_mul(5)
 }
 
+/// Folder prefixes between `prefix` and `key` whose own name contains `query_lower`.
+/// A key `a/photos/2024/x.jpg` under prefix `a/` yields `a/photos/` for "photo".
+fn matching_folder_prefixes(key: &str, prefix: &str, query_lower: &str) -> Vec<String> {
+    let Some(relative) = key.strip_prefix(prefix) else {
+        return Vec::new();
+    };
+
+    let mut folders = Vec::new();
+    let mut offset = prefix.len();
+    for segment in relative.split_inclusive('/') {
+        offset += segment.len();
+        let Some(name) = segment.strip_suffix('/') else {
+            break;
+        };
+        if !name.is_empty() && name.to_lowercase().contains(query_lower) {
+            folders.push(key[..offset].to_string());
+        }
+    }
+    folders
+}
+
+fn gzip_bytes(bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(bytes)?;
+    Ok(encoder.finish()?)
+}
+
+fn gunzip_bytes(bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut decoder = flate2::read::GzDecoder::new(bytes);
+    let mut decoded = Vec::new();
+    decoder.read_to_end(&mut decoded).map_err(|e| {
+        crate::error::AppError::InvalidContent(format!(
+            "Object is marked as gzip-encoded but could not be decompressed: {}",
+            e
+        ))
+    })?;
+    Ok(decoded)
+}
+
+fn is_gzip_encoding(content_encoding: Option<&str>) -> bool {
+    content_encoding
+        .map(|encoding| {
+            encoding
+                .split(',')
+                .any(|part| part.trim().eq_ignore_ascii_case("gzip"))
+        })
+        .unwrap_or(false)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ListObjectsResult {
     pub objects: Vec<S3Object>,
@@ -59,7 +111,7 @@ fn paginate_folder_content(
     let offset = continuation_token
         .and_then(|t| t.parse::<usize>().ok())
         .unwrap_or(0);
-    let max = max_keys.unwrap_or(1000).max(1) as usize;
+    let max = max_keys.unwrap_or(crate::s3::client::DEFAULT_LIST_PAGE_SIZE).max(1) as usize;
     let end = (offset + max).min(content.objects.len());
     let next_token = if end < content.objects.len() {
         Some(end.to_string())
@@ -86,6 +138,9 @@ async fn list_complete_folder_content(
     bucket_name: &str,
     prefix: &str,
     delimiter: &str,
+    page_size: i32,
+    timeout: std::time::Duration,
+    operation: &OperationHandle,
 ) -> Result<FolderContent> {
     let mut objects = Vec::new();
     let mut common_prefixes = Vec::new();
@@ -93,11 +148,12 @@ async fn list_complete_folder_content(
     let mut continuation_token: Option<String> = None;
 
     loop {
+        operation.check()?;
         let mut request = client
             .list_objects_v2()
             .bucket(bucket_name)
             .prefix(prefix)
-            .max_keys(1000);
+            .max_keys(page_size);
 
         if !delimiter.is_empty() {
             request = request.delimiter(delimiter);
@@ -107,21 +163,21 @@ async fn list_complete_folder_content(
             request = request.continuation_token(token);
         }
 
-        let output = request
-            .send()
-            .await
+        let output = crate::s3::with_operation_timeout(timeout, "ListObjectsV2", request.send())
+            .await?
             .map_err(|err| crate::error::AppError::S3Error(err.to_string()))?;
 
         for obj in output.contents() {
             let key = obj.key().unwrap_or_default();
             let size = obj.size().unwrap_or(0);
-            if !delimiter.is_empty() && key.ends_with('/') && size == 0 {
+            if !delimiter.is_empty() && key.ends_with(delimiter) && size == 0 {
                 continue;
             }
 
             objects.push(S3Object {
                 key: key.to_string(),
                 last_modified: obj.last_modified().map(|d| d.to_string()),
+                last_modified_epoch_ms: obj.last_modified().and_then(crate::s3::epoch_millis),
                 size,
                 storage_class: obj.storage_class().map(|s| s.as_str().to_string()),
             });
@@ -170,43 +226,52 @@ fn sort_folder_content(content: &mut FolderContent, sort_field: &str, sort_direc
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn list_objects(
     bucket_name: String,
     bucket_region: Option<String>,
     prefix: Option<String>,
     delimiter: Option<String>,
     continuation_token: Option<String>,
+    start_after: Option<String>,
     max_keys: Option<i32>,
     bypass_cache: Option<bool>,
     sort_field: Option<String>,
     sort_direction: Option<String>,
     profile_state: State<'_, ProfileState>,
     s3_state: State<'_, S3State>,
+    activity_state: State<'_, ActivityState>,
 ) -> Result<ListObjectsResult> {
     let prefix_str = prefix.clone().unwrap_or_default();
     let delimiter_str = delimiter.unwrap_or_else(|| "/".to_string());
     let requested_bucket_region = bucket_region.clone();
     let sort_field = normalize_sort_field(sort_field);
     let sort_direction = normalize_sort_direction(sort_direction);
+    // Resuming after a key only makes sense in S3's own key order, so it always
+    // goes to the live listing below.
+    let resumes_after_key = start_after.is_some();
     let uses_complete_sort = sort_field
         .as_deref()
         .map(|field| field != "name" || sort_direction == "desc")
         .unwrap_or(false)
-        && !delimiter_str.is_empty();
+        && !delimiter_str.is_empty()
+        && !resumes_after_key;
+    // The cached folder tree is built by splitting keys on "/", so any other
+    // delimiter must always be served from a live listing.
+    let uses_folder_cache = delimiter_str == "/" && !bypass_cache.unwrap_or(false) && !resumes_after_key;
     
     // Get active profile
-    let profile_manager = profile_state.read().await;
-    let active_profile = profile_manager
-        .get_active_profile()
-        .await?
-        .ok_or_else(|| crate::error::AppError::ProfileNotFound("No active profile".into()))?;
-    drop(profile_manager);
+    let active_profile = require_active_profile(&profile_state).await?;
+    let op_timeout = active_profile.operation_timeout();
+    // Pages default to the configured listing page size
+    let page_size = s3_state.read().await.list_page_size();
+    let max_keys = Some(max_keys.unwrap_or(page_size));
 
     // 1. Try Read Lock first for Cache (highly concurrent)
     {
         let s3_manager = s3_state.read().await;
-        let cached_bucket_region = s3_manager.get_bucket_region(&bucket_name).or(requested_bucket_region.clone());
-        if uses_complete_sort && !bypass_cache.unwrap_or(false) {
+        let cached_bucket_region = s3_manager.get_bucket_region(&active_profile.id, &bucket_name).or(requested_bucket_region.clone());
+        if uses_complete_sort && uses_folder_cache {
             if let Some(field) = sort_field.as_deref() {
                 if let Some(content) = s3_manager.get_sorted_folder_content(
                     &active_profile.id,
@@ -226,10 +291,10 @@ pub async fn list_objects(
             }
         }
 
-        if !uses_complete_sort && !bypass_cache.unwrap_or(false) && s3_manager.has_cache(&active_profile.id, &bucket_name) {
+        if !uses_complete_sort && uses_folder_cache && s3_manager.has_cache(&active_profile.id, &bucket_name) {
             if let Some(content) = s3_manager.get_folder_content(&active_profile.id, &bucket_name, &prefix_str) {
                  return Ok(paginate_folder_content(
-                     content,
+                     &content,
                      prefix_str,
                      cached_bucket_region.clone(),
                      continuation_token,
@@ -266,51 +331,34 @@ pub async fn list_objects(
     }
 
     // Check cache for bucket region first
-    let mut resolved_bucket_region = {
-        let s3_manager = s3_state.read().await;
-        s3_manager.get_bucket_region(&bucket_name)
-    }.or(bucket_region);
-
-    let client = {
-        let mut s3_manager = s3_state.write().await;
-        if let Some(ref region) = resolved_bucket_region {
-            s3_manager.get_client_for_region(&active_profile, region).await?.clone()
-        } else {
-            s3_manager.get_client(&active_profile).await?.clone()
-        }
-    };
+    let (mut resolved_bucket_region, client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
 
     if uses_complete_sort {
         let field = sort_field.clone().unwrap_or_else(|| "name".to_string());
-        let mut content = match list_complete_folder_content(&client, &bucket_name, &prefix_str, &delimiter_str).await {
+        // Sorting needs the whole folder, which can take many pages
+        let operation = activity_state.register("Folder listing", uuid::Uuid::new_v4().to_string(), s3_target(&bucket_name, &prefix_str));
+        let mut content = match list_complete_folder_content(&client, &bucket_name, &prefix_str, &delimiter_str, page_size, op_timeout, &operation).await {
             Ok(content) => content,
             Err(err) => {
                 log::warn!("Sorted list_objects failed, attempting region discovery: {}", err);
-                let detected_region = {
-                    let retry_client = {
-                       let mut s3_manager = s3_state.write().await;
-                       s3_manager.get_client(&active_profile).await?.clone()
-                    };
-                    crate::s3::get_bucket_region(&retry_client, &bucket_name).await.ok()
-                };
-
-                if let Some(new_region) = detected_region {
-                    let new_client = {
-                        let mut s3_manager = s3_state.write().await;
-                        s3_manager.set_bucket_region(&bucket_name, new_region.clone());
-                        s3_manager.get_client_for_region(&active_profile, &new_region).await?.clone()
-                    };
-                    resolved_bucket_region = Some(new_region);
-                    list_complete_folder_content(&new_client, &bucket_name, &prefix_str, &delimiter_str).await?
-                } else {
-                    return Err(err);
+                let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, None, |client| {
+                    let (bucket_name, prefix_str, delimiter_str, operation) = (&bucket_name, &prefix_str, &delimiter_str, &operation);
+                    async move { list_complete_folder_content(&client, bucket_name, prefix_str, delimiter_str, page_size, op_timeout, operation).await }
+                }).await?;
+
+                match retry {
+                    Some(retry) => {
+                        resolved_bucket_region = Some(retry.region);
+                        retry.result?
+                    }
+                    None => return Err(err),
                 }
             }
         };
 
         sort_folder_content(&mut content, &field, &sort_direction);
 
-        {
+        if delimiter_str == "/" {
             let mut s3_manager = s3_state.write().await;
             s3_manager.set_sorted_folder_content(
                 &active_profile.id,
@@ -345,81 +393,56 @@ pub async fn list_objects(
     if let Some(token) = &continuation_token {
         request = request.continuation_token(token);
     }
+    if let Some(after) = &start_after {
+        request = request.start_after(after);
+    }
     if let Some(max) = max_keys {
         request = request.max_keys(max);
     }
 
-    let result = request.send().await;
+    let result = crate::s3::with_operation_timeout(op_timeout, "ListObjectsV2", request.send()).await?;
 
     // Handle the result, implementing retry logic on error
     let output = match result {
         Ok(out) => out,
         Err(err) => {
             log::warn!("Initial list_objects failed: {}", err);
-            // Attempt to detect region and retry
-            let detected_region = {
-                // ... (Region detection logic remains the same, we trust get_bucket_region works on any client usually)
-                let retry_client = {
-                   let s3_manager = s3_state.read().await; 
-                   // Use default region client to ask about location
-                   // We don't need write lock just to get a client that might already exist
-                   // Wait, get_client requires &mut Self. Okay, we need write lock.
-                   drop(s3_manager);
-                   let mut s3_manager = s3_state.write().await;
-                   s3_manager.get_client(&active_profile).await?.clone()
-                };
-
-                match crate::s3::get_bucket_region(&retry_client, &bucket_name).await {
-                    Ok(region) => {
-                        log::info!("Detected correct region for bucket '{}': {}", bucket_name, region);
-                        Some(region)
-                    },
-                    Err(e) => {
-                        log::error!("Failed to detect bucket region: {}", e);
-                        None
-                    }
-                }
-            };
-            
-            if let Some(new_region) = detected_region {
-                // Get NEW client for this region
-                let new_client = {
-                    let mut s3_manager = s3_state.write().await;
-                    s3_manager.get_client_for_region(&active_profile, &new_region).await?.clone()
-                };
-                
-                // Retry request
-                let mut retry_req = new_client
+            // A redirect already names the right region; otherwise it is
+            // discovered, and the request repeated there.
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, crate::s3::region_from_redirect(&err), |client| {
+                let mut retry_req = client
                     .list_objects_v2()
                     .bucket(&bucket_name)
                     .prefix(&prefix_str);
-                
+
                 // Only set delimiter if it's non-empty
                 if !delimiter_str.is_empty() {
                     retry_req = retry_req.delimiter(&delimiter_str);
                 }
-                    
+
                 if let Some(token) = &continuation_token {
                     retry_req = retry_req.continuation_token(token);
                 }
+                if let Some(after) = &start_after {
+                    retry_req = retry_req.start_after(after);
+                }
                 if let Some(max) = max_keys {
                     retry_req = retry_req.max_keys(max);
                 }
-                
-                // Update the region we will return and use for fallback
-                resolved_bucket_region = Some(new_region.clone());
-                
-                // Cache the discovered region for future requests
-                {
-                    let mut s3_manager = s3_state.write().await;
-                    s3_manager.set_bucket_region(&bucket_name, new_region);
+
+                async move {
+                    crate::s3::with_operation_timeout(op_timeout, "ListObjectsV2", retry_req.send()).await?
+                        .map_err(|e| crate::error::AppError::S3Error(format!("Retry failed: {}", e)))
                 }
-                
-                retry_req.send().await
-                    .map_err(|e| crate::error::AppError::S3Error(format!("Retry failed: {}", e)))?
-            } else {
+            }).await?;
+
+            let Some(retry) = retry else {
+                log::error!("Failed to detect region for bucket '{}'", bucket_name);
                 return Err(crate::error::AppError::S3Error(err.to_string()));
-            }
+            };
+            // Update the region we will return and use for fallback
+            resolved_bucket_region = Some(retry.region);
+            retry.result?
         }
     };
 
@@ -431,9 +454,9 @@ pub async fn list_objects(
             let key = obj.key().unwrap_or_default();
             let size = obj.size().unwrap_or(0);
             
-            // Exclude folder markers (zero-byte objects ending with '/') ONLY if we are using a delimiter (structured view).
+            // Exclude folder markers (zero-byte objects ending with the delimiter) ONLY if we are using a delimiter (structured view).
             // In recursive view (no delimiter), we want ALL markers so they can be managed/deleted.
-            if !delimiter_str.is_empty() && key.ends_with('/') && size == 0 {
+            if !delimiter_str.is_empty() && key.ends_with(delimiter_str.as_str()) && size == 0 {
                 return false;
             }
             true
@@ -441,6 +464,7 @@ pub async fn list_objects(
         .map(|obj| S3Object {
             key: obj.key().unwrap_or_default().to_string(),
             last_modified: obj.last_modified().map(|d| d.to_string()),
+            last_modified_epoch_ms: obj.last_modified().and_then(crate::s3::epoch_millis),
             size: obj.size().unwrap_or(0),
             storage_class: obj.storage_class().map(|s| s.as_str().to_string()),
         })
@@ -455,22 +479,19 @@ pub async fn list_objects(
 
     // Fallback: If empty, try HeadObject to see if it's a direct file reference
     // We strip the trailing slash because some systems/users append it accidentally to files
-    if objects.is_empty() && common_prefixes.is_empty() && !prefix_str.is_empty() && !prefix_str.ends_with('/') {
+    if objects.is_empty() && common_prefixes.is_empty() && !prefix_str.is_empty() && !prefix_str.ends_with('/')
+        && (delimiter_str.is_empty() || !prefix_str.ends_with(delimiter_str.as_str()))
+    {
         let clean_key = prefix_str.trim_end_matches('/').to_string();
         if !clean_key.is_empty() {
-            let client = {
-                let mut s3_manager = s3_state.write().await;
-                if let Some(ref region) = resolved_bucket_region {
-                    s3_manager.get_client_for_region(&active_profile, region).await?.clone()
-                } else {
-                    s3_manager.get_client(&active_profile).await?.clone()
-                }
-            };
+            let (_, client) = bucket_client(&s3_state, &active_profile, &bucket_name, resolved_bucket_region.clone()).await?;
 
-            if let Ok(head_output) = client.head_object().bucket(&bucket_name).key(&clean_key).send().await {
+            let head = client.head_object().bucket(&bucket_name).key(&clean_key).send();
+            if let Ok(head_output) = crate::s3::with_operation_timeout(op_timeout, "HeadObject", head).await? {
                 objects.push(S3Object {
                     key: clean_key,
                     last_modified: head_output.last_modified().map(|d| d.to_string()),
+                    last_modified_epoch_ms: head_output.last_modified().and_then(crate::s3::epoch_millis),
                     size: head_output.content_length().unwrap_or(0),
                     storage_class: head_output.storage_class().map(|s| s.as_str().to_string()),
                 });
@@ -488,54 +509,387 @@ pub async fn list_objects(
     })
 }
 
+/// Refresh one object in the cached listing after it changed outside the app,
+/// without re-listing the bucket. The object is looked up again and its entry
+/// replaced, or dropped if it no longer exists. Returns the fresh entry.
+#[tauri::command]
+pub async fn invalidate_object(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    key: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<Option<S3Object>> {
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    {
+        let mut s3_manager = s3_state.write().await;
+        s3_manager.invalidate_object(&active_profile.id, &bucket_name, &key);
+    }
+
+    let (_, client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
+    let head = match client.head_object().bucket(&bucket_name).key(&key).send().await {
+        Ok(head) => head,
+        Err(err) if err.as_service_error().map(|e| e.is_not_found()).unwrap_or(false) => {
+            return Ok(None);
+        }
+        Err(err) => {
+            log::warn!("invalidate_object head failed, attempting region discovery: {}", err);
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, crate::s3::region_from_redirect(&err), |client| {
+                client.head_object().bucket(&bucket_name).key(&key).send()
+            }).await?;
+            let Some(retry) = retry else {
+                return Err(crate::error::AppError::S3Error(err.to_string()));
+            };
+            match retry.result {
+                Ok(head) => head,
+                Err(err) if err.as_service_error().map(|e| e.is_not_found()).unwrap_or(false) => {
+                    return Ok(None);
+                }
+                Err(err) => return Err(crate::error::AppError::S3Error(err.to_string())),
+            }
+        }
+    };
+
+    let object = S3Object {
+        key: key.clone(),
+        last_modified: head.last_modified().map(|d| d.to_string()),
+        last_modified_epoch_ms: head.last_modified().and_then(crate::s3::epoch_millis),
+        size: head.content_length().unwrap_or(0),
+        storage_class: head.storage_class().map(|s| s.as_str().to_string()),
+    };
+    let mut s3_manager = s3_state.write().await;
+    s3_manager.upsert_cached_object(&active_profile.id, &bucket_name, object.clone());
+    Ok(Some(object))
+}
+
+async fn send_raw_list_request(
+    client: &Client,
+    bucket_name: &str,
+    prefix: &str,
+    delimiter: Option<&str>,
+    continuation_token: Option<&str>,
+    max_keys: Option<i32>,
+) -> std::result::Result<
+    aws_sdk_s3::operation::list_objects_v2::ListObjectsV2Output,
+    aws_sdk_s3::error::SdkError<aws_sdk_s3::operation::list_objects_v2::ListObjectsV2Error>,
+> {
+    client
+        .list_objects_v2()
+        .bucket(bucket_name)
+        .prefix(prefix)
+        .set_delimiter(delimiter.filter(|d| !d.is_empty()).map(str::to_string))
+        .set_continuation_token(continuation_token.map(str::to_string))
+        .set_max_keys(max_keys)
+        .send()
+        .await
+}
+
+/// List one page straight from S3, bypassing every cache. Unlike `list_objects`,
+/// no delimiter is applied unless one is given, folder markers are kept, and the
+/// continuation token is S3's own, so it can be passed back to page through
+/// very large flat buckets.
+#[tauri::command]
+pub async fn list_objects_raw(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    prefix: Option<String>,
+    delimiter: Option<String>,
+    continuation_token: Option<String>,
+    max_keys: Option<i32>,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<ListObjectsResult> {
+    let prefix_str = prefix.unwrap_or_default();
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let (mut resolved_bucket_region, client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
+    let max_keys = Some(max_keys.unwrap_or(s3_state.read().await.list_page_size()));
+    let op_timeout = active_profile.operation_timeout();
+    let result = crate::s3::with_operation_timeout(op_timeout, "ListObjectsV2", send_raw_list_request(
+        &client,
+        &bucket_name,
+        &prefix_str,
+        delimiter.as_deref(),
+        continuation_token.as_deref(),
+        max_keys,
+    )).await?;
+
+    let output = match result {
+        Ok(output) => output,
+        Err(err) => {
+            log::warn!("list_objects_raw failed, attempting region discovery: {}", err);
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, crate::s3::region_from_redirect(&err), |client| {
+                let (bucket_name, prefix_str, delimiter, continuation_token) = (&bucket_name, &prefix_str, &delimiter, &continuation_token);
+                async move {
+                    crate::s3::with_operation_timeout(op_timeout, "ListObjectsV2", send_raw_list_request(
+                        &client,
+                        bucket_name,
+                        prefix_str,
+                        delimiter.as_deref(),
+                        continuation_token.as_deref(),
+                        max_keys,
+                    )).await?
+                        .map_err(|e| crate::error::AppError::S3Error(format!("Retry failed: {}", e)))
+                }
+            }).await?;
+
+            match retry {
+                Some(retry) => {
+                    resolved_bucket_region = Some(retry.region);
+                    retry.result?
+                }
+                None => return Err(crate::error::AppError::S3Error(err.to_string())),
+            }
+        }
+    };
+
+    let objects = output
+        .contents()
+        .iter()
+        .map(|obj| S3Object {
+            key: obj.key().unwrap_or_default().to_string(),
+            last_modified: obj.last_modified().map(|d| d.to_string()),
+            last_modified_epoch_ms: obj.last_modified().and_then(crate::s3::epoch_millis),
+            size: obj.size().unwrap_or(0),
+            storage_class: obj.storage_class().map(|s| s.as_str().to_string()),
+        })
+        .collect();
+
+    let common_prefixes = output
+        .common_prefixes()
+        .iter()
+        .filter_map(|cp| cp.prefix().map(str::to_string))
+        .collect();
+
+    Ok(ListObjectsResult {
+        objects,
+        common_prefixes,
+        next_continuation_token: output.next_continuation_token().map(str::to_string),
+        is_truncated: output.is_truncated().unwrap_or(false),
+        prefix: prefix_str,
+        bucket_region: resolved_bucket_region,
+    })
+}
+
+async fn list_common_prefixes(
+    client: &Client,
+    bucket_name: &str,
+    timeout: std::time::Duration,
+) -> Result<Vec<String>> {
+    let mut prefixes = Vec::new();
+    let mut continuation_token: Option<String> = None;
+    loop {
+        let request = client
+            .list_objects_v2()
+            .bucket(bucket_name)
+            .delimiter("/")
+            .set_continuation_token(continuation_token.take())
+            .send();
+        let output = crate::s3::with_operation_timeout(timeout, "ListObjectsV2", request)
+            .await?
+            .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?;
+
+        prefixes.extend(
+            output
+                .common_prefixes()
+                .iter()
+                .filter_map(|cp| cp.prefix().map(str::to_string)),
+        );
+
+        match output.next_continuation_token() {
+            Some(token) if output.is_truncated().unwrap_or(false) => {
+                continuation_token = Some(token.to_string())
+            }
+            _ => break,
+        }
+    }
+    Ok(prefixes)
+}
+
+/// The bucket's top-level folders, for populating a sidebar before the user drills
+/// in. Objects in the listing are ignored, so this is usually a single request.
+#[tauri::command]
+pub async fn list_top_level_prefixes(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<Vec<String>> {
+    let active_profile = require_active_profile(&profile_state).await?;
+    let op_timeout = active_profile.operation_timeout();
+
+    {
+        let s3_manager = s3_state.read().await;
+        if let Some(content) = s3_manager.get_folder_content(&active_profile.id, &bucket_name, "") {
+            return Ok(content.common_prefixes.clone());
+        }
+    }
+
+    let (_, client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
+    match list_common_prefixes(&client, &bucket_name, op_timeout).await {
+        Ok(prefixes) => Ok(prefixes),
+        Err(err @ crate::error::AppError::Timeout(_)) => Err(err),
+        Err(err) => {
+            log::warn!("list_top_level_prefixes failed, attempting region discovery: {}", err);
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, None, |client| {
+                let bucket_name = &bucket_name;
+                async move { list_common_prefixes(&client, bucket_name, op_timeout).await }
+            }).await?;
+
+            match retry {
+                Some(retry) => retry.result,
+                None => Err(err),
+            }
+        }
+    }
+}
+
+/// List the whole bucket and cache it, so browsing it afterwards is served from
+/// the cache. Listings past the disk cache threshold are kept on disk. Returns
+/// the number of objects cached.
+#[tauri::command]
+pub async fn cache_bucket_listing(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    activity_state: State<'_, ActivityState>,
+) -> Result<usize> {
+    let active_profile = require_active_profile(&profile_state).await?;
+    let op_timeout = active_profile.operation_timeout();
+    let page_size = s3_state.read().await.list_page_size();
+    let operation = activity_state.register("Bucket caching", uuid::Uuid::new_v4().to_string(), s3_target(&bucket_name, ""));
+
+    let (_, client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
+    let cached = crate::s3::client::cache_bucket_objects(
+        &s3_state,
+        &client,
+        &active_profile.id,
+        &bucket_name,
+        page_size,
+        op_timeout,
+        operation.cancel_flag(),
+    )
+    .await;
+    match cached {
+        Err(err)
+            if !matches!(
+                err,
+                crate::error::AppError::Timeout(_) | crate::error::AppError::Cancelled(_) | crate::error::AppError::IoError(_)
+            ) =>
+        {
+            log::warn!("cache_bucket_listing failed, attempting region discovery: {}", err);
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, None, |client| {
+                let (s3_state, profile_id, bucket_name, operation) = (&s3_state, &active_profile.id, &bucket_name, &operation);
+                async move {
+                    crate::s3::client::cache_bucket_objects(
+                        s3_state,
+                        &client,
+                        profile_id,
+                        bucket_name,
+                        page_size,
+                        op_timeout,
+                        operation.cancel_flag(),
+                    )
+                    .await
+                }
+            }).await?;
+
+            match retry {
+                Some(retry) => retry.result,
+                None => Err(err),
+            }
+        }
+        cached => cached,
+    }
+}
+
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn search_objects(
     bucket_name: String,
     bucket_region: Option<String>,
     prefix: Option<String>,
     query: String,
+    include_prefixes: Option<bool>,
     profile_state: State<'_, ProfileState>,
     s3_state: State<'_, S3State>,
+    activity_state: State<'_, ActivityState>,
 ) -> Result<Vec<S3Object>> {
-    let profile_manager = profile_state.read().await;
-    let active_profile = profile_manager
-        .get_active_profile()
-        .await?
-        .ok_or_else(|| crate::error::AppError::ProfileNotFound("No active profile".into()))?;
-    drop(profile_manager);
+    let active_profile = require_active_profile(&profile_state).await?;
+    let op_timeout = active_profile.operation_timeout();
     
     let prefix_str = prefix.unwrap_or_default();
     let query_lower = query.to_lowercase();
+    let include_prefixes = include_prefixes.unwrap_or(false);
+    let operation = activity_state.register("Search", uuid::Uuid::new_v4().to_string(), s3_target(&bucket_name, &prefix_str));
 
-    // Check cache for bucket region first
-    let bucket_region = {
-        let s3_manager = s3_state.read().await;
-        s3_manager.get_bucket_region(&bucket_name)
-    }.or(bucket_region);
-
-    let mut client = {
-        let mut s3_manager = s3_state.write().await;
-        if let Some(ref region) = bucket_region {
-            s3_manager.get_client_for_region(&active_profile, region).await?.clone()
-        } else {
-            s3_manager.get_client(&active_profile).await?.clone()
+    let mut objects = Vec::new();
+    let mut folders = BTreeSet::new();
+
+    // A bucket listing cached on disk answers without going to S3. The file is
+    // read on a blocking thread, without holding the client lock.
+    let disk_listing = s3_state.read().await.disk_listing(&active_profile.id, &bucket_name);
+    let mut searched_cache = false;
+    if let Some(listing) = disk_listing {
+        let (prefix, query) = (prefix_str.clone(), query_lower.clone());
+        let searched = tokio::task::spawn_blocking(move || {
+            let mut objects = Vec::new();
+            let mut folders = BTreeSet::new();
+            listing
+                .for_each_object(&prefix, |obj| {
+                    if include_prefixes {
+                        folders.extend(matching_folder_prefixes(&obj.key, &prefix, &query));
+                    }
+                    if obj.key.ends_with('/') && obj.size == 0 {
+                        return;
+                    }
+                    if obj.key.to_lowercase().contains(&query) {
+                        objects.push(obj.clone());
+                    }
+                })
+                .map(|_| (objects, folders))
+        })
+        .await;
+        match searched {
+            Ok(Ok((cached_objects, cached_folders))) => {
+                objects = cached_objects;
+                folders = cached_folders;
+                searched_cache = true;
+            }
+            Ok(Err(e)) => log::warn!("Could not read the cached listing of '{}': {}", bucket_name, e),
+            Err(e) => log::warn!("Searching the cached listing of '{}' failed: {}", bucket_name, e),
         }
-    };
+    }
+
+    // Check cache for bucket region first
+    let (_, mut client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+    let page_size = s3_state.read().await.list_page_size();
 
-    let mut objects = Vec::new();
     let mut continuation_token = None;
     let mut calls = 0;
 
     loop {
+        if searched_cache {
+            break;
+        }
+        operation.check()?;
         let mut req = client.list_objects_v2()
             .bucket(&bucket_name)
-            .prefix(&prefix_str); // Respect prefix context
+            .prefix(&prefix_str) // Respect prefix context
+            .max_keys(page_size);
 
         if let Some(ref token) = continuation_token {
             req = req.continuation_token(token);
         }
 
-        let result = req.send().await;
+        let result = crate::s3::with_operation_timeout(op_timeout, "ListObjectsV2", req.send()).await?;
         
         // implement region detection and retry on error
         let output = match result {
@@ -550,34 +904,28 @@ pub async fn search_objects(
                 }
                 
                 // Attempt to detect region and retry (only if this is the first call)
-                let detected_region = {
-                    let retry_client = {
-                       let mut s3_manager = s3_state.write().await;
-                       s3_manager.get_client(&active_profile).await?.clone()
-                    };
-                    crate::s3::get_bucket_region(&retry_client, &bucket_name).await.ok()
-                };
-
-                if let Some(new_region) = detected_region {
-                    let new_client = {
-                        let mut s3_manager = s3_state.write().await;
-                        s3_manager.set_bucket_region(&bucket_name, new_region.clone());
-                        s3_manager.get_client_for_region(&active_profile, &new_region).await?.clone()
-                    };
-                    client = new_client.clone();
-                    
-                    let mut retry_req = new_client.list_objects_v2()
+                let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, crate::s3::region_from_redirect(&err), |client| {
+                    let mut retry_req = client.list_objects_v2()
                         .bucket(&bucket_name)
-                        .prefix(&prefix_str);
+                        .prefix(&prefix_str)
+                        .max_keys(page_size);
 
                     if let Some(token) = &continuation_token {
                         retry_req = retry_req.continuation_token(token);
                     }
 
-                    retry_req.send().await
-                        .map_err(|e| crate::error::AppError::S3Error(format!("Search retry failed: {}", e)))?
-                } else {
-                    return Err(crate::error::AppError::S3Error(err.to_string()));
+                    async move {
+                        crate::s3::with_operation_timeout(op_timeout, "ListObjectsV2", retry_req.send()).await?
+                            .map_err(|e| crate::error::AppError::S3Error(format!("Search retry failed: {}", e)))
+                    }
+                }).await?;
+
+                match retry {
+                    Some(retry) => {
+                        client = retry.client;
+                        retry.result?
+                    }
+                    None => return Err(crate::error::AppError::S3Error(err.to_string())),
                 }
             }
         };
@@ -587,6 +935,11 @@ pub async fn search_objects(
         for obj in output.contents() {
             let key = obj.key().unwrap_or_default();
             let size = obj.size().unwrap_or(0);
+            // The search is flat, so folders are derived from the key paths
+            // (including explicit folder markers) rather than common prefixes.
+            if include_prefixes {
+                folders.extend(matching_folder_prefixes(key, &prefix_str, &query_lower));
+            }
             // Skip folder markers (zero-byte objects ending with /)
             if key.ends_with('/') && size == 0 {
                 continue;
@@ -596,6 +949,7 @@ pub async fn search_objects(
                     key: key.to_string(),
                     size,
                     last_modified: obj.last_modified().map(|d| d.to_string()),
+                    last_modified_epoch_ms: obj.last_modified().and_then(crate::s3::epoch_millis),
                     storage_class: obj.storage_class().map(|s| s.as_str().to_string()),
                 });
             }
@@ -607,80 +961,282 @@ pub async fn search_objects(
         continuation_token = output.next_continuation_token().map(|s| s.to_string());
     }
 
+    if !folders.is_empty() {
+        // Folders first, matching the browse view ordering.
+        let mut results: Vec<S3Object> = folders
+            .into_iter()
+            .map(|key| S3Object {
+                key,
+                size: 0,
+                last_modified: None,
+                last_modified_epoch_ms: None,
+                storage_class: None,
+            })
+            .collect();
+        results.append(&mut objects);
+        return Ok(results);
+    }
+
     Ok(objects)
 }
 
-#[tauri::command]
-pub async fn get_presigned_url(
-    bucket_name: String,
-    bucket_region: Option<String>,
-    key: String,
-    expires_in: u64,
-    profile_state: State<'_, ProfileState>,
-    s3_state: State<'_, S3State>,
-) -> Result<String> {
-    use aws_sdk_s3::presigning::PresigningConfig;
-    use std::time::Duration;
-
-    let profile_manager = profile_state.read().await;
-    let active_profile = profile_manager
-        .get_active_profile()
-        .await?
-        .ok_or_else(|| crate::error::AppError::ProfileNotFound("No active profile".into()))?;
-    drop(profile_manager);
+const DEFAULT_RECENT_LIMIT: usize = 100;
+const DEFAULT_RECENT_PARTITIONS: u32 = 90;
 
-    let bucket_region = {
-        let s3_manager = s3_state.read().await;
-        s3_manager.get_bucket_region(&bucket_name)
-    }.or(bucket_region);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PartitionStep {
+    Hour,
+    Day,
+    Month,
+    Year,
+}
 
-    let client = {
-        let mut s3_manager = s3_state.write().await;
-        if let Some(ref region) = bucket_region {
-            s3_manager.get_client_for_region(&active_profile, region).await?.clone()
-        } else {
-            s3_manager.get_client(&active_profile).await?.clone()
-        }
-    };
+/// The finest date field in a strftime-style key template such as
+/// `logs/%Y/%m/%d/`, which is how far apart consecutive partitions are
+fn partition_step(template: &str) -> Option<PartitionStep> {
+    if template.contains("%H") {
+        Some(PartitionStep::Hour)
+    } else if template.contains("%d") || template.contains("%j") {
+        Some(PartitionStep::Day)
+    } else if template.contains("%m") {
+        Some(PartitionStep::Month)
+    } else if template.contains("%Y") {
+        Some(PartitionStep::Year)
+    } else {
+        None
+    }
+}
 
-    let presigning_config_result = PresigningConfig::expires_in(Duration::from_secs(expires_in))
-        .map_err(|e| crate::error::AppError::S3Error(e.to_string()));
+/// Prefixes produced by `template` walking back from `now`, newest first
+fn recent_partitions(
+    template: &str,
+    now: chrono::DateTime<chrono::Utc>,
+    count: u32,
+) -> Result<Vec<String>> {
+    // chrono panics while formatting a template with a bad specifier
+    if chrono::format::StrftimeItems::new(template).any(|item| matches!(item, chrono::format::Item::Error)) {
+        return Err(crate::error::AppError::InvalidContent(format!(
+            "Key template '{}' has an invalid % field",
+            template
+        )));
+    }
 
-    let mut get_obj_builder = client
-        .get_object()
-        .bucket(&bucket_name)
-        .key(&key)
-        .response_content_disposition("inline");
+    let step = partition_step(template).ok_or_else(|| {
+        crate::error::AppError::InvalidContent(format!(
+            "Key template '{}' has no date fields. Use %Y, %m, %d or %H.",
+            template
+        ))
+    })?;
 
-    // Force PDF content type if extension matches, ensuring browser renders it
-    if key.to_lowercase().ends_with(".pdf") {
-        get_obj_builder = get_obj_builder.response_content_type("application/pdf");
+    let mut partitions: Vec<String> = Vec::new();
+    let mut at = now;
+    for _ in 0..count {
+        let prefix = at.format(template).to_string();
+        if partitions.last() != Some(&prefix) {
+            partitions.push(prefix);
+        }
+        let previous = match step {
+            PartitionStep::Hour => at.checked_sub_signed(chrono::Duration::hours(1)),
+            PartitionStep::Day => at.checked_sub_signed(chrono::Duration::days(1)),
+            PartitionStep::Month => at.checked_sub_months(chrono::Months::new(1)),
+            PartitionStep::Year => at.checked_sub_months(chrono::Months::new(12)),
+        };
+        match previous {
+            Some(previous) => at = previous,
+            None => break,
+        }
     }
+    Ok(partitions)
+}
 
-    let presigned_request_result = match presigning_config_result {
-        Ok(config) => get_obj_builder.presigned(config).await
-            .map_err(|e| crate::error::AppError::S3Error(e.to_string())),
-        Err(e) => Err(e),
-    };
-
-    match presigned_request_result {
-        Ok(req) => Ok(req.uri().to_string()),
-        Err(err) => {
-            log::warn!("Presigning failed, attempting region discovery: {}", err);
-            let detected_region = {
+async fn list_prefix_objects(
+    client: &Client,
+    bucket_name: &str,
+    prefix: &str,
+    page_size: i32,
+    timeout: std::time::Duration,
+) -> Result<Vec<S3Object>> {
+    let mut objects = Vec::new();
+    let mut continuation_token: Option<String> = None;
+    loop {
+        let request = client
+            .list_objects_v2()
+            .bucket(bucket_name)
+            .prefix(prefix)
+            .max_keys(page_size)
+            .set_continuation_token(continuation_token.take())
+            .send();
+        let output = crate::s3::with_operation_timeout(timeout, "ListObjectsV2", request)
+            .await?
+            .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?;
+
+        objects.extend(
+            output
+                .contents()
+                .iter()
+                .filter(|obj| !obj.key().unwrap_or_default().ends_with('/'))
+                .map(|obj| S3Object {
+                    key: obj.key().unwrap_or_default().to_string(),
+                    last_modified: obj.last_modified().map(|d| d.to_string()),
+                    last_modified_epoch_ms: obj.last_modified().and_then(crate::s3::epoch_millis),
+                    size: obj.size().unwrap_or(0),
+                    storage_class: obj.storage_class().map(|s| s.as_str().to_string()),
+                }),
+        );
+
+        match output.next_continuation_token() {
+            Some(token) if output.is_truncated().unwrap_or(false) => {
+                continuation_token = Some(token.to_string())
+            }
+            _ => break,
+        }
+    }
+    Ok(objects)
+}
+
+async fn collect_recent_objects(
+    client: &Client,
+    bucket_name: &str,
+    prefix: &str,
+    partitions: &Option<Vec<String>>,
+    limit: usize,
+    page_size: i32,
+    timeout: std::time::Duration,
+) -> Result<Vec<S3Object>> {
+    let newest_first = |objects: &mut Vec<S3Object>| {
+        objects.sort_by(|a, b| {
+            b.last_modified_epoch_ms
+                .cmp(&a.last_modified_epoch_ms)
+                .then_with(|| a.key.cmp(&b.key))
+        });
+    };
+
+    let Some(partitions) = partitions else {
+        let mut objects = list_prefix_objects(client, bucket_name, prefix, page_size, timeout).await?;
+        newest_first(&mut objects);
+        objects.truncate(limit);
+        return Ok(objects);
+    };
+
+    // Every object in a partition is newer than those in the partitions after it,
+    // so the walk can stop at the first partition that fills the limit
+    let mut recent = Vec::new();
+    for partition in partitions {
+        let mut objects = list_prefix_objects(client, bucket_name, partition, page_size, timeout).await?;
+        newest_first(&mut objects);
+        recent.extend(objects);
+        if recent.len() >= limit {
+            break;
+        }
+    }
+    recent.truncate(limit);
+    Ok(recent)
+}
+
+/// List the most recently modified objects, newest first. S3 can't sort by date,
+/// so with a strftime-style `key_template` such as `logs/%Y/%m/%d/` only the
+/// latest date partitions are listed, walking back from now (UTC) for at most
+/// `max_partitions` steps until `limit` objects are found. The template is
+/// relative to `prefix`; without one the whole `prefix` is scanned.
+#[tauri::command]
+pub async fn list_recent_objects(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    prefix: Option<String>,
+    key_template: Option<String>,
+    limit: Option<usize>,
+    max_partitions: Option<u32>,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<Vec<S3Object>> {
+    let limit = limit.unwrap_or(DEFAULT_RECENT_LIMIT).max(1);
+    let prefix = prefix.unwrap_or_default();
+    let partitions = match key_template.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        Some(template) => Some(recent_partitions(
+            template,
+            chrono::Utc::now(),
+            max_partitions.unwrap_or(DEFAULT_RECENT_PARTITIONS).max(1),
+        )?
+        .into_iter()
+        .map(|partition| format!("{}{}", prefix, partition))
+        .collect()),
+        None => None,
+    };
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let page_size = s3_state.read().await.list_page_size();
+    let (_, client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
+    match collect_recent_objects(&client, &bucket_name, &prefix, &partitions, limit, page_size, active_profile.operation_timeout()).await {
+        Ok(objects) => Ok(objects),
+        Err(err) => {
+            log::warn!("list_recent_objects failed, attempting region discovery: {}", err);
+            let op_timeout = active_profile.operation_timeout();
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, None, |client| {
+                let (bucket_name, prefix, partitions) = (&bucket_name, &prefix, &partitions);
+                async move { collect_recent_objects(&client, bucket_name, prefix, partitions, limit, page_size, op_timeout).await }
+            }).await?;
+
+            match retry {
+                Some(retry) => retry.result,
+                None => Err(err),
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_presigned_url(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    key: String,
+    expires_in: u64,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<String> {
+    use aws_sdk_s3::presigning::PresigningConfig;
+    use std::time::Duration;
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let (_, client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
+    let presigning_config_result = PresigningConfig::expires_in(Duration::from_secs(expires_in))
+        .map_err(|e| crate::error::AppError::S3Error(e.to_string()));
+
+    let mut get_obj_builder = client
+        .get_object()
+        .bucket(&bucket_name)
+        .key(&key)
+        .response_content_disposition("inline");
+
+    // Force PDF content type if extension matches, ensuring browser renders it
+    if key.to_lowercase().ends_with(".pdf") {
+        get_obj_builder = get_obj_builder.response_content_type("application/pdf");
+    }
+
+    let presigned_request_result = match presigning_config_result {
+        Ok(config) => get_obj_builder.presigned(config).await
+            .map_err(|e| crate::error::AppError::S3Error(e.to_string())),
+        Err(e) => Err(e),
+    };
+
+    match presigned_request_result {
+        Ok(req) => Ok(req.uri().to_string()),
+        Err(err) => {
+            log::warn!("Presigning failed, attempting region discovery: {}", err);
+            let detected_region = {
                 let retry_client = {
                    let mut s3_manager = s3_state.write().await;
                    s3_manager.get_client(&active_profile).await?.clone()
                 };
-                crate::s3::get_bucket_region(&retry_client, &bucket_name).await.ok()
+                crate::s3::resolve_bucket_region(&retry_client, &active_profile, &bucket_name).await.ok()
             };
 
             if let Some(new_region) = detected_region {
-                let new_client = {
-                    let mut s3_manager = s3_state.write().await;
-                    s3_manager.set_bucket_region(&bucket_name, new_region.clone());
-                    s3_manager.get_client_for_region(&active_profile, &new_region).await?.clone()
-                };
+                s3_state.write().await.set_bucket_region(&active_profile.id, &bucket_name, new_region.clone());
+                let new_client = crate::s3::client_for_bucket(&s3_state, &active_profile, &bucket_name, &new_region).await?;
 
                 let mut get_obj = new_client
                     .get_object()
@@ -705,35 +1261,469 @@ pub async fn get_presigned_url(
     }
 }
 
+#[derive(Debug, Serialize)]
+pub struct PresignedRequestInfo {
+    pub method: String,
+    pub url: String,
+    /// Headers the caller must send verbatim, since they are part of the signature.
+    pub headers: std::collections::BTreeMap<String, String>,
+    pub expires_in: u64,
+}
+
+async fn presign_with_headers(
+    client: &Client,
+    method: &str,
+    bucket_name: &str,
+    key: &str,
+    headers: &[(String, String)],
+    expires_in: u64,
+) -> Result<aws_sdk_s3::presigning::PresignedRequest> {
+    use aws_sdk_s3::presigning::PresigningConfig;
+    use std::time::Duration;
+
+    let config = PresigningConfig::expires_in(Duration::from_secs(expires_in))
+        .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?;
+
+    // Headers are added before signing so they end up in SignedHeaders.
+    let extra_headers = headers.to_vec();
+    let add_headers = move |req: &mut aws_sdk_s3::config::http::HttpRequest| {
+        for (name, value) in &extra_headers {
+            req.headers_mut().insert(name.clone(), value.clone());
+        }
+    };
+
+    let presigned = match method {
+        "GET" => client.get_object().bucket(bucket_name).key(key)
+            .customize().mutate_request(add_headers).presigned(config).await
+            .map_err(|e| e.to_string()),
+        "PUT" => client.put_object().bucket(bucket_name).key(key)
+            .customize().mutate_request(add_headers).presigned(config).await
+            .map_err(|e| e.to_string()),
+        "DELETE" => client.delete_object().bucket(bucket_name).key(key)
+            .customize().mutate_request(add_headers).presigned(config).await
+            .map_err(|e| e.to_string()),
+        "HEAD" => client.head_object().bucket(bucket_name).key(key)
+            .customize().mutate_request(add_headers).presigned(config).await
+            .map_err(|e| e.to_string()),
+        other => {
+            return Err(crate::error::AppError::InvalidContent(format!(
+                "Unsupported presign method '{}'. Use GET, PUT, DELETE or HEAD.",
+                other
+            )))
+        }
+    };
+
+    presigned.map_err(crate::error::AppError::S3Error)
+}
+
+/// Presign an arbitrary object request for use by external tools, e.g. a PUT that
+/// must carry `x-amz-acl`. Returns the URL plus every header the caller has to send.
 #[tauri::command]
-pub async fn get_object_content(
+pub async fn presign_request(
+    method: String,
     bucket_name: String,
     bucket_region: Option<String>,
     key: String,
+    headers: Option<std::collections::HashMap<String, String>>,
+    expires_in: u64,
     profile_state: State<'_, ProfileState>,
     s3_state: State<'_, S3State>,
-) -> Result<String> {
-    let profile_manager = profile_state.read().await;
-    let active_profile = profile_manager
-        .get_active_profile()
-        .await?
-        .ok_or_else(|| crate::error::AppError::ProfileNotFound("No active profile".into()))?;
-    drop(profile_manager);
-
-    let bucket_region = {
-        let s3_manager = s3_state.read().await;
-        s3_manager.get_bucket_region(&bucket_name)
-    }.or(bucket_region);
+) -> Result<PresignedRequestInfo> {
+    let method = method.trim().to_ascii_uppercase();
+    if !matches!(method.as_str(), "GET" | "PUT" | "DELETE" | "HEAD") {
+        return Err(crate::error::AppError::InvalidContent(format!(
+            "Unsupported presign method '{}'. Use GET, PUT, DELETE or HEAD.",
+            method
+        )));
+    }
 
-    let client = {
-        let mut s3_manager = s3_state.write().await;
-        if let Some(ref region) = bucket_region {
-            s3_manager.get_client_for_region(&active_profile, region).await?.clone()
-        } else {
-            s3_manager.get_client(&active_profile).await?.clone()
+    let mut extra_headers = Vec::new();
+    for (name, value) in headers.unwrap_or_default() {
+        let name = name.trim().to_ascii_lowercase();
+        if name.is_empty() || name == "host" || name == "authorization" || name.starts_with("x-amz-date") {
+            return Err(crate::error::AppError::InvalidContent(format!(
+                "Header '{}' cannot be set on a presigned request",
+                name
+            )));
+        }
+        // Checked here because the signer panics on an invalid header
+        if http::HeaderName::from_bytes(name.as_bytes()).is_err() {
+            return Err(crate::error::AppError::InvalidContent(format!(
+                "'{}' is not a valid header name",
+                name
+            )));
         }
+        if http::HeaderValue::from_str(&value).is_err() {
+            return Err(crate::error::AppError::InvalidContent(format!(
+                "Header '{}' has a value with characters a header can't contain",
+                name
+            )));
+        }
+        extra_headers.push((name, value));
+    }
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let (_, client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
+    let presigned = presign_with_headers(&client, &method, &bucket_name, &key, &extra_headers, expires_in).await?;
+
+    Ok(PresignedRequestInfo {
+        method: presigned.method().to_string(),
+        url: presigned.uri().to_string(),
+        headers: presigned
+            .headers()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        expires_in,
+    })
+}
+
+const PRESIGNED_CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+#[derive(Debug, Serialize)]
+pub struct PresignedUrlStatus {
+    /// The URL answered with a 2xx
+    pub valid: bool,
+    pub status: u16,
+    /// Request that succeeded or was tried last: HEAD, or a one-byte GET for URLs
+    /// signed for GET
+    pub method: String,
+    /// S3 error code, e.g. `AccessDenied`, `SignatureDoesNotMatch` or `NoSuchKey`
+    pub error_code: Option<String>,
+    pub expires_at: Option<String>,
+    pub expired: bool,
+    /// Region the URL was signed for
+    pub signed_region: Option<String>,
+    /// Region S3 says the bucket is in, when it reports one
+    pub bucket_region: Option<String>,
+    pub content_length: Option<u64>,
+    pub content_type: Option<String>,
+}
+
+/// Expiry and signing region encoded in a SigV4 (or SigV2) presigned URL
+fn presigned_url_details(url: &reqwest::Url) -> (Option<chrono::DateTime<chrono::Utc>>, Option<String>) {
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.into_owned())
+    };
+
+    let signed_at = param("X-Amz-Date").and_then(|date| {
+        chrono::NaiveDateTime::parse_from_str(&date, "%Y%m%dT%H%M%SZ")
+            .ok()
+            .map(|date| date.and_utc())
+    });
+    let expires_in = param("X-Amz-Expires").and_then(|secs| secs.parse::<i64>().ok());
+    let expires_at = match (signed_at, expires_in) {
+        // The expiry comes from the pasted URL, so it may be far out of range
+        (Some(signed_at), Some(expires_in)) => chrono::TimeDelta::try_seconds(expires_in)
+            .and_then(|expires_in| signed_at.checked_add_signed(expires_in)),
+        // SigV2 carries an absolute epoch time instead
+        _ => param("Expires")
+            .and_then(|secs| secs.parse::<i64>().ok())
+            .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0)),
+    };
+
+    // AKID/20240307/eu-west-1/s3/aws4_request
+    let signed_region = param("X-Amz-Credential")
+        .and_then(|credential| credential.split('/').nth(2).map(str::to_string));
+
+    (expires_at, signed_region)
+}
+
+fn xml_error_code(body: &str) -> Option<String> {
+    let start = body.find("<Code>")? + "<Code>".len();
+    let end = body[start..].find("</Code>")? + start;
+    Some(body[start..end].to_string())
+}
+
+/// Check that a presigned URL works before sharing it: not expired, signed for
+/// the bucket's region and pointing at an object that exists. Tries a HEAD first;
+/// URLs signed for GET reject HEAD, so those are checked with a one-byte GET.
+#[tauri::command]
+pub async fn validate_presigned_url(url: String) -> Result<PresignedUrlStatus> {
+    let parsed = reqwest::Url::parse(url.trim())
+        .map_err(|e| crate::error::AppError::InvalidContent(format!("Invalid URL: {}", e)))?;
+    let (expires_at, signed_region) = presigned_url_details(&parsed);
+    let expired = expires_at.map(|at| at <= chrono::Utc::now()).unwrap_or(false);
+
+    let http = reqwest::Client::builder()
+        .timeout(PRESIGNED_CHECK_TIMEOUT)
+        .build()
+        .map_err(|e| crate::error::AppError::ConnectionFailed(e.to_string()))?;
+
+    let mut method = "HEAD";
+    let mut response = http
+        .head(parsed.clone())
+        .send()
+        .await
+        .map_err(|e| crate::error::AppError::ConnectionFailed(e.to_string()))?;
+    if response.status() == reqwest::StatusCode::FORBIDDEN {
+        method = "GET";
+        response = http
+            .get(parsed)
+            .header(reqwest::header::RANGE, "bytes=0-0")
+            .send()
+            .await
+            .map_err(|e| crate::error::AppError::ConnectionFailed(e.to_string()))?;
+    }
+
+    let status = response.status();
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let bucket_region = header("x-amz-bucket-region");
+    let content_type = header("content-type");
+    // A ranged GET reports the full size after the slash in Content-Range
+    let content_length = header("content-range")
+        .and_then(|range| range.rsplit('/').next().and_then(|total| total.parse().ok()))
+        .or_else(|| header("content-length").and_then(|length| length.parse().ok()));
+    let error_code = if status.is_success() {
+        None
+    } else {
+        header("x-amz-error-code").or(match response.text().await {
+            Ok(body) => xml_error_code(&body),
+            Err(_) => None,
+        })
     };
 
+    Ok(PresignedUrlStatus {
+        valid: status.is_success(),
+        status: status.as_u16(),
+        method: method.to_string(),
+        error_code,
+        expires_at: expires_at.map(|at| at.to_rfc3339()),
+        expired,
+        signed_region,
+        bucket_region,
+        content_length: if status.is_success() { content_length } else { None },
+        content_type: if status.is_success() { content_type } else { None },
+    })
+}
+
+/// Objects presigned by `generate_share_manifest` unless the caller asks for fewer
+const DEFAULT_SHARE_MANIFEST_OBJECTS: usize = 1000;
+const MAX_SHARE_MANIFEST_OBJECTS: usize = 5000;
+const SHARE_PRESIGN_CONCURRENCY: usize = 16;
+/// Longest expiry SigV4 presigned URLs allow: seven days
+const MAX_PRESIGN_EXPIRY_SECS: u64 = 604_800;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ShareManifestEntry {
+    pub key: String,
+    pub url: String,
+    pub size: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShareManifest {
+    pub bucket: String,
+    pub prefix: String,
+    pub expires_in: u64,
+    pub expires_at: String,
+    pub entries: Vec<ShareManifestEntry>,
+    /// More objects existed under the prefix than were presigned
+    pub truncated: bool,
+    /// Where the HTML/JSON index was written, if one was requested
+    pub index_path: Option<String>,
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_share_manifest_html(manifest: &ShareManifest) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">");
+    html.push_str(&format!(
+        "<title>s3://{}/{}</title></head><body>\n<h1>s3://{}/{}</h1>\n<p>Links expire {}</p>\n<ul>\n",
+        escape_html(&manifest.bucket),
+        escape_html(&manifest.prefix),
+        escape_html(&manifest.bucket),
+        escape_html(&manifest.prefix),
+        escape_html(&manifest.expires_at),
+    ));
+    for entry in &manifest.entries {
+        let name = entry.key.strip_prefix(&manifest.prefix).unwrap_or(&entry.key);
+        html.push_str(&format!(
+            "<li><a href=\"{}\">{}</a> ({})</li>\n",
+            escape_html(&entry.url),
+            escape_html(name),
+            crate::s3::format_size(entry.size as u64),
+        ));
+    }
+    html.push_str("</ul>\n</body></html>\n");
+    html
+}
+
+/// Lists up to `limit` downloadable objects under `prefix`. A page that times out
+/// fails the outer result; S3 errors are kept in the inner one so the caller can
+/// look for a region redirect.
+async fn list_share_candidates(
+    client: &Client,
+    bucket_name: &str,
+    prefix: &str,
+    limit: usize,
+    timeout: std::time::Duration,
+) -> Result<
+    std::result::Result<
+        (Vec<(String, i64)>, bool),
+        aws_sdk_s3::error::SdkError<aws_sdk_s3::operation::list_objects_v2::ListObjectsV2Error>,
+    >,
+> {
+    let mut objects = Vec::new();
+    let mut continuation_token = None;
+
+    loop {
+        let request = client
+            .list_objects_v2()
+            .bucket(bucket_name)
+            .prefix(prefix)
+            .set_continuation_token(continuation_token.take())
+            .send();
+        let output = match crate::s3::with_operation_timeout(timeout, "ListObjectsV2", request).await? {
+            Ok(output) => output,
+            Err(err) => return Ok(Err(err)),
+        };
+
+        for obj in output.contents() {
+            let key = obj.key().unwrap_or_default();
+            // Folder markers have nothing to download
+            if key.ends_with('/') && obj.size().unwrap_or(0) == 0 {
+                continue;
+            }
+            if objects.len() == limit {
+                return Ok(Ok((objects, true)));
+            }
+            objects.push((key.to_string(), obj.size().unwrap_or(0)));
+        }
+
+        match output.next_continuation_token() {
+            Some(token) if output.is_truncated().unwrap_or(false) => {
+                continuation_token = Some(token.to_string());
+            }
+            _ => return Ok(Ok((objects, false))),
+        }
+    }
+}
+
+/// Presign GET links for every object under a prefix so a batch of files can be
+/// shared, optionally writing an index next to them. The index is HTML when
+/// `index_path` ends in `.html`/`.htm` and JSON otherwise.
+#[tauri::command]
+pub async fn generate_share_manifest(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    prefix: String,
+    expires_in: u64,
+    max_objects: Option<usize>,
+    index_path: Option<String>,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<ShareManifest> {
+    use futures::StreamExt;
+
+    if expires_in == 0 || expires_in > MAX_PRESIGN_EXPIRY_SECS {
+        return Err(crate::error::AppError::InvalidContent(format!(
+            "Links must expire within 1 to {} seconds (7 days)",
+            MAX_PRESIGN_EXPIRY_SECS
+        )));
+    }
+
+    let limit = max_objects
+        .unwrap_or(DEFAULT_SHARE_MANIFEST_OBJECTS)
+        .clamp(1, MAX_SHARE_MANIFEST_OBJECTS);
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let (_, mut client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
+    // Presigning never talks to S3, so the listing is what proves the region is
+    // right before any URL is signed with it.
+    let timeout = active_profile.operation_timeout();
+    let (objects, truncated) = match list_share_candidates(&client, &bucket_name, &prefix, limit, timeout).await? {
+        Ok(listed) => listed,
+        Err(err) => {
+            log::warn!("generate_share_manifest listing failed, attempting region discovery: {}", err);
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, crate::s3::region_from_redirect(&err), |client| {
+                let (bucket_name, prefix) = (&bucket_name, &prefix);
+                async move { list_share_candidates(&client, bucket_name, prefix, limit, timeout).await }
+            }).await?;
+            let Some(retry) = retry else {
+                return Err(crate::error::AppError::S3Error(err.to_string()));
+            };
+            client = retry.client;
+            retry.result?.map_err(|e| crate::error::AppError::S3Error(format!("Retry failed: {}", e)))?
+        }
+    };
+
+    let expires_at = (chrono::Utc::now() + chrono::Duration::seconds(expires_in as i64)).to_rfc3339();
+    let results: Vec<Result<ShareManifestEntry>> = futures::stream::iter(objects)
+        .map(|(key, size)| {
+            let client = client.clone();
+            let bucket_name = bucket_name.clone();
+            async move {
+                // Signing may still have to fetch credentials
+                let presign = presign_with_headers(&client, "GET", &bucket_name, &key, &[], expires_in);
+                let presigned = crate::s3::with_operation_timeout(timeout, "GetObject presign", presign).await??;
+                Ok(ShareManifestEntry {
+                    key,
+                    url: presigned.uri().to_string(),
+                    size,
+                })
+            }
+        })
+        .buffer_unordered(SHARE_PRESIGN_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut entries = results.into_iter().collect::<Result<Vec<_>>>()?;
+    entries.sort_by(|a, b| a.key.cmp(&b.key));
+
+    let mut manifest = ShareManifest {
+        bucket: bucket_name,
+        prefix,
+        expires_in,
+        expires_at,
+        entries,
+        truncated,
+        index_path: None,
+    };
+
+    if let Some(path) = index_path {
+        let lower = path.to_ascii_lowercase();
+        let contents = if lower.ends_with(".html") || lower.ends_with(".htm") {
+            render_share_manifest_html(&manifest)
+        } else {
+            serde_json::to_string_pretty(&manifest)?
+        };
+        tokio::fs::write(&path, contents).await?;
+        manifest.index_path = Some(path);
+    }
+
+    Ok(manifest)
+}
+
+#[tauri::command]
+pub async fn get_object_content(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    key: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<String> {
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let (_, client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
     let result = client
         .get_object()
         .bucket(&bucket_name)
@@ -745,32 +1735,26 @@ pub async fn get_object_content(
         Ok(res) => res,
         Err(err) => {
             log::warn!("get_object_content failed, attempting region discovery: {}", err);
-            let detected_region = {
-                let retry_client = {
-                   let mut s3_manager = s3_state.write().await;
-                   s3_manager.get_client(&active_profile).await?.clone()
-                };
-                crate::s3::get_bucket_region(&retry_client, &bucket_name).await.ok()
-            };
-
-            if let Some(new_region) = detected_region {
-                let new_client = {
-                    let mut s3_manager = s3_state.write().await;
-                    s3_manager.set_bucket_region(&bucket_name, new_region.clone());
-                    s3_manager.get_client_for_region(&active_profile, &new_region).await?.clone()
-                };
-                new_client.get_object().bucket(&bucket_name).key(&key).send().await
-                    .map_err(|e| crate::error::AppError::S3Error(format!("Retry get content failed: {}", e)))?
-            } else {
-                return Err(crate::error::AppError::S3Error(err.to_string()));
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, crate::s3::region_from_redirect(&err), |client| {
+                client.get_object().bucket(&bucket_name).key(&key).send()
+            }).await?;
+
+            match retry {
+                Some(retry) => retry.result
+                    .map_err(|e| crate::error::AppError::S3Error(format!("Retry get content failed: {}", e)))?,
+                None => return Err(crate::error::AppError::S3Error(err.to_string())),
             }
         }
     };
 
+    let is_gzip = is_gzip_encoding(response.content_encoding());
     let body = response.body.collect().await
         .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?;
 
-    let bytes = body.into_bytes().to_vec();
+    let mut bytes = body.into_bytes().to_vec();
+    if is_gzip {
+        bytes = gunzip_bytes(&bytes)?;
+    }
     let content = String::from_utf8(bytes.clone()).map_err(|_| {
         crate::error::AppError::InvalidContent(
             "This object is not readable as UTF-8 text. Download it to inspect locally.".to_string(),
@@ -786,39 +1770,184 @@ pub async fn get_object_content(
     Ok(content)
 }
 
+/// Largest window `read_object_window` will fetch at once
+const MAX_WINDOW_BYTES: u64 = 8 * 1024 * 1024;
+
+#[derive(Debug, Serialize)]
+pub struct ObjectWindow {
+    pub text: String,
+    /// Byte range of `text` within the object, end exclusive
+    pub start: u64,
+    pub end: u64,
+    pub total_size: u64,
+}
+
+/// Which part of a fetched range forms whole lines. `fetched_from` is the object
+/// offset of `bytes[0]` and is one byte before the requested offset unless that
+/// offset is 0, so a line that starts exactly at the offset is recognised.
+/// Falls back to the raw range when it holds no complete line.
+fn line_aligned_range(bytes: &[u8], fetched_from: u64, offset: u64, reaches_end: bool) -> (usize, usize) {
+    let requested = (offset - fetched_from) as usize;
+    let start = if offset == 0 || bytes.get(requested.wrapping_sub(1)) == Some(&b'\n') {
+        requested
+    } else {
+        match bytes[requested..].iter().position(|byte| *byte == b'\n') {
+            Some(newline) => requested + newline + 1,
+            None => requested,
+        }
+    };
+
+    let end = if reaches_end {
+        bytes.len()
+    } else {
+        match bytes[start..].iter().rposition(|byte| *byte == b'\n') {
+            Some(newline) => start + newline + 1,
+            None => bytes.len(),
+        }
+    };
+    (start.min(end), end)
+}
+
+/// Object size from a Content-Range header: "bytes 0-99/12345" or, on a 416,
+/// "bytes */12345". `None` when the size is given as "*".
+fn content_range_total(range: &str) -> Option<u64> {
+    range.rsplit('/').next()?.trim().parse().ok()
+}
+
+/// Read a window of a large text object starting near `offset`, trimmed to whole
+/// lines so the editor can page through logs without loading the whole file. The
+/// returned `start`/`end` say which bytes were actually served, so the next window
+/// can start at `end`.
+#[tauri::command]
+pub async fn read_object_window(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    key: String,
+    offset: u64,
+    length: u64,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<ObjectWindow> {
+    let length = length.clamp(1, MAX_WINDOW_BYTES);
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let (_, client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
+    let window_end = offset.checked_add(length).ok_or_else(|| {
+        crate::error::AppError::InvalidContent(format!("Offset {} is past the largest possible object", offset))
+    })?;
+    let fetched_from = offset.saturating_sub(1);
+    let range = format!("bytes={}-{}", fetched_from, window_end - 1);
+    let result = client.get_object().bucket(&bucket_name).key(&key).range(&range).send().await;
+
+    let response = match result {
+        Ok(res) => res,
+        // Past the end of the object. The 416 reports the size as "bytes */12345";
+        // providers that leave it out get a HeadObject instead.
+        Err(err) if err.raw_response().map(|r| r.status().as_u16()) == Some(416) => {
+            let reported = err
+                .raw_response()
+                .and_then(|r| r.headers().get("content-range"))
+                .and_then(content_range_total);
+            let total_size = match reported {
+                Some(total) => total,
+                None => client
+                    .head_object()
+                    .bucket(&bucket_name)
+                    .key(&key)
+                    .send()
+                    .await
+                    .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?
+                    .content_length()
+                    .unwrap_or(0)
+                    .max(0) as u64,
+            };
+            return Ok(ObjectWindow { text: String::new(), start: offset, end: offset, total_size });
+        }
+        Err(err) => {
+            log::warn!("read_object_window failed, attempting region discovery: {}", err);
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, crate::s3::region_from_redirect(&err), |client| {
+                client.get_object().bucket(&bucket_name).key(&key).range(&range).send()
+            }).await?;
+            let Some(retry) = retry else {
+                return Err(crate::error::AppError::S3Error(err.to_string()));
+            };
+            retry.result
+                .map_err(|e| crate::error::AppError::S3Error(format!("Retry range read failed: {}", e)))?
+        }
+    };
+
+    if is_gzip_encoding(response.content_encoding()) {
+        return Err(crate::error::AppError::InvalidContent(
+            "This object is gzip-compressed and can't be read in windows. Open it in full instead.".to_string(),
+        ));
+    }
+
+    let total_size = response
+        .content_range()
+        .and_then(content_range_total)
+        .unwrap_or_else(|| response.content_length().unwrap_or(0).max(0) as u64);
+    let bytes = response.body.collect().await
+        .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?
+        .into_bytes();
+
+    let reaches_end = fetched_from + bytes.len() as u64 >= total_size;
+    let (start, end) = line_aligned_range(&bytes, fetched_from, offset.min(fetched_from + bytes.len() as u64), reaches_end);
+    let window = &bytes[start..end];
+    if is_likely_binary_text_mismatch(window) {
+        return Err(crate::error::AppError::InvalidContent(
+            "This object appears to contain binary data and can't be shown as text.".to_string(),
+        ));
+    }
+
+    // A window cut inside a long line may split a multi-byte character
+    let text = match std::str::from_utf8(window) {
+        Ok(text) => text.to_string(),
+        Err(e) => String::from_utf8_lossy(&window[..e.valid_up_to()]).into_owned(),
+    };
+
+    Ok(ObjectWindow {
+        start: fetched_from + start as u64,
+        end: fetched_from + start as u64 + text.len() as u64,
+        text,
+        total_size,
+    })
+}
+
+/// Write text content to an object. With `auto_compress`, the body is gzipped and
+/// stored with `Content-Encoding: gzip`; `append_gz_extension` additionally adds a
+/// `.gz` suffix to the key. Returns the key that was written.
 #[tauri::command]
 pub async fn put_object_content(
     bucket_name: String,
     bucket_region: Option<String>,
     key: String,
     content: String,
+    auto_compress: Option<bool>,
+    append_gz_extension: Option<bool>,
     profile_state: State<'_, ProfileState>,
     s3_state: State<'_, S3State>,
-) -> Result<()> {
+) -> Result<String> {
     use aws_sdk_s3::primitives::ByteStream;
 
-    let profile_manager = profile_state.read().await;
-    let active_profile = profile_manager
-        .get_active_profile()
-        .await?
-        .ok_or_else(|| crate::error::AppError::ProfileNotFound("No active profile".into()))?;
-    drop(profile_manager);
+    let compress = auto_compress.unwrap_or(false);
+    let key = if compress && append_gz_extension.unwrap_or(false) && !key.ends_with(".gz") {
+        format!("{}.gz", key)
+    } else {
+        key
+    };
 
-    let bucket_region = {
-        let s3_manager = s3_state.read().await;
-        s3_manager.get_bucket_region(&bucket_name)
-    }.or(bucket_region);
+    let active_profile = require_active_profile(&profile_state).await?;
 
-    let client = {
-        let mut s3_manager = s3_state.write().await;
-        if let Some(ref region) = bucket_region {
-            s3_manager.get_client_for_region(&active_profile, region).await?.clone()
-        } else {
-            s3_manager.get_client(&active_profile).await?.clone()
-        }
-    };
+    let (_, client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
 
-    let body_bytes = content.into_bytes();
+    let body_bytes = if compress {
+        gzip_bytes(content.as_bytes())?
+    } else {
+        content.into_bytes()
+    };
+    let content_encoding = compress.then_some("gzip");
     let body = ByteStream::from(body_bytes.clone());
 
     let result = client
@@ -826,34 +1955,234 @@ pub async fn put_object_content(
         .bucket(&bucket_name)
         .key(&key)
         .body(body)
+        .set_content_encoding(content_encoding.map(str::to_string))
         .send()
         .await;
 
     match result {
-        Ok(_) => Ok(()),
+        Ok(_) => Ok(key),
         Err(err) => {
             log::warn!("put_object_content failed, attempting region discovery: {}", err);
-            let detected_region = {
-                let retry_client = {
-                   let mut s3_manager = s3_state.write().await;
-                   s3_manager.get_client(&active_profile).await?.clone()
-                };
-                crate::s3::get_bucket_region(&retry_client, &bucket_name).await.ok()
-            };
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, crate::s3::region_from_redirect(&err), |client| {
+                client.put_object().bucket(&bucket_name).key(&key).body(ByteStream::from(body_bytes.clone()))
+                    .set_content_encoding(content_encoding.map(str::to_string))
+                    .send()
+            }).await?;
+
+            match retry {
+                Some(retry) => {
+                    retry.result
+                        .map_err(|e| crate::error::AppError::S3Error(format!("Retry put content failed: {}", e)))?;
+                    Ok(key)
+                }
+                None => Err(crate::error::AppError::S3Error(err.to_string())),
+            }
+        }
+    }
+}
 
-            if let Some(new_region) = detected_region {
-                let new_client = {
-                    let mut s3_manager = s3_state.write().await;
-                    s3_manager.set_bucket_region(&bucket_name, new_region.clone());
-                    s3_manager.get_client_for_region(&active_profile, &new_region).await?.clone()
-                };
-                let retry_body = ByteStream::from(body_bytes);
-                new_client.put_object().bucket(&bucket_name).key(&key).body(retry_body).send().await
-                    .map_err(|e| crate::error::AppError::S3Error(format!("Retry put content failed: {}", e)))?;
-                Ok(())
-            } else {
-                Err(crate::error::AppError::S3Error(err.to_string()))
+/// Bytes read from the start of an object to recognise its type
+const SNIFF_BYTES: u64 = 8 * 1024;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SniffedContentType {
+    pub content_type: Option<String>,
+    /// Whether `content_type` was recognised from the object's bytes rather than
+    /// taken from its stored Content-Type
+    pub detected: bool,
+    /// Usual extension for a recognised type, without the dot
+    pub extension: Option<String>,
+}
+
+/// Recognise a type from magic bytes, falling back to the stored Content-Type
+fn sniffed_content_type(bytes: &[u8], stored: Option<&str>) -> SniffedContentType {
+    match infer::get(bytes) {
+        Some(kind) => SniffedContentType {
+            content_type: Some(kind.mime_type().to_string()),
+            detected: true,
+            extension: Some(kind.extension().to_string()),
+        },
+        None => SniffedContentType {
+            content_type: stored.filter(|value| !value.is_empty()).map(str::to_string),
+            detected: false,
+            extension: None,
+        },
+    }
+}
+
+/// The real type of an object, recognised from its first few KB since keys
+/// often have a wrong or missing extension. Types without a signature (plain
+/// text, JSON, CSV) get the object's stored Content-Type instead.
+#[tauri::command]
+pub async fn sniff_content_type(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    key: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<SniffedContentType> {
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let (_, mut client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
+    let range = format!("bytes=0-{}", SNIFF_BYTES - 1);
+    let result = client
+        .get_object()
+        .bucket(&bucket_name)
+        .key(&key)
+        .range(&range)
+        .send()
+        .await;
+
+    let result = match result {
+        Err(err) if !is_invalid_range(&err) => {
+            log::warn!("sniff_content_type failed, attempting region discovery: {}", err);
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, crate::s3::region_from_redirect(&err), |client| {
+                client.get_object().bucket(&bucket_name).key(&key).range(&range).send()
+            }).await?;
+
+            match retry {
+                Some(retry) => {
+                    client = retry.client;
+                    retry.result
+                }
+                None => return Err(crate::error::AppError::S3Error(err.to_string())),
             }
         }
+        result => result,
+    };
+
+    match result {
+        Ok(response) => {
+            let stored = response.content_type().map(str::to_string);
+            let bytes = response.body.collect().await
+                .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?
+                .into_bytes();
+            Ok(sniffed_content_type(&bytes, stored.as_deref()))
+        }
+        // An empty object has no first byte to range over
+        Err(err) if is_invalid_range(&err) => {
+            let head = client
+                .head_object()
+                .bucket(&bucket_name)
+                .key(&key)
+                .send()
+                .await
+                .map_err(|e| crate::error::AppError::S3Error(format!("Failed to read '{}': {}", key, e)))?;
+            Ok(sniffed_content_type(&[], head.content_type()))
+        }
+        Err(err) => Err(crate::error::AppError::S3Error(format!("Failed to read '{}': {}", key, err))),
+    }
+}
+
+fn is_invalid_range<E: aws_sdk_s3::error::ProvideErrorMetadata>(err: &E) -> bool {
+    err.code() == Some("InvalidRange")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        content_range_total, gunzip_bytes, gzip_bytes, is_gzip_encoding, line_aligned_range, matching_folder_prefixes, presigned_url_details,
+        recent_partitions, sniffed_content_type,
+    };
+
+    #[test]
+    fn sniffing_prefers_magic_bytes_over_stored_type() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        let sniffed = sniffed_content_type(png, Some("application/octet-stream"));
+        assert_eq!(sniffed.content_type.as_deref(), Some("image/png"));
+        assert!(sniffed.detected);
+        assert_eq!(sniffed.extension.as_deref(), Some("png"));
+
+        let text = sniffed_content_type(b"key,size\na,1\n", Some("text/csv"));
+        assert_eq!(text.content_type.as_deref(), Some("text/csv"));
+        assert!(!text.detected);
+        assert_eq!(sniffed_content_type(&[], Some("")).content_type, None);
+    }
+
+    #[test]
+    fn gzip_round_trips_text() {
+        let original = b"{\"level\":\"info\",\"message\":\"hello\"}\n".repeat(10);
+        let compressed = gzip_bytes(&original).unwrap();
+        assert_ne!(compressed, original);
+        assert_eq!(gunzip_bytes(&compressed).unwrap(), original);
+    }
+
+    #[test]
+    fn gzip_encoding_detection_is_case_insensitive() {
+        assert!(is_gzip_encoding(Some("gzip")));
+        assert!(is_gzip_encoding(Some("GZIP")));
+        assert!(is_gzip_encoding(Some("identity, gzip")));
+        assert!(!is_gzip_encoding(Some("br")));
+        assert!(!is_gzip_encoding(None));
+    }
+
+    #[test]
+    fn folder_prefixes_match_on_folder_name_only() {
+        assert_eq!(
+            matching_folder_prefixes("a/photos/2024/photo.jpg", "a/", "photo"),
+            vec!["a/photos/".to_string()]
+        );
+        assert!(matching_folder_prefixes("photos/x.jpg", "a/", "photo").is_empty());
+        assert_eq!(
+            matching_folder_prefixes("docs/Reports/", "", "report"),
+            vec!["docs/Reports/".to_string()]
+        );
+    }
+
+    #[test]
+    fn recent_partitions_walk_back_by_finest_field() {
+        use chrono::TimeZone;
+        let now = chrono::Utc.with_ymd_and_hms(2024, 3, 1, 5, 0, 0).unwrap();
+        assert_eq!(
+            recent_partitions("logs/%Y/%m/%d/", now, 3).unwrap(),
+            vec!["logs/2024/03/01/", "logs/2024/02/29/", "logs/2024/02/28/"]
+        );
+        assert_eq!(
+            recent_partitions("%Y-%m/", now, 2).unwrap(),
+            vec!["2024-03/", "2024-02/"]
+        );
+        assert!(recent_partitions("logs/", now, 3).is_err());
+        assert!(recent_partitions("logs/%Y/%Q/", now, 3).is_err());
+        assert!(recent_partitions("logs/%Y/%", now, 3).is_err());
+    }
+
+    #[test]
+    fn content_range_total_reads_the_size_after_the_slash() {
+        assert_eq!(content_range_total("bytes 0-99/12345"), Some(12345));
+        assert_eq!(content_range_total("bytes */12345"), Some(12345));
+        assert_eq!(content_range_total("bytes 0-99/*"), None);
+    }
+
+    #[test]
+    fn windows_are_trimmed_to_whole_lines() {
+        let text = b"one\ntwo\nthree\nfour\n";
+        // From the start, cut back to the last full line
+        assert_eq!(line_aligned_range(&text[..10], 0, 0, false), (0, 8));
+        // Offset 5 is inside "two", so the window starts at "three"
+        assert_eq!(line_aligned_range(&text[4..], 4, 5, true), (4, 15));
+        // Offset 4 starts "two" exactly; the byte before it is fetched to tell
+        assert_eq!(line_aligned_range(&text[3..], 3, 4, true), (1, 16));
+        // A single long line with no newline is returned as-is
+        assert_eq!(line_aligned_range(b"abcdef", 0, 0, false), (0, 6));
+    }
+
+    #[test]
+    fn presigned_url_details_reads_expiry_and_region() {
+        let url = reqwest::Url::parse(
+            "https://b.s3.eu-west-1.amazonaws.com/k?X-Amz-Algorithm=AWS4-HMAC-SHA256\
+             &X-Amz-Credential=AKID%2F20240307%2Feu-west-1%2Fs3%2Faws4_request\
+             &X-Amz-Date=20240307T050607Z&X-Amz-Expires=3600&X-Amz-Signature=abc",
+        )
+        .unwrap();
+        let (expires_at, region) = presigned_url_details(&url);
+        assert_eq!(expires_at.unwrap().to_rfc3339(), "2024-03-07T06:06:07+00:00");
+        assert_eq!(region.as_deref(), Some("eu-west-1"));
+
+        let url = reqwest::Url::parse(
+            "https://b.s3.amazonaws.com/k?X-Amz-Date=20240307T050607Z&X-Amz-Expires=9223372036854775807",
+        )
+        .unwrap();
+        assert_eq!(presigned_url_details(&url).0, None);
     }
 }
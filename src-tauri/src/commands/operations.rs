@@ -1,35 +1,22 @@
 use crate::commands::profiles::ProfileState;
+use crate::commands::buckets::{bucket_client, require_active_profile};
+use crate::activity::{s3_target, ActiveOperation, ActivityState};
 use crate::s3::S3State;
 use crate::error::Result;
 use aws_sdk_s3::Client;
 use aws_sdk_s3::primitives::ByteStream;
-use aws_sdk_s3::types::{Delete, ObjectCannedAcl, ObjectIdentifier};
-use tauri::State;
-use std::collections::HashSet;
+use futures::StreamExt;
+use aws_sdk_s3::types::{
+    AccessControlPolicy, Delete, Grant, Grantee, MetadataDirective, ObjectCannedAcl, ObjectIdentifier, Owner, Permission, StorageClass,
+    Tag, Tagging, Type,
+};
+use tauri::{AppHandle, Emitter, State};
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 
-async fn detect_and_cache_bucket_region(
-    active_profile: &crate::credentials::Profile,
-    bucket_name: &str,
-    s3_state: &State<'_, S3State>,
-) -> Result<Option<String>> {
-    let retry_client = {
-        let mut s3_manager = s3_state.write().await;
-        s3_manager.get_client(active_profile).await?.clone()
-    };
-
-    let detected_region = crate::s3::get_bucket_region(&retry_client, bucket_name).await.ok();
-
-    if let Some(ref new_region) = detected_region {
-        let mut s3_manager = s3_state.write().await;
-        s3_manager.set_bucket_region(bucket_name, new_region.clone());
-    }
-
-    Ok(detected_region)
-}
-
 fn validate_folder_target(
     source_bucket: &str,
     source_key: &str,
@@ -74,6 +61,107 @@ fn parse_object_canned_acl(value: &str) -> Result<ObjectCannedAcl> {
     }
 }
 
+const ACL_GROUP_URI_PREFIX: &str = "http://acs.amazonaws.com/groups/";
+
+/// An explicit ACL grant to apply instead of a canned ACL
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct AclGrantInput {
+    /// `CanonicalUser`, `Group` or `AmazonCustomerByEmail` (or `Email`)
+    pub grantee_type: String,
+    /// Canonical user ID, group URI or short group name (`AllUsers`,
+    /// `AuthenticatedUsers`, `LogDelivery`), or email address
+    pub grantee: String,
+    /// `READ`, `READ_ACP`, `WRITE_ACP` or `FULL_CONTROL`
+    pub permission: String,
+}
+
+/// What `set_object_permissions` applies to each target object
+enum AclChange {
+    Canned(ObjectCannedAcl),
+    Grants(Vec<Grant>),
+}
+
+fn parse_acl_permission(value: &str) -> Result<Permission> {
+    match value.trim().to_ascii_uppercase().replace('-', "_").as_str() {
+        "READ" => Ok(Permission::Read),
+        // WRITE has no meaning on an object; S3 only honours it on buckets
+        "WRITE" => Err(crate::error::AppError::ConfigError(
+            "WRITE can only be granted on buckets, not objects".into(),
+        )),
+        "READ_ACP" => Ok(Permission::ReadAcp),
+        "WRITE_ACP" => Ok(Permission::WriteAcp),
+        "FULL_CONTROL" => Ok(Permission::FullControl),
+        _ => Err(crate::error::AppError::ConfigError(format!(
+            "Unsupported ACL permission '{}'",
+            value
+        ))),
+    }
+}
+
+fn build_acl_grant(input: &AclGrantInput) -> Result<Grant> {
+    let grantee = input.grantee.trim();
+    if grantee.is_empty() {
+        return Err(crate::error::AppError::ConfigError("ACL grant has no grantee".into()));
+    }
+
+    let builder = match input.grantee_type.trim().to_ascii_lowercase().as_str() {
+        "canonicaluser" | "canonical_user" => Grantee::builder().r#type(Type::CanonicalUser).id(grantee),
+        "group" => {
+            let uri = if grantee.contains("://") {
+                grantee.to_string()
+            } else {
+                match grantee.to_ascii_lowercase().as_str() {
+                    "allusers" => format!("{}global/AllUsers", ACL_GROUP_URI_PREFIX),
+                    "authenticatedusers" => format!("{}global/AuthenticatedUsers", ACL_GROUP_URI_PREFIX),
+                    "logdelivery" => format!("{}s3/LogDelivery", ACL_GROUP_URI_PREFIX),
+                    _ => {
+                        return Err(crate::error::AppError::ConfigError(format!(
+                            "Unknown ACL group '{}'",
+                            grantee
+                        )))
+                    }
+                }
+            };
+            Grantee::builder().r#type(Type::Group).uri(uri)
+        }
+        "amazoncustomerbyemail" | "email" => Grantee::builder().r#type(Type::AmazonCustomerByEmail).email_address(grantee),
+        _ => {
+            return Err(crate::error::AppError::ConfigError(format!(
+                "Unsupported ACL grantee type '{}'",
+                input.grantee_type
+            )))
+        }
+    };
+    let grantee = builder
+        .build()
+        .map_err(|e| crate::error::AppError::ConfigError(e.to_string()))?;
+
+    Ok(Grant::builder()
+        .grantee(grantee)
+        .permission(parse_acl_permission(&input.permission)?)
+        .build())
+}
+
+/// The grants plus full control for the owner, unless they already have it, so
+/// an explicit grant list can't lock the owner out of their own object
+fn access_control_policy(owner: Owner, mut grants: Vec<Grant>) -> AccessControlPolicy {
+    if let Some(owner_id) = owner.id() {
+        let owner_has_full_control = grants.iter().any(|grant| {
+            grant.permission() == Some(&Permission::FullControl)
+                && grant.grantee().and_then(|grantee| grantee.id()) == Some(owner_id)
+        });
+        if !owner_has_full_control {
+            if let Ok(grantee) = Grantee::builder().r#type(Type::CanonicalUser).id(owner_id).build() {
+                grants.insert(0, Grant::builder().grantee(grantee).permission(Permission::FullControl).build());
+            }
+        }
+    }
+    AccessControlPolicy::builder()
+        .owner(owner)
+        .set_grants(Some(grants))
+        .build()
+}
+
 fn classify_acl_error(error: &str) -> Option<(&'static str, &'static str)> {
     let normalized = error.to_ascii_lowercase();
 
@@ -118,11 +206,122 @@ fn map_acl_error(error: impl ToString) -> crate::error::AppError {
     }
 }
 
+/// Walks `list_objects_v2` under a prefix one page at a time for the commands
+/// that work through everything under a folder. A failed first page triggers
+/// region discovery; later pages, and follow-up requests made through
+/// [`ObjectPages::client`], use the client that worked.
+pub(crate) struct ObjectPages<'a> {
+    s3_state: &'a S3State,
+    profile: &'a crate::credentials::Profile,
+    bucket_name: &'a str,
+    prefix: &'a str,
+    delimiter: Option<&'static str>,
+    page_size: i32,
+    client: Client,
+    continuation_token: Option<String>,
+    first_page: bool,
+    finished: bool,
+}
+
+impl<'a> ObjectPages<'a> {
+    /// Start with a client for the bucket's cached region, falling back to
+    /// `bucket_region` and then the profile's region
+    pub(crate) async fn new(
+        s3_state: &'a S3State,
+        profile: &'a crate::credentials::Profile,
+        bucket_name: &'a str,
+        bucket_region: Option<String>,
+        prefix: &'a str,
+    ) -> Result<Self> {
+        let page_size = s3_state.read().await.list_page_size();
+        let (_, client) = bucket_client(s3_state, profile, bucket_name, bucket_region).await?;
+
+        Ok(Self {
+            s3_state,
+            profile,
+            bucket_name,
+            prefix,
+            delimiter: None,
+            page_size,
+            client,
+            continuation_token: None,
+            first_page: true,
+            finished: false,
+        })
+    }
+
+    /// List only the prefix's direct children
+    fn delimited(mut self) -> Self {
+        self.delimiter = Some("/");
+        self
+    }
+
+    /// Client for the bucket's region, once the first page has found it
+    pub(crate) fn client(&self) -> &Client {
+        &self.client
+    }
+
+    fn request(&self, client: &Client) -> aws_sdk_s3::operation::list_objects_v2::builders::ListObjectsV2FluentBuilder {
+        client
+            .list_objects_v2()
+            .bucket(self.bucket_name)
+            .prefix(self.prefix)
+            .set_delimiter(self.delimiter.map(str::to_string))
+            .max_keys(self.page_size)
+            .set_continuation_token(self.continuation_token.clone())
+    }
+
+    /// The next page, or `None` once the listing is done. Each request is bounded
+    /// by the profile's operation timeout.
+    pub(crate) async fn next_page(&mut self) -> Result<Option<aws_sdk_s3::operation::list_objects_v2::ListObjectsV2Output>> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        let timeout = self.profile.operation_timeout();
+        let page = match crate::s3::with_operation_timeout(timeout, "ListObjectsV2", self.request(&self.client).send()).await? {
+            Ok(page) => page,
+            Err(err) if self.first_page => {
+                log::warn!(
+                    "Listing {} failed, attempting region discovery: {}",
+                    s3_target(self.bucket_name, self.prefix),
+                    err
+                );
+                let redirect_region = crate::s3::region_from_redirect(&err);
+                let retry = crate::s3::retry_in_bucket_region(self.s3_state, self.profile, self.bucket_name, redirect_region, |client| {
+                    let request = self.request(&client);
+                    async move {
+                        crate::s3::with_operation_timeout(timeout, "ListObjectsV2", request.send()).await?
+                            .map_err(|e| crate::error::AppError::S3Error(format!("Retry listing failed: {}", e)))
+                    }
+                }).await?;
+
+                match retry {
+                    Some(retry) => {
+                        self.client = retry.client;
+                        retry.result?
+                    }
+                    None => return Err(crate::error::AppError::S3Error(err.to_string())),
+                }
+            }
+            Err(err) => return Err(crate::error::AppError::S3Error(err.to_string())),
+        };
+        self.first_page = false;
+
+        match page.next_continuation_token().filter(|_| page.is_truncated().unwrap_or(false)) {
+            Some(token) => self.continuation_token = Some(token.to_string()),
+            None => self.finished = true,
+        }
+        Ok(Some(page))
+    }
+}
+
 async fn list_keys_for_permission_target(
     client: &Client,
     bucket_name: &str,
     key: &str,
     is_folder: bool,
+    page_size: i32,
 ) -> Result<Vec<String>> {
     if !is_folder {
         return Ok(vec![key.to_string()]);
@@ -132,7 +331,7 @@ async fn list_keys_for_permission_target(
     let mut continuation_token = None;
 
     loop {
-        let mut request = client.list_objects_v2().bucket(bucket_name).prefix(key);
+        let mut request = client.list_objects_v2().bucket(bucket_name).prefix(key).max_keys(page_size);
         if let Some(token) = continuation_token {
             request = request.continuation_token(token);
         }
@@ -172,28 +371,10 @@ pub async fn put_object(
     s3_state: State<'_, S3State>,
 ) -> Result<()> {
     // Get active profile
-    let profile_manager = profile_state.read().await;
-    let active_profile = profile_manager
-        .get_active_profile()
-        .await?
-        .ok_or_else(|| crate::error::AppError::ProfileNotFound("No active profile".into()))?;
-    drop(profile_manager);
+    let active_profile = require_active_profile(&profile_state).await?;
 
     // Check cache for bucket region first
-    let bucket_region = {
-        let s3_manager = s3_state.read().await;
-        s3_manager.get_bucket_region(&bucket_name)
-    }.or(bucket_region);
-
-    // Get S3 client
-    let client = {
-        let mut s3_manager = s3_state.write().await;
-        if let Some(ref d) = bucket_region {
-            s3_manager.get_client_for_region(&active_profile, d).await?.clone()
-        } else {
-            s3_manager.get_client(&active_profile).await?.clone()
-        }
-    };
+    let (_, client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
 
     let mut request = client
         .put_object()
@@ -213,31 +394,30 @@ pub async fn put_object(
     if let Err(err) = request.send().await {
         log::warn!("put_object failed, attempting region discovery: {}", err);
 
-        if let Some(new_region) = detect_and_cache_bucket_region(&active_profile, &bucket_name, &s3_state).await? {
-            let new_client = {
-                let mut s3_manager = s3_state.write().await;
-                s3_manager.get_client_for_region(&active_profile, &new_region).await?.clone()
-            };
-
-            let mut retry_request = new_client
-                .put_object()
-                .bucket(&bucket_name)
-                .key(&key);
-
-            if let Some(ref path) = local_path {
-                let body = ByteStream::from_path(Path::new(path)).await
-                    .map_err(|e| crate::error::AppError::IoError(e.to_string()))?;
-                retry_request = retry_request.body(body);
-            } else {
-                retry_request = retry_request.body(ByteStream::from_static(b""));
+        let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, crate::s3::region_from_redirect(&err), |client| {
+            let (bucket_name, key, local_path) = (&bucket_name, &key, &local_path);
+            async move {
+                let body = match local_path {
+                    Some(path) => ByteStream::from_path(Path::new(path)).await
+                        .map_err(|e| crate::error::AppError::IoError(e.to_string()))?,
+                    None => ByteStream::from_static(b""),
+                };
+                client
+                    .put_object()
+                    .bucket(bucket_name)
+                    .key(key)
+                    .body(body)
+                    .send()
+                    .await
+                    .map_err(|e| crate::error::AppError::S3Error(format!("Retry put failed: {}", e)))
             }
+        }).await?;
 
-            retry_request
-                .send()
-                .await
-                .map_err(|e| crate::error::AppError::S3Error(format!("Retry put failed: {}", e)))?;
-        } else {
-            return Err(crate::error::AppError::S3Error(err.to_string()));
+        match retry {
+            Some(retry) => {
+                retry.result?;
+            }
+            None => return Err(crate::error::AppError::S3Error(err.to_string())),
         }
     }
 
@@ -254,28 +434,10 @@ pub async fn get_object(
     s3_state: State<'_, S3State>,
 ) -> Result<()> {
     // Get active profile
-    let profile_manager = profile_state.read().await;
-    let active_profile = profile_manager
-        .get_active_profile()
-        .await?
-        .ok_or_else(|| crate::error::AppError::ProfileNotFound("No active profile".into()))?;
-    drop(profile_manager);
+    let active_profile = require_active_profile(&profile_state).await?;
 
     // Check cache for bucket region first
-    let bucket_region = {
-        let s3_manager = s3_state.read().await;
-        s3_manager.get_bucket_region(&bucket_name)
-    }.or(bucket_region);
-
-    // Get S3 client
-    let client = {
-        let mut s3_manager = s3_state.write().await;
-        if let Some(ref d) = bucket_region {
-            s3_manager.get_client_for_region(&active_profile, d).await?.clone()
-        } else {
-            s3_manager.get_client(&active_profile).await?.clone()
-        }
-    };
+    let (_, client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
 
     // Get object
     let result = client
@@ -290,21 +452,14 @@ pub async fn get_object(
         Err(err) => {
             log::warn!("get_object failed, attempting region discovery: {}", err);
 
-            if let Some(new_region) = detect_and_cache_bucket_region(&active_profile, &bucket_name, &s3_state).await? {
-                let new_client = {
-                    let mut s3_manager = s3_state.write().await;
-                    s3_manager.get_client_for_region(&active_profile, &new_region).await?.clone()
-                };
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, crate::s3::region_from_redirect(&err), |client| {
+                client.get_object().bucket(&bucket_name).key(&key).send()
+            }).await?;
 
-                new_client
-                    .get_object()
-                    .bucket(&bucket_name)
-                    .key(&key)
-                    .send()
-                    .await
-                    .map_err(|e| crate::error::AppError::S3Error(format!("Retry get failed: {}", e)))?
-            } else {
-                return Err(crate::error::AppError::S3Error(err.to_string()));
+            match retry {
+                Some(retry) => retry.result
+                    .map_err(|e| crate::error::AppError::S3Error(format!("Retry get failed: {}", e)))?,
+                None => return Err(crate::error::AppError::S3Error(err.to_string())),
             }
         }
     };
@@ -331,6 +486,170 @@ pub async fn get_object(
     Ok(())
 }
 
+/// Downloads made by `download_and_open` live under this directory so they can be
+/// cleaned up later. Each download gets its own subdirectory to keep the filename.
+const OPEN_DOWNLOADS_DIR: &str = "brows3-open";
+/// Opened files are left alone for a day, since the external app may still have them open.
+const OPEN_DOWNLOADS_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+#[derive(Clone, serde::Serialize)]
+pub struct OpenDownloadProgress {
+    pub key: String,
+    pub processed_bytes: u64,
+    pub total_bytes: u64,
+}
+
+async fn prune_open_downloads(root: &Path) {
+    let Ok(mut entries) = tokio::fs::read_dir(root).await else {
+        return;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let expired = entry
+            .metadata()
+            .await
+            .ok()
+            .and_then(|metadata| metadata.modified().ok())
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > OPEN_DOWNLOADS_MAX_AGE);
+        if expired {
+            if let Err(err) = tokio::fs::remove_dir_all(entry.path()).await {
+                log::warn!("Failed to remove old download {:?}: {}", entry.path(), err);
+            }
+        }
+    }
+}
+
+/// Extensions the OS would run rather than open in a viewer
+const EXECUTABLE_EXTENSIONS: &[&str] = &[
+    "app", "appimage", "application", "bat", "bin", "cmd", "com", "command", "cpl", "csh", "deb", "desktop",
+    "dmg", "exe", "gadget", "hta", "inf", "jar", "js", "jse", "ksh", "lnk", "msc", "msi", "msp", "pif", "pkg",
+    "pl", "ps1", "psm1", "py", "pyw", "rb", "reg", "rpm", "run", "scf", "scr", "sh", "terminal", "tool", "url",
+    "vb", "vbe", "vbs", "workflow", "ws", "wsf", "wsh", "zsh",
+];
+
+fn is_executable_file_name(name: &str) -> bool {
+    name.rsplit_once('.').is_some_and(|(_, extension)| {
+        EXECUTABLE_EXTENSIONS.iter().any(|executable| extension.eq_ignore_ascii_case(executable))
+    })
+}
+
+/// Native binaries and scripts with a shebang, whatever their name
+fn is_executable_content(head: &[u8]) -> bool {
+    head.starts_with(b"#!") || infer::is_app(head)
+}
+
+fn open_download_file_name(key: &str) -> Result<String> {
+    let name: String = key
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if matches!(c, '<' | '>' | ':' | '"' | '\\' | '|' | '?' | '*') || c.is_control() { '_' } else { c })
+        .collect();
+    if name.is_empty() || name == "." || name == ".." {
+        return Err(crate::error::AppError::InvalidContent(format!("'{}' is not a file", key)));
+    }
+    Ok(name)
+}
+
+/// Download an object to a temporary directory and open it with the OS default
+/// application. Progress is emitted as `open-download-progress`. Returns the local path.
+/// Executables and scripts are refused, by name or by content, since opening them
+/// would run them.
+#[tauri::command]
+pub async fn download_and_open(
+    app_handle: AppHandle,
+    bucket_name: String,
+    bucket_region: Option<String>,
+    key: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<String> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let file_name = open_download_file_name(&key)?;
+    if is_executable_file_name(&file_name) {
+        return Err(crate::error::AppError::InvalidContent(format!(
+            "'{}' is an executable or script and won't be opened; download it instead",
+            file_name
+        )));
+    }
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let (_, client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
+    let mut output = match client.get_object().bucket(&bucket_name).key(&key).send().await {
+        Ok(output) => output,
+        Err(err) => {
+            log::warn!("download_and_open failed, attempting region discovery: {}", err);
+
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, crate::s3::region_from_redirect(&err), |client| {
+                client.get_object().bucket(&bucket_name).key(&key).send()
+            }).await?;
+
+            match retry {
+                Some(retry) => retry.result
+                    .map_err(|e| crate::error::AppError::S3Error(format!("Retry get failed: {}", e)))?,
+                None => return Err(crate::error::AppError::S3Error(err.to_string())),
+            }
+        }
+    };
+
+    let root = std::env::temp_dir().join(OPEN_DOWNLOADS_DIR);
+    prune_open_downloads(&root).await;
+
+    let download_dir = root.join(uuid::Uuid::new_v4().to_string());
+    tokio::fs::create_dir_all(&download_dir).await?;
+    let local_path = download_dir.join(&file_name);
+
+    let total_bytes = output.content_length().unwrap_or(0).max(0) as u64;
+    let mut processed_bytes = 0u64;
+    let mut last_emit = std::time::Instant::now();
+    let mut file = File::create(&local_path).await?;
+
+    while let Some(bytes) = output.body.try_next().await
+        .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?
+    {
+        if processed_bytes == 0 && is_executable_content(&bytes) {
+            drop(file);
+            let _ = tokio::fs::remove_dir_all(&download_dir).await;
+            return Err(crate::error::AppError::InvalidContent(format!(
+                "'{}' is an executable or script and won't be opened; download it instead",
+                file_name
+            )));
+        }
+        file.write_all(&bytes).await?;
+        processed_bytes += bytes.len() as u64;
+
+        if last_emit.elapsed() >= std::time::Duration::from_millis(200) {
+            last_emit = std::time::Instant::now();
+            let _ = app_handle.emit("open-download-progress", OpenDownloadProgress {
+                key: key.clone(),
+                processed_bytes,
+                total_bytes,
+            });
+        }
+    }
+    file.flush().await?;
+    drop(file);
+
+    let _ = app_handle.emit("open-download-progress", OpenDownloadProgress {
+        key: key.clone(),
+        processed_bytes,
+        total_bytes: total_bytes.max(processed_bytes),
+    });
+
+    let local_path = local_path.to_string_lossy().to_string();
+    // Opening from Rust isn't subject to the JS open scope, so local paths are allowed.
+    app_handle
+        .opener()
+        .open_path(local_path.clone(), None::<&str>)
+        .map_err(|e| crate::error::AppError::IoError(format!("Failed to open '{}': {}", local_path, e)))?;
+
+    Ok(local_path)
+}
+
 #[tauri::command]
 pub async fn delete_object(
     bucket_name: String,
@@ -340,28 +659,10 @@ pub async fn delete_object(
     s3_state: State<'_, S3State>,
 ) -> Result<()> {
     // Get active profile
-    let profile_manager = profile_state.read().await;
-    let active_profile = profile_manager
-        .get_active_profile()
-        .await?
-        .ok_or_else(|| crate::error::AppError::ProfileNotFound("No active profile".into()))?;
-    drop(profile_manager);
+    let active_profile = require_active_profile(&profile_state).await?;
 
     // Check cache for bucket region first
-    let bucket_region = {
-        let s3_manager = s3_state.read().await;
-        s3_manager.get_bucket_region(&bucket_name)
-    }.or(bucket_region);
-
-    // Get S3 client
-    let client = {
-        let mut s3_manager = s3_state.write().await;
-        if let Some(ref d) = bucket_region {
-            s3_manager.get_client_for_region(&active_profile, d).await?.clone()
-        } else {
-            s3_manager.get_client(&active_profile).await?.clone()
-        }
-    };
+    let (_, client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
 
     let result = client
         .delete_object()
@@ -373,21 +674,16 @@ pub async fn delete_object(
     if let Err(err) = result {
         log::warn!("delete_object failed, attempting region discovery: {}", err);
 
-        if let Some(new_region) = detect_and_cache_bucket_region(&active_profile, &bucket_name, &s3_state).await? {
-            let new_client = {
-                let mut s3_manager = s3_state.write().await;
-                s3_manager.get_client_for_region(&active_profile, &new_region).await?.clone()
-            };
+        let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, crate::s3::region_from_redirect(&err), |client| {
+            client.delete_object().bucket(&bucket_name).key(&key).send()
+        }).await?;
 
-            new_client
-                .delete_object()
-                .bucket(&bucket_name)
-                .key(&key)
-                .send()
-                .await
-                .map_err(|e| crate::error::AppError::S3Error(format!("Retry delete failed: {}", e)))?;
-        } else {
-            return Err(crate::error::AppError::S3Error(err.to_string()));
+        match retry {
+            Some(retry) => {
+                retry.result
+                    .map_err(|e| crate::error::AppError::S3Error(format!("Retry delete failed: {}", e)))?;
+            }
+            None => return Err(crate::error::AppError::S3Error(err.to_string())),
         }
     }
 
@@ -402,6 +698,7 @@ pub async fn copy_object(
     destination_bucket: String,
     destination_region: Option<String>,
     destination_key: String,
+    preserve_storage_class: Option<bool>,
     profile_state: State<'_, ProfileState>,
     s3_state: State<'_, S3State>,
 ) -> Result<()> {
@@ -409,13 +706,9 @@ pub async fn copy_object(
         return Ok(());
     }
     validate_folder_target(&source_bucket, &source_key, &destination_bucket, &destination_key)?;
+    let preserve_storage_class = preserve_storage_class.unwrap_or(false);
 
-    let profile_manager = profile_state.read().await;
-    let active_profile = profile_manager
-        .get_active_profile()
-        .await?
-        .ok_or_else(|| crate::error::AppError::ProfileNotFound("No active profile".into()))?;
-    drop(profile_manager);
+    let active_profile = require_active_profile(&profile_state).await?;
 
     // Check if this is a folder copy (key ends with /)
     if source_key.ends_with('/') {
@@ -424,28 +717,19 @@ pub async fn copy_object(
                    source_bucket, source_key, destination_bucket, destination_key);
         
         // Get client for listing source bucket
-        let source_region_resolved = {
-            let s3_manager = s3_state.read().await;
-            s3_manager.get_bucket_region(&source_bucket)
-        }.or(source_region.clone());
-        
-        let client = {
-            let mut s3_manager = s3_state.write().await;
-            if let Some(ref r) = source_region_resolved {
-                s3_manager.get_client_for_region(&active_profile, r).await?.clone()
-            } else {
-                s3_manager.get_client(&active_profile).await?.clone()
-            }
-        };
+        let page_size = s3_state.read().await.list_page_size();
+        let (_, client) = bucket_client(&s3_state, &active_profile, &source_bucket, source_region.clone()).await?;
         
         // List all objects under the source prefix
         let mut continuation_token = None;
         let mut all_keys = Vec::new();
+        let mut storage_classes = HashMap::new();
         
         loop {
             let mut req = client.list_objects_v2()
                 .bucket(&source_bucket)
-                .prefix(&source_key);
+                .prefix(&source_key)
+                .max_keys(page_size);
             
             if let Some(token) = continuation_token {
                 req = req.continuation_token(token);
@@ -457,6 +741,9 @@ pub async fn copy_object(
             if let Some(contents) = resp.contents {
                 for obj in contents {
                     if let Some(key) = obj.key {
+                        if let Some(class) = obj.storage_class {
+                            storage_classes.insert(key.clone(), class.as_str().to_string());
+                        }
                         all_keys.push(key);
                     }
                 }
@@ -485,6 +772,7 @@ pub async fn copy_object(
                 &destination_bucket,
                 destination_region.clone(),
                 &new_key,
+                preserved_class(preserve_storage_class, &storage_classes, key),
                 &active_profile,
                 &s3_state,
             ).await?;
@@ -511,51 +799,133 @@ pub async fn copy_object(
         Ok(())
     } else {
         // Single file copy
+        let storage_class = if preserve_storage_class {
+            source_storage_class(&source_bucket, source_region, &source_key, &active_profile, &s3_state).await?
+        } else {
+            None
+        };
+
         copy_single_object(
             &source_bucket,
             &source_key,
             &destination_bucket,
             destination_region,
             &destination_key,
+            storage_class.as_deref(),
             &active_profile,
             &s3_state,
         ).await
     }
 }
 
-/// Internal helper for copying a single object (non-recursive)
+/// Storage class to carry over for `key`, if preservation is on and the listing
+/// reported one.
+fn preserved_class<'a>(
+    preserve: bool,
+    storage_classes: &'a HashMap<String, String>,
+    key: &str,
+) -> Option<&'a str> {
+    if preserve {
+        storage_classes.get(key).map(String::as_str)
+    } else {
+        None
+    }
+}
+
+async fn source_storage_class(
+    source_bucket: &str,
+    source_region: Option<String>,
+    source_key: &str,
+    active_profile: &crate::credentials::Profile,
+    s3_state: &State<'_, S3State>,
+) -> Result<Option<String>> {
+    let (_, client) = bucket_client(s3_state, active_profile, source_bucket, source_region).await?;
+
+    let head = client
+        .head_object()
+        .bucket(source_bucket)
+        .key(source_key)
+        .send()
+        .await
+        .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?;
+
+    Ok(head.storage_class().map(|class| class.as_str().to_string()))
+}
+
+/// Carry an object's server-side encryption, read from its HEAD, into a copy of
+/// it. CopyObject otherwise encrypts the copy with the bucket's default, so a
+/// KMS-encrypted object would lose its key.
+pub(crate) fn keep_encryption(
+    request: aws_sdk_s3::operation::copy_object::builders::CopyObjectFluentBuilder,
+    head: &aws_sdk_s3::operation::head_object::HeadObjectOutput,
+) -> aws_sdk_s3::operation::copy_object::builders::CopyObjectFluentBuilder {
+    request
+        .set_server_side_encryption(head.server_side_encryption().cloned())
+        .set_ssekms_key_id(head.ssekms_key_id().map(str::to_string))
+        .set_bucket_key_enabled(head.bucket_key_enabled())
+}
+
+/// Server-side copy of one object within a bucket, keeping its metadata, tags and
+/// encryption. The copy is written in `storage_class`, or STANDARD without one.
+/// Objects too large for a single CopyObject are copied in parts.
+pub(crate) async fn copy_within_bucket(
+    client: &Client,
+    bucket_name: &str,
+    source_key: &str,
+    destination_key: &str,
+    storage_class: Option<StorageClass>,
+) -> Result<()> {
+    let head = client
+        .head_object()
+        .bucket(bucket_name)
+        .key(source_key)
+        .send()
+        .await
+        .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?;
+
+    let size = head.content_length().unwrap_or(0).max(0) as u64;
+    if size > crate::transfer::multipart::MAX_COPY_OBJECT_SIZE {
+        return crate::transfer::multipart::copy_in_parts(client, bucket_name, source_key, &head, destination_key, storage_class).await;
+    }
+
+    keep_encryption(client.copy_object(), &head)
+        .bucket(bucket_name)
+        .key(destination_key)
+        .copy_source(crate::s3::encode_copy_source(bucket_name, source_key))
+        .metadata_directive(MetadataDirective::Copy)
+        .set_storage_class(storage_class)
+        .send()
+        .await
+        .map(|_| ())
+        .map_err(|e| crate::error::AppError::S3Error(e.to_string()))
+}
+
+/// Internal helper for copying a single object (non-recursive).
+/// Content type and user metadata are always copied from the source; the storage
+/// class is only kept when `storage_class` is given, otherwise S3 writes STANDARD.
+#[allow(clippy::too_many_arguments)]
 async fn copy_single_object(
     source_bucket: &str,
     source_key: &str,
     destination_bucket: &str,
     destination_region: Option<String>,
     destination_key: &str,
+    storage_class: Option<&str>,
     active_profile: &crate::credentials::Profile,
     s3_state: &State<'_, S3State>,
 ) -> Result<()> {
-    // Check cache for bucket region first
-    let destination_region = {
-        let s3_manager = s3_state.read().await;
-        s3_manager.get_bucket_region(destination_bucket)
-    }.or(destination_region);
-
-    let mut s3_manager = s3_state.write().await;
     // We need the client for the DESTINATION region to initiate copy
-    let client = if let Some(ref d) = destination_region {
-        s3_manager.get_client_for_region(active_profile, d).await?
-    } else {
-        s3_manager.get_client(active_profile).await?
-    };
+    let (_, client) = bucket_client(s3_state, active_profile, destination_bucket, destination_region).await?;
 
-    // Copy source must be URL encoded
-    let key_encoded = urlencoding::encode(source_key).into_owned();
-    let final_source = format!("{}/{}", source_bucket, key_encoded);
+    let final_source = crate::s3::encode_copy_source(source_bucket, source_key);
 
     client
         .copy_object()
         .bucket(destination_bucket)
         .key(destination_key)
         .copy_source(final_source)
+        .metadata_directive(MetadataDirective::Copy)
+        .set_storage_class(storage_class.map(StorageClass::from))
         .send()
         .await
         .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?;
@@ -643,27 +1013,10 @@ pub async fn delete_objects(
         return Ok(());
     }
 
-    let profile_manager = profile_state.read().await;
-    let active_profile = profile_manager
-        .get_active_profile()
-        .await?
-        .ok_or_else(|| crate::error::AppError::ProfileNotFound("No active profile".into()))?;
-    drop(profile_manager);
+    let active_profile = require_active_profile(&profile_state).await?;
 
     // Check cache for bucket region first
-    let bucket_region = {
-        let s3_manager = s3_state.read().await;
-        s3_manager.get_bucket_region(&bucket_name)
-    }.or(bucket_region);
-
-    let client = {
-        let mut s3_manager = s3_state.write().await;
-        if let Some(ref d) = bucket_region {
-            s3_manager.get_client_for_region(&active_profile, d).await?.clone()
-        } else {
-            s3_manager.get_client(&active_profile).await?.clone()
-        }
-    };
+    let (_, client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
 
     // Delete in batches of 1000. Some S3-compatible providers support single-object
     // deletion but return service errors for DeleteObjects, so fall back per key.
@@ -682,34 +1035,19 @@ pub async fn delete_objects(
              Err(err) => {
                  // Retry logic for bulk delete
                  log::warn!("delete_objects failed, attempting region discovery: {}", err);
-                 let detected_region = {
-                     let retry_client = {
-                        let mut s3_manager = s3_state.write().await;
-                        s3_manager.get_client(&active_profile).await?.clone()
-                     };
-                     crate::s3::get_bucket_region(&retry_client, &bucket_name).await.ok()
-                 };
-
-                 if let Some(new_region) = detected_region {
-                     let new_client = {
-                         let mut s3_manager = s3_state.write().await;
-                         s3_manager.set_bucket_region(&bucket_name, new_region.clone());
-                         s3_manager.get_client_for_region(&active_profile, &new_region).await?.clone()
-                     };
-
-                     match new_client.delete_objects()
-                         .bucket(&bucket_name)
-                         .delete(delete)
-                         .send()
-                         .await
-                     {
+                 let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, crate::s3::region_from_redirect(&err), |client| {
+                     client.delete_objects().bucket(&bucket_name).delete(delete.clone()).send()
+                 }).await?;
+
+                 if let Some(retry) = retry {
+                     match retry.result {
                          Ok(output) => validate_delete_result(&bucket_name, &output)?,
                          Err(retry_err) => {
                              log::warn!(
                                  "delete_objects retry failed, falling back to single deletes: {}",
                                  retry_err
                              );
-                             delete_keys_individually(&new_client, &bucket_name, chunk).await?;
+                             delete_keys_individually(&retry.client, &bucket_name, chunk).await?;
                          }
                      }
                  } else {
@@ -732,55 +1070,306 @@ pub async fn delete_objects(
     Ok(())
 }
 
-#[tauri::command]
-pub async fn move_object(
-    source_bucket: String,
-    source_region: Option<String>,
-    source_key: String,
-    destination_bucket: String,
-    destination_region: Option<String>,
-    destination_key: String,
-    profile_state: State<'_, ProfileState>,
-    s3_state: State<'_, S3State>,
-) -> Result<()> {
-    if source_bucket == destination_bucket && source_key == destination_key {
-        return Ok(());
+/// DeleteObjects takes at most this many keys per request
+const DELETE_BATCH_SIZE: i32 = 1000;
+/// Failed keys listed in a `delete_prefix` summary; the count covers the rest
+const MAX_REPORTED_DELETE_FAILURES: usize = 100;
+
+#[derive(Clone, serde::Serialize)]
+pub struct DeletePrefixProgress {
+    pub operation_id: String,
+    pub deleted: u64,
+    pub failed: u64,
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct DeletePrefixSummary {
+    pub deleted: u64,
+    pub failed: u64,
+    /// `(key, reason)` for the first failures
+    pub failures: Vec<(String, String)>,
+    /// Whether the delete was stopped before it finished
+    pub cancelled: bool,
+}
+
+impl DeletePrefixSummary {
+    fn record_failure(&mut self, key: &str, reason: String) {
+        self.failed += 1;
+        if self.failures.len() < MAX_REPORTED_DELETE_FAILURES {
+            self.failures.push((key.to_string(), reason));
+        }
     }
-    validate_folder_target(&source_bucket, &source_key, &destination_bucket, &destination_key)?;
+}
 
-    // Check if this is a folder move (key ends with /)
-    if source_key.ends_with('/') {
-        // RECURSIVE FOLDER MOVE
-        let profile_manager = profile_state.read().await;
-        let active_profile = profile_manager
-            .get_active_profile()
-            .await?
-            .ok_or_else(|| crate::error::AppError::ProfileNotFound("No active profile".into()))?;
-        drop(profile_manager);
-        
-        // Get client for listing source bucket
-        let source_region_resolved = {
-            let s3_manager = s3_state.read().await;
-            s3_manager.get_bucket_region(&source_bucket)
-        }.or(source_region.clone());
-
-        let client = {
-            let mut s3_manager = s3_state.write().await;
-            if let Some(ref r) = source_region_resolved {
-                s3_manager.get_client_for_region(&active_profile, r).await?.clone()
-            } else {
-                s3_manager.get_client(&active_profile).await?.clone()
-            }
+/// Where the next page of a `delete_prefix` listing starts
+#[derive(Debug, Default, Clone)]
+struct DeletionCursor {
+    continuation_token: Option<String>,
+    key_marker: Option<String>,
+    version_id_marker: Option<String>,
+}
+
+/// One page of keys (with version ids when deleting every version) to delete,
+/// and the cursor for the next page if there is one
+async fn list_deletion_page(
+    client: &Client,
+    bucket_name: &str,
+    prefix: &str,
+    all_versions: bool,
+    cursor: &DeletionCursor,
+) -> Result<(Vec<(String, Option<String>)>, Option<DeletionCursor>)> {
+    if !all_versions {
+        let page = client
+            .list_objects_v2()
+            .bucket(bucket_name)
+            .prefix(prefix)
+            .max_keys(DELETE_BATCH_SIZE)
+            .set_continuation_token(cursor.continuation_token.clone())
+            .send()
+            .await
+            .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?;
+        let keys = page
+            .contents()
+            .iter()
+            .filter_map(|object| object.key())
+            .map(|key| (key.to_string(), None))
+            .collect();
+        let next = match page.next_continuation_token() {
+            Some(token) if page.is_truncated().unwrap_or(false) => Some(DeletionCursor {
+                continuation_token: Some(token.to_string()),
+                ..DeletionCursor::default()
+            }),
+            _ => None,
         };
-        
-        // List all objects under the source prefix
-        let mut continuation_token = None;
-        let mut all_keys = Vec::new();
-        
-        loop {
+        return Ok((keys, next));
+    }
+
+    let page = client
+        .list_object_versions()
+        .bucket(bucket_name)
+        .prefix(prefix)
+        .max_keys(DELETE_BATCH_SIZE)
+        .set_key_marker(cursor.key_marker.clone())
+        .set_version_id_marker(cursor.version_id_marker.clone())
+        .send()
+        .await
+        .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?;
+    let versions = page
+        .versions()
+        .iter()
+        .map(|version| (version.key(), version.version_id()))
+        .chain(page.delete_markers().iter().map(|marker| (marker.key(), marker.version_id())))
+        .filter_map(|(key, version_id)| Some((key?.to_string(), version_id.map(str::to_string))))
+        .collect();
+    let next = if page.is_truncated().unwrap_or(false)
+        && (page.next_key_marker().is_some() || page.next_version_id_marker().is_some())
+    {
+        Some(DeletionCursor {
+            key_marker: page.next_key_marker().map(str::to_string),
+            version_id_marker: page.next_version_id_marker().map(str::to_string),
+            ..DeletionCursor::default()
+        })
+    } else {
+        None
+    };
+    Ok((versions, next))
+}
+
+/// Delete one batch, recording each key's outcome. Providers that reject
+/// DeleteObjects get single deletes instead.
+async fn delete_batch(
+    client: &Client,
+    bucket_name: &str,
+    batch: &[(String, Option<String>)],
+    summary: &mut DeletePrefixSummary,
+) -> Result<()> {
+    let mut identifiers = Vec::with_capacity(batch.len());
+    for (key, version_id) in batch {
+        let identifier = ObjectIdentifier::builder()
+            .key(key)
+            .set_version_id(version_id.clone())
+            .build()
+            .map_err(|e| crate::error::AppError::S3Error(format!("Invalid object key '{}': {}", key, e)))?;
+        identifiers.push(identifier);
+    }
+    let delete = Delete::builder()
+        .set_objects(Some(identifiers))
+        .quiet(true)
+        .build()
+        .map_err(|e| crate::error::AppError::S3Error(format!("Failed to build delete request: {}", e)))?;
+
+    match client.delete_objects().bucket(bucket_name).delete(delete).send().await {
+        Ok(output) => {
+            // Quiet mode only reports failures
+            summary.deleted += (batch.len() - output.errors().len()) as u64;
+            for error in output.errors() {
+                summary.record_failure(
+                    error.key().unwrap_or("<unknown>"),
+                    format!(
+                        "{}: {}",
+                        error.code().unwrap_or("Unknown"),
+                        error.message().unwrap_or("Delete failed")
+                    ),
+                );
+            }
+        }
+        Err(err) => {
+            log::warn!("delete_prefix batch failed, falling back to single deletes: {}", err);
+            for (key, version_id) in batch {
+                let result = client
+                    .delete_object()
+                    .bucket(bucket_name)
+                    .key(key)
+                    .set_version_id(version_id.clone())
+                    .send()
+                    .await;
+                match result {
+                    Ok(_) => summary.deleted += 1,
+                    Err(e) => summary.record_failure(key, e.to_string()),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Delete everything under a folder, a listing page (at most 1000 keys) at a
+/// time, emitting `delete-prefix-progress` after each batch. `all_versions`
+/// deletes every version and delete marker in a versioned bucket instead of
+/// hiding the current versions behind delete markers. Stops between batches when
+/// cancelled through `cancel_operation` with `operation_id`. Keys that fail are
+/// counted and the rest still deleted.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn delete_prefix(
+    app_handle: AppHandle,
+    bucket_name: String,
+    bucket_region: Option<String>,
+    prefix: String,
+    operation_id: String,
+    all_versions: Option<bool>,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    activity_state: State<'_, ActivityState>,
+) -> Result<DeletePrefixSummary> {
+    if prefix.trim_matches('/').is_empty() {
+        return Err(crate::error::AppError::ConfigError(
+            "Refusing to delete everything in the bucket; give a folder prefix".into(),
+        ));
+    }
+    let prefix = if prefix.ends_with('/') { prefix } else { format!("{}/", prefix) };
+    let all_versions = all_versions.unwrap_or(false);
+    let operation = activity_state.register("Folder delete", operation_id.clone(), s3_target(&bucket_name, &prefix));
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let (_, mut client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
+    let mut summary = DeletePrefixSummary::default();
+    let mut cursor = DeletionCursor::default();
+    let mut first_page = true;
+
+    let result: Result<()> = async {
+        loop {
+            if operation.is_cancelled() {
+                summary.cancelled = true;
+                break;
+            }
+
+            let (batch, next) = match list_deletion_page(&client, &bucket_name, &prefix, all_versions, &cursor).await {
+                Ok(page) => page,
+                Err(err) if first_page => {
+                    log::warn!("delete_prefix listing failed, attempting region discovery: {}", err);
+                    let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, None, |client| {
+                        let (bucket_name, prefix, cursor) = (&bucket_name, &prefix, &cursor);
+                        async move { list_deletion_page(&client, bucket_name, prefix, all_versions, cursor).await }
+                    }).await?;
+
+                    match retry {
+                        Some(retry) => {
+                            client = retry.client;
+                            retry.result?
+                        }
+                        None => return Err(err),
+                    }
+                }
+                Err(err) => return Err(err),
+            };
+            first_page = false;
+
+            if !batch.is_empty() {
+                delete_batch(&client, &bucket_name, &batch, &mut summary).await?;
+                let _ = app_handle.emit("delete-prefix-progress", DeletePrefixProgress {
+                    operation_id: operation_id.clone(),
+                    deleted: summary.deleted,
+                    failed: summary.failed,
+                });
+            }
+
+            match next {
+                Some(next) => cursor = next,
+                None => break,
+            }
+        }
+        Ok(())
+    }
+    .await;
+
+    {
+        let mut s3_manager = s3_state.write().await;
+        s3_manager.remove_bucket_cache(&active_profile.id, &bucket_name);
+    }
+
+    result.map(|_| summary)
+}
+
+/// Outcome of a folder operation run with `continue_on_error`
+#[derive(Debug, Default, Clone, serde::Serialize)]
+pub struct FolderOperationSummary {
+    pub succeeded: usize,
+    /// `(key, reason)` for every object that was skipped
+    pub failed: Vec<(String, String)>,
+}
+
+#[tauri::command]
+pub async fn move_object(
+    source_bucket: String,
+    source_region: Option<String>,
+    source_key: String,
+    destination_bucket: String,
+    destination_region: Option<String>,
+    destination_key: String,
+    preserve_storage_class: Option<bool>,
+    continue_on_error: Option<bool>,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<FolderOperationSummary> {
+    if source_bucket == destination_bucket && source_key == destination_key {
+        return Ok(FolderOperationSummary::default());
+    }
+    validate_folder_target(&source_bucket, &source_key, &destination_bucket, &destination_key)?;
+    let preserve_storage_class = preserve_storage_class.unwrap_or(false);
+    let continue_on_error = continue_on_error.unwrap_or(false);
+
+    // Check if this is a folder move (key ends with /)
+    if source_key.ends_with('/') {
+        // RECURSIVE FOLDER MOVE
+        let active_profile = require_active_profile(&profile_state).await?;
+        
+        // Get client for listing source bucket
+        let page_size = s3_state.read().await.list_page_size();
+        let (source_region_resolved, client) = bucket_client(&s3_state, &active_profile, &source_bucket, source_region.clone()).await?;
+        
+        // List all objects under the source prefix
+        let mut continuation_token = None;
+        let mut all_keys = Vec::new();
+        let mut storage_classes = HashMap::new();
+        
+        loop {
             let mut req = client.list_objects_v2()
                 .bucket(&source_bucket)
-                .prefix(&source_key);
+                .prefix(&source_key)
+                .max_keys(page_size);
             
             if let Some(token) = continuation_token {
                 req = req.continuation_token(token);
@@ -792,6 +1381,9 @@ pub async fn move_object(
             if let Some(contents) = resp.contents {
                 for obj in contents {
                     if let Some(key) = obj.key {
+                        if let Some(class) = obj.storage_class {
+                            storage_classes.insert(key.clone(), class.as_str().to_string());
+                        }
                         all_keys.push(key);
                     }
                 }
@@ -814,47 +1406,65 @@ pub async fn move_object(
             format!("{}/", destination_key)
         };
 
+        let mut summary = FolderOperationSummary::default();
+        let mut copied_keys = Vec::with_capacity(all_keys.len());
+
         // Move each object individually
         for key in &all_keys {
             // Calculate destination key by replacing source prefix with destination prefix
-            if key == &source_key {
-                copy_single_object(
-                    &source_bucket,
-                    key,
-                    &destination_bucket,
-                    destination_region.clone(),
-                    &destination_folder_key,
-                    &active_profile,
-                    &s3_state,
-                ).await?;
+            let new_key = if key == &source_key {
+                destination_folder_key.clone()
             } else {
                 let relative_path = key.strip_prefix(&source_key).unwrap_or(key);
-                let new_key = format!("{}{}", destination_key, relative_path);
+                format!("{}{}", destination_key, relative_path)
+            };
 
-                copy_single_object(
-                    &source_bucket,
-                    key,
-                    &destination_bucket,
-                    destination_region.clone(),
-                    &new_key,
-                    &active_profile,
-                    &s3_state,
-                ).await?;
+            let copied = copy_single_object(
+                &source_bucket,
+                key,
+                &destination_bucket,
+                destination_region.clone(),
+                &new_key,
+                preserved_class(preserve_storage_class, &storage_classes, key),
+                &active_profile,
+                &s3_state,
+            ).await;
+
+            match copied {
+                Ok(()) => copied_keys.push(key.clone()),
+                // The source is only deleted for keys that were copied, so a failed
+                // key stays where it was
+                Err(e) if continue_on_error => {
+                    log::warn!("Skipping '{}' in folder move: {}", key, e);
+                    summary.failed.push((key.clone(), e.to_string()));
+                }
+                Err(e) => return Err(e),
             }
         }
         
-        // Delete all source objects at once
-        if !all_keys.is_empty() {
-            delete_objects(
+        // Delete all copied source objects at once
+        if !copied_keys.is_empty() {
+            let deleted = delete_objects(
                 source_bucket,
                 source_region_resolved,
-                all_keys,
+                copied_keys.clone(),
                 profile_state,
                 s3_state
-            ).await?;
+            ).await;
+
+            match deleted {
+                Ok(()) => summary.succeeded = copied_keys.len(),
+                Err(e) if continue_on_error => {
+                    let reason = format!("Copied, but deleting the source failed: {}", e);
+                    summary
+                        .failed
+                        .extend(copied_keys.into_iter().map(|key| (key, reason.clone())));
+                }
+                Err(e) => return Err(e),
+            }
         }
         
-        Ok(())
+        Ok(summary)
     } else {
         // Single file move (original behavior)
         // 1. Copy
@@ -865,6 +1475,7 @@ pub async fn move_object(
             destination_bucket.clone(),
             destination_region.clone(),
             destination_key.clone(),
+            Some(preserve_storage_class),
             profile_state.clone(),
             s3_state.clone()
         ).await?;
@@ -878,7 +1489,10 @@ pub async fn move_object(
             s3_state
         ).await?;
         
-        Ok(())
+        Ok(FolderOperationSummary {
+            succeeded: 1,
+            failed: Vec::new(),
+        })
     }
 }
 
@@ -887,6 +1501,8 @@ pub struct ObjectMetadata {
     pub key: String,
     pub size: i64,
     pub last_modified: Option<String>,
+    /// `last_modified` as milliseconds since the Unix epoch
+    pub last_modified_epoch_ms: Option<i64>,
     pub content_type: Option<String>,
     pub e_tag: Option<String>,
     pub storage_class: Option<String>,
@@ -918,6 +1534,25 @@ pub struct ObjectPermissions {
 #[derive(serde::Serialize)]
 pub struct SetObjectPermissionsResult {
     pub affected_count: usize,
+    /// Grants read back from the first affected object, empty if they couldn't be read
+    pub grants: Vec<ObjectAclGrant>,
+}
+
+fn object_acl_grants(grants: &[Grant]) -> Vec<ObjectAclGrant> {
+    grants
+        .iter()
+        .map(|grant| {
+            let grantee = grant.grantee();
+            ObjectAclGrant {
+                grantee_type: grantee.map(|g| g.r#type().as_str().to_string()),
+                display_name: grantee.and_then(|g| g.display_name()).map(|value| value.to_string()),
+                id: grantee.and_then(|g| g.id()).map(|value| value.to_string()),
+                uri: grantee.and_then(|g| g.uri()).map(|value| value.to_string()),
+                email_address: grantee.and_then(|g| g.email_address()).map(|value| value.to_string()),
+                permission: grant.permission().map(|value| value.as_str().to_string()),
+            }
+        })
+        .collect()
 }
 
 #[tauri::command]
@@ -929,39 +1564,26 @@ pub async fn get_object_permissions(
     profile_state: State<'_, ProfileState>,
     s3_state: State<'_, S3State>,
 ) -> Result<ObjectPermissions> {
-    let profile_manager = profile_state.read().await;
-    let active_profile = profile_manager
-        .get_active_profile()
-        .await?
-        .ok_or_else(|| crate::error::AppError::ProfileNotFound("No active profile".into()))?;
-    drop(profile_manager);
+    let active_profile = require_active_profile(&profile_state).await?;
 
-    let bucket_region = {
-        let s3_manager = s3_state.read().await;
-        s3_manager.get_bucket_region(&bucket_name)
-    }.or(bucket_region);
-
-    let mut client = {
-        let mut s3_manager = s3_state.write().await;
-        if let Some(ref region) = bucket_region {
-            s3_manager.get_client_for_region(&active_profile, region).await?.clone()
-        } else {
-            s3_manager.get_client(&active_profile).await?.clone()
-        }
-    };
+    let page_size = s3_state.read().await.list_page_size();
+    let (_, mut client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
 
-    let mut target_keys = match list_keys_for_permission_target(&client, &bucket_name, &key, is_folder).await {
+    let mut target_keys = match list_keys_for_permission_target(&client, &bucket_name, &key, is_folder, page_size).await {
         Ok(keys) => keys,
         Err(err) => {
             log::warn!("Permission target listing failed, attempting region discovery: {}", err);
-            if let Some(new_region) = detect_and_cache_bucket_region(&active_profile, &bucket_name, &s3_state).await? {
-                client = {
-                    let mut s3_manager = s3_state.write().await;
-                    s3_manager.get_client_for_region(&active_profile, &new_region).await?.clone()
-                };
-                list_keys_for_permission_target(&client, &bucket_name, &key, is_folder).await?
-            } else {
-                return Err(err);
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, None, |client| {
+                let (bucket_name, key) = (&bucket_name, &key);
+                async move { list_keys_for_permission_target(&client, bucket_name, key, is_folder, page_size).await }
+            }).await?;
+
+            match retry {
+                Some(retry) => {
+                    client = retry.client;
+                    retry.result?
+                }
+                None => return Err(err),
             }
         }
     };
@@ -998,21 +1620,7 @@ pub async fn get_object_permissions(
         }
     };
 
-    let grants = output
-        .grants()
-        .iter()
-        .map(|grant| {
-            let grantee = grant.grantee();
-            ObjectAclGrant {
-                grantee_type: grantee.map(|g| g.r#type().as_str().to_string()),
-                display_name: grantee.and_then(|g| g.display_name()).map(|value| value.to_string()),
-                id: grantee.and_then(|g| g.id()).map(|value| value.to_string()),
-                uri: grantee.and_then(|g| g.uri()).map(|value| value.to_string()),
-                email_address: grantee.and_then(|g| g.email_address()).map(|value| value.to_string()),
-                permission: grant.permission().map(|value| value.as_str().to_string()),
-            }
-        })
-        .collect();
+    let grants = object_acl_grants(output.grants());
 
     Ok(ObjectPermissions {
         key,
@@ -1026,63 +1634,77 @@ pub async fn get_object_permissions(
     })
 }
 
+/// Apply a canned ACL, or explicit `grants` to canonical users, groups or email
+/// addresses, to an object or everything in a folder. Explicit grants replace the
+/// object's ACL; its owner keeps full control.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn set_object_permissions(
     bucket_name: String,
     bucket_region: Option<String>,
     key: String,
     is_folder: bool,
-    canned_acl: String,
+    canned_acl: Option<String>,
+    grants: Option<Vec<AclGrantInput>>,
     profile_state: State<'_, ProfileState>,
     s3_state: State<'_, S3State>,
 ) -> Result<SetObjectPermissionsResult> {
-    let acl = parse_object_canned_acl(&canned_acl)?;
-    let profile_manager = profile_state.read().await;
-    let active_profile = profile_manager
-        .get_active_profile()
-        .await?
-        .ok_or_else(|| crate::error::AppError::ProfileNotFound("No active profile".into()))?;
-    drop(profile_manager);
-
-    let bucket_region = {
-        let s3_manager = s3_state.read().await;
-        s3_manager.get_bucket_region(&bucket_name)
-    }.or(bucket_region);
-
-    let mut client = {
-        let mut s3_manager = s3_state.write().await;
-        if let Some(ref region) = bucket_region {
-            s3_manager.get_client_for_region(&active_profile, region).await?.clone()
-        } else {
-            s3_manager.get_client(&active_profile).await?.clone()
+    let acl = match (canned_acl.filter(|acl| !acl.is_empty()), grants) {
+        (Some(canned_acl), None) => AclChange::Canned(parse_object_canned_acl(&canned_acl)?),
+        (None, Some(grants)) if !grants.is_empty() => AclChange::Grants(
+            grants.iter().map(build_acl_grant).collect::<Result<Vec<Grant>>>()?,
+        ),
+        (Some(_), Some(_)) => {
+            return Err(crate::error::AppError::ConfigError(
+                "Give either a canned ACL or explicit grants, not both".into(),
+            ))
         }
+        _ => return Err(crate::error::AppError::ConfigError("No ACL or grants given".into())),
     };
+    let active_profile = require_active_profile(&profile_state).await?;
 
-    let target_keys = match list_keys_for_permission_target(&client, &bucket_name, &key, is_folder).await {
+    let page_size = s3_state.read().await.list_page_size();
+    let (_, mut client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
+    let target_keys = match list_keys_for_permission_target(&client, &bucket_name, &key, is_folder, page_size).await {
         Ok(keys) => keys,
         Err(err) => {
             log::warn!("Permission target listing failed, attempting region discovery: {}", err);
-            if let Some(new_region) = detect_and_cache_bucket_region(&active_profile, &bucket_name, &s3_state).await? {
-                client = {
-                    let mut s3_manager = s3_state.write().await;
-                    s3_manager.get_client_for_region(&active_profile, &new_region).await?.clone()
-                };
-                list_keys_for_permission_target(&client, &bucket_name, &key, is_folder).await?
-            } else {
-                return Err(err);
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, None, |client| {
+                let (bucket_name, key) = (&bucket_name, &key);
+                async move { list_keys_for_permission_target(&client, bucket_name, key, is_folder, page_size).await }
+            }).await?;
+
+            match retry {
+                Some(retry) => {
+                    client = retry.client;
+                    retry.result?
+                }
+                None => return Err(err),
             }
         }
     };
 
     for target_key in &target_keys {
-        client
-            .put_object_acl()
-            .bucket(&bucket_name)
-            .key(target_key)
-            .acl(acl.clone())
-            .send()
-            .await
-            .map_err(map_acl_error)?;
+        let request = client.put_object_acl().bucket(&bucket_name).key(target_key);
+        let request = match &acl {
+            AclChange::Canned(canned_acl) => request.acl(canned_acl.clone()),
+            AclChange::Grants(grants) => {
+                // The policy has to name the owner, which can differ per object
+                let current = client
+                    .get_object_acl()
+                    .bucket(&bucket_name)
+                    .key(target_key)
+                    .send()
+                    .await
+                    .map_err(map_acl_error)?;
+                let owner = current.owner().cloned().ok_or_else(|| {
+                    crate::error::AppError::S3Error(format!("No owner returned for '{}'", target_key))
+                })?;
+                request.access_control_policy(access_control_policy(owner, grants.clone()))
+            }
+        };
+        request.send().await.map_err(map_acl_error)?;
     }
 
     {
@@ -1091,8 +1713,20 @@ pub async fn set_object_permissions(
         s3_manager.remove_bucket_cache(&profile_id, &bucket_name);
     }
 
+    let grants = match target_keys.first() {
+        Some(first_key) => match client.get_object_acl().bucket(&bucket_name).key(first_key).send().await {
+            Ok(output) => object_acl_grants(output.grants()),
+            Err(e) => {
+                log::warn!("Could not read back ACL of '{}': {}", first_key, e);
+                Vec::new()
+            }
+        },
+        None => Vec::new(),
+    };
+
     Ok(SetObjectPermissionsResult {
         affected_count: target_keys.len(),
+        grants,
     })
 }
 
@@ -1104,27 +1738,10 @@ pub async fn get_object_metadata(
     profile_state: State<'_, ProfileState>,
     s3_state: State<'_, S3State>,
 ) -> Result<ObjectMetadata> {
-    let profile_manager = profile_state.read().await;
-    let active_profile = profile_manager
-        .get_active_profile()
-        .await?
-        .ok_or_else(|| crate::error::AppError::ProfileNotFound("No active profile".into()))?;
-    drop(profile_manager);
+    let active_profile = require_active_profile(&profile_state).await?;
     
     // Check cache for bucket region first
-    let bucket_region = {
-        let s3_manager = s3_state.read().await;
-        s3_manager.get_bucket_region(&bucket_name)
-    }.or(bucket_region);
-
-    let client = {
-        let mut s3_manager = s3_state.write().await;
-        if let Some(ref d) = bucket_region {
-            s3_manager.get_client_for_region(&active_profile, d).await?.clone()
-        } else {
-            s3_manager.get_client(&active_profile).await?.clone()
-        }
-    };
+    let (_, client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
 
     let result = client.head_object()
         .bucket(&bucket_name)
@@ -1144,21 +1761,12 @@ pub async fn get_object_metadata(
             
             // Retry logic
             log::warn!("head_object failed, attempting region discovery: {}", err);
-            let detected_region = {
-                let retry_client = {
-                   let mut s3_manager = s3_state.write().await;
-                   s3_manager.get_client(&active_profile).await?.clone()
-                };
-                crate::s3::get_bucket_region(&retry_client, &bucket_name).await.ok()
-            };
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, crate::s3::region_from_redirect(&err), |client| {
+                client.head_object().bucket(&bucket_name).key(&key).send()
+            }).await?;
 
-            if let Some(new_region) = detected_region {
-                let new_client = {
-                    let mut s3_manager = s3_state.write().await;
-                    s3_manager.set_bucket_region(&bucket_name, new_region.clone());
-                    s3_manager.get_client_for_region(&active_profile, &new_region).await?.clone()
-                };
-                new_client.head_object().bucket(&bucket_name).key(&key).send().await
+            if let Some(retry) = retry {
+                retry.result
                     .map_err(|e| {
                          let e_str = e.to_string();
                          if e_str.contains("403") || e_str.contains("Access Denied") {
@@ -1174,6 +1782,7 @@ pub async fn get_object_metadata(
     };
 
     let last_modified = output.last_modified.map(|d| d.to_string());
+    let last_modified_epoch_ms = output.last_modified.as_ref().and_then(crate::s3::epoch_millis);
     
     // Convert HashMap<String, String> from SDK to standard HashMap
     let user_metadata = output.metadata.unwrap_or_default();
@@ -1182,9 +1791,1438 @@ pub async fn get_object_metadata(
         key,
         size: output.content_length.unwrap_or(0),
         last_modified,
+        last_modified_epoch_ms,
         content_type: output.content_type,
         e_tag: output.e_tag,
         storage_class: output.storage_class.map(|s| s.as_str().to_string()),
         user_metadata: user_metadata.into_iter().collect(),
     })
 }
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct ObjectChecksums {
+    pub key: String,
+    pub e_tag: Option<String>,
+    pub size: Option<i64>,
+    pub crc32: Option<String>,
+    pub crc32c: Option<String>,
+    pub crc64nvme: Option<String>,
+    pub sha1: Option<String>,
+    pub sha256: Option<String>,
+    /// `FULL_OBJECT` or `COMPOSITE`; composite checksums of multipart uploads are
+    /// checksums of the part checksums and can't be compared to a whole-file hash
+    pub checksum_type: Option<String>,
+    pub part_count: Option<i32>,
+    /// "GetObjectAttributes", or "HeadObject" when the provider doesn't support it
+    pub source: String,
+}
+
+async fn fetch_object_attributes(
+    client: &Client,
+    bucket_name: &str,
+    key: &str,
+) -> std::result::Result<
+    aws_sdk_s3::operation::get_object_attributes::GetObjectAttributesOutput,
+    aws_sdk_s3::error::SdkError<aws_sdk_s3::operation::get_object_attributes::GetObjectAttributesError>,
+> {
+    use aws_sdk_s3::types::ObjectAttributes;
+
+    client
+        .get_object_attributes()
+        .bucket(bucket_name)
+        .key(key)
+        .object_attributes(ObjectAttributes::Checksum)
+        .object_attributes(ObjectAttributes::Etag)
+        .object_attributes(ObjectAttributes::ObjectSize)
+        .object_attributes(ObjectAttributes::ObjectParts)
+        .send()
+        .await
+}
+
+/// Server-recorded checksums for an object, so downloads can be verified against
+/// more than the ETag. Falls back to HeadObject with checksum mode enabled when
+/// the provider doesn't implement GetObjectAttributes.
+#[tauri::command]
+pub async fn get_object_checksums(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    key: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<ObjectChecksums> {
+    use aws_sdk_s3::types::ChecksumMode;
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let (_, mut client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
+    let mut attributes = fetch_object_attributes(&client, &bucket_name, &key).await;
+    if let Err(err) = &attributes {
+        log::warn!("get_object_attributes failed, attempting region discovery: {}", err);
+        let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, crate::s3::region_from_redirect(err), |client| {
+            let (bucket_name, key) = (&bucket_name, &key);
+            async move { fetch_object_attributes(&client, bucket_name, key).await }
+        }).await?;
+        if let Some(retry) = retry {
+            client = retry.client;
+            attributes = retry.result;
+        }
+    }
+
+    match attributes {
+        Ok(output) => {
+            let checksum = output.checksum();
+            Ok(ObjectChecksums {
+                key,
+                e_tag: output.e_tag().map(str::to_string),
+                size: output.object_size(),
+                crc32: checksum.and_then(|c| c.checksum_crc32()).map(str::to_string),
+                crc32c: checksum.and_then(|c| c.checksum_crc32_c()).map(str::to_string),
+                crc64nvme: checksum.and_then(|c| c.checksum_crc64_nvme()).map(str::to_string),
+                sha1: checksum.and_then(|c| c.checksum_sha1()).map(str::to_string),
+                sha256: checksum.and_then(|c| c.checksum_sha256()).map(str::to_string),
+                checksum_type: checksum
+                    .and_then(|c| c.checksum_type())
+                    .map(|t| t.as_str().to_string()),
+                part_count: output.object_parts().and_then(|p| p.total_parts_count()),
+                source: "GetObjectAttributes".to_string(),
+            })
+        }
+        Err(err) => {
+            let error_str = err.to_string();
+            if error_str.contains("403") || error_str.contains("Access Denied") {
+                return Err(crate::error::AppError::AccessDenied(error_str));
+            }
+
+            log::info!("GetObjectAttributes unavailable for '{}', falling back to HeadObject: {}", bucket_name, error_str);
+            let output = client
+                .head_object()
+                .bucket(&bucket_name)
+                .key(&key)
+                .checksum_mode(ChecksumMode::Enabled)
+                .send()
+                .await
+                .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?;
+
+            Ok(ObjectChecksums {
+                key,
+                e_tag: output.e_tag().map(str::to_string),
+                size: output.content_length(),
+                crc32: output.checksum_crc32().map(str::to_string),
+                crc32c: output.checksum_crc32_c().map(str::to_string),
+                crc64nvme: output.checksum_crc64_nvme().map(str::to_string),
+                sha1: output.checksum_sha1().map(str::to_string),
+                sha256: output.checksum_sha256().map(str::to_string),
+                checksum_type: output.checksum_type().map(|t| t.as_str().to_string()),
+                part_count: output.parts_count(),
+                source: "HeadObject".to_string(),
+            })
+        }
+    }
+}
+
+/// Listing, stats and export operations still running, oldest first. Transfers
+/// are listed separately by `list_transfers`.
+#[tauri::command]
+pub async fn list_active_operations(activity_state: State<'_, ActivityState>) -> Result<Vec<ActiveOperation>> {
+    Ok(activity_state.list())
+}
+
+/// Stop any running operation from `list_active_operations`. It ends with a
+/// `Cancelled` error after its current request. Returns false when no operation
+/// with that id is running.
+#[tauri::command]
+pub async fn cancel_operation(operation_id: String, activity_state: State<'_, ActivityState>) -> Result<bool> {
+    activity_state.cancel(&operation_id)
+}
+
+#[derive(serde::Serialize)]
+pub struct DeleteMarkerInfo {
+    pub key: String,
+    pub version_id: String,
+    pub is_latest: bool,
+    pub last_modified: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct DeleteMarkerRemovalResult {
+    pub success: bool,
+    pub version_id: Option<String>,
+    pub error: Option<String>,
+}
+
+async fn list_delete_markers_for_prefix(
+    client: &Client,
+    bucket_name: &str,
+    prefix: &str,
+    cancelled: &AtomicBool,
+) -> Result<Vec<DeleteMarkerInfo>> {
+    let mut markers = Vec::new();
+    let mut key_marker: Option<String> = None;
+    let mut version_id_marker: Option<String> = None;
+
+    loop {
+        if cancelled.load(Ordering::Relaxed) {
+            return Err(crate::error::AppError::Cancelled(format!("Listing delete markers under '{}'", prefix)));
+        }
+        let mut request = client
+            .list_object_versions()
+            .bucket(bucket_name)
+            .prefix(prefix);
+        if let Some(marker) = &key_marker {
+            request = request.key_marker(marker);
+        }
+        if let Some(marker) = &version_id_marker {
+            request = request.version_id_marker(marker);
+        }
+
+        let output = request
+            .send()
+            .await
+            .map_err(|err| crate::error::AppError::S3Error(err.to_string()))?;
+
+        for marker in output.delete_markers() {
+            let (Some(key), Some(version_id)) = (marker.key(), marker.version_id()) else {
+                continue;
+            };
+            markers.push(DeleteMarkerInfo {
+                key: key.to_string(),
+                version_id: version_id.to_string(),
+                is_latest: marker.is_latest().unwrap_or(false),
+                last_modified: marker.last_modified().map(|d| d.to_string()),
+            });
+        }
+
+        if output.is_truncated().unwrap_or(false) {
+            key_marker = output.next_key_marker().map(|marker| marker.to_string());
+            version_id_marker = output.next_version_id_marker().map(|marker| marker.to_string());
+            if key_marker.is_none() && version_id_marker.is_none() {
+                break;
+            }
+        } else {
+            break;
+        }
+    }
+
+    Ok(markers)
+}
+
+/// List delete markers under a prefix in a versioned bucket
+#[tauri::command]
+pub async fn list_delete_markers(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    prefix: Option<String>,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<Vec<DeleteMarkerInfo>> {
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let prefix = prefix.unwrap_or_default();
+    let (_, client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
+    let not_cancelled = AtomicBool::new(false);
+    match list_delete_markers_for_prefix(&client, &bucket_name, &prefix, &not_cancelled).await {
+        Ok(markers) => Ok(markers),
+        Err(err) => {
+            log::warn!("list_delete_markers failed, attempting region discovery: {}", err);
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, None, |client| {
+                let (bucket_name, prefix) = (&bucket_name, &prefix);
+                let not_cancelled = &not_cancelled;
+                async move { list_delete_markers_for_prefix(&client, bucket_name, prefix, not_cancelled).await }
+            }).await?;
+
+            match retry {
+                Some(retry) => retry.result,
+                None => Err(err),
+            }
+        }
+    }
+}
+
+/// Versions listed per key when looking for its current delete marker. A key's
+/// newest entry comes first in a versions listing, so a few are plenty.
+const DELETE_MARKER_LOOKUP_KEYS: i32 = 10;
+const DELETE_MARKER_LOOKUP_CONCURRENCY: usize = 8;
+
+/// Version id of a key's current delete marker, or `None` when the key isn't
+/// deleted. Only the first few versions under the key itself are listed.
+async fn current_delete_marker(client: &Client, bucket_name: &str, key: &str) -> Result<Option<String>> {
+    let output = client
+        .list_object_versions()
+        .bucket(bucket_name)
+        .prefix(key)
+        .max_keys(DELETE_MARKER_LOOKUP_KEYS)
+        .send()
+        .await
+        .map_err(|err| crate::error::AppError::S3Error(err.to_string()))?;
+
+    Ok(output
+        .delete_markers()
+        .iter()
+        .find(|marker| marker.key() == Some(key) && marker.is_latest().unwrap_or(false))
+        .and_then(|marker| marker.version_id())
+        .map(str::to_string))
+}
+
+/// [`current_delete_marker`] for each key, a few at a time. Keys are looked up
+/// one by one so keys in unrelated folders don't list the bucket's whole history.
+async fn current_delete_markers(
+    client: &Client,
+    bucket_name: &str,
+    keys: impl IntoIterator<Item = String>,
+    cancelled: &AtomicBool,
+) -> Vec<(String, Result<Option<String>>)> {
+    futures::stream::iter(keys)
+        .map(|key| async move {
+            if cancelled.load(Ordering::Relaxed) {
+                return (key, Err(crate::error::AppError::Cancelled("Delete marker lookup".into())));
+            }
+            let marker = current_delete_marker(client, bucket_name, &key).await;
+            (key, marker)
+        })
+        .buffer_unordered(DELETE_MARKER_LOOKUP_CONCURRENCY)
+        .collect()
+        .await
+}
+
+/// Remove the latest delete marker for each key, restoring the previous version
+#[tauri::command]
+pub async fn remove_delete_markers(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    keys: Vec<String>,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    activity_state: State<'_, ActivityState>,
+) -> Result<HashMap<String, DeleteMarkerRemovalResult>> {
+    let mut results = HashMap::new();
+    if keys.is_empty() {
+        return Ok(results);
+    }
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let (_, mut client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+    let operation = activity_state.register("Delete marker removal", uuid::Uuid::new_v4().to_string(), s3_target(&bucket_name, ""));
+
+    // Look up the first key on its own so a region mismatch is fixed once,
+    // before the rest go out concurrently.
+    let unique_keys: HashSet<String> = keys.into_iter().collect();
+    let mut remaining = unique_keys.into_iter();
+    let mut lookups = Vec::new();
+    if let Some(key) = remaining.next() {
+        let lookup = match current_delete_marker(&client, &bucket_name, &key).await {
+            Ok(marker) => Ok(marker),
+            Err(err) => {
+                log::warn!("remove_delete_markers lookup failed, attempting region discovery: {}", err);
+                let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, None, |client| {
+                    let (bucket_name, key) = (&bucket_name, &key);
+                    async move { current_delete_marker(&client, bucket_name, key).await }
+                }).await?;
+
+                match retry {
+                    Some(retry) => {
+                        client = retry.client;
+                        retry.result
+                    }
+                    None => Err(err),
+                }
+            }
+        };
+        lookups.push((key, lookup));
+    }
+    lookups.extend(current_delete_markers(&client, &bucket_name, remaining, operation.cancel_flag()).await);
+    operation.check()?;
+
+    let mut targets: Vec<(String, String)> = Vec::new();
+    for (key, lookup) in lookups {
+        match lookup {
+            Ok(Some(version_id)) => targets.push((key, version_id)),
+            Ok(None) => {
+                results.insert(key, DeleteMarkerRemovalResult {
+                    success: false,
+                    version_id: None,
+                    error: Some("Object is not currently deleted".to_string()),
+                });
+            }
+            Err(err) => {
+                results.insert(key, DeleteMarkerRemovalResult {
+                    success: false,
+                    version_id: None,
+                    error: Some(err.to_string()),
+                });
+            }
+        }
+    }
+
+    for chunk in targets.chunks(DELETE_BATCH_SIZE as usize) {
+        operation.check()?;
+        let mut identifiers = Vec::with_capacity(chunk.len());
+        for (key, version_id) in chunk {
+            let identifier = ObjectIdentifier::builder()
+                .key(key)
+                .version_id(version_id)
+                .build()
+                .map_err(|e| crate::error::AppError::S3Error(format!("Invalid object key '{}': {}", key, e)))?;
+            identifiers.push(identifier);
+        }
+        let delete = Delete::builder()
+            .set_objects(Some(identifiers))
+            .quiet(false)
+            .build()
+            .map_err(|e| crate::error::AppError::S3Error(format!("Failed to build delete request: {}", e)))?;
+
+        match client.delete_objects().bucket(&bucket_name).delete(delete).send().await {
+            Ok(output) => {
+                for deleted in output.deleted() {
+                    if let Some(key) = deleted.key() {
+                        results.insert(key.to_string(), DeleteMarkerRemovalResult {
+                            success: true,
+                            version_id: deleted.version_id().map(|v| v.to_string()),
+                            error: None,
+                        });
+                    }
+                }
+                for error in output.errors() {
+                    if let Some(key) = error.key() {
+                        results.insert(key.to_string(), DeleteMarkerRemovalResult {
+                            success: false,
+                            version_id: error.version_id().map(|v| v.to_string()),
+                            error: Some(format!(
+                                "{}: {}",
+                                error.code().unwrap_or("Unknown"),
+                                error.message().unwrap_or("Delete failed")
+                            )),
+                        });
+                    }
+                }
+            }
+            Err(err) => {
+                // Some providers reject DeleteObjects; remove markers one by one instead.
+                log::warn!("Bulk delete marker removal failed, falling back to single deletes: {}", err);
+                for (key, version_id) in chunk {
+                    let result = client
+                        .delete_object()
+                        .bucket(&bucket_name)
+                        .key(key)
+                        .version_id(version_id)
+                        .send()
+                        .await;
+                    results.insert(key.clone(), DeleteMarkerRemovalResult {
+                        success: result.is_ok(),
+                        version_id: Some(version_id.clone()),
+                        error: result.err().map(|e| e.to_string()),
+                    });
+                }
+            }
+        }
+    }
+
+    {
+        let mut s3_manager = s3_state.write().await;
+        s3_manager.remove_bucket_cache(&active_profile.id, &bucket_name);
+    }
+
+    Ok(results)
+}
+
+#[derive(serde::Serialize)]
+pub struct CopyMetadataResult {
+    pub content_type: Option<String>,
+    pub cache_control: Option<String>,
+    pub user_metadata: HashMap<String, String>,
+    pub tags: HashMap<String, String>,
+    pub tags_applied: bool,
+    pub warnings: Vec<String>,
+}
+
+/// Apply one object's content type, cache-control, user metadata and tags to another
+/// object in the same bucket. The destination is rewritten in place, so its body is
+/// untouched.
+#[tauri::command]
+pub async fn copy_metadata(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    source_key: String,
+    destination_key: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<CopyMetadataResult> {
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let (_, mut client) = bucket_client(&s3_state, &active_profile, &bucket_name, bucket_region).await?;
+
+    let source = match client.head_object().bucket(&bucket_name).key(&source_key).send().await {
+        Ok(output) => output,
+        Err(err) => {
+            log::warn!("copy_metadata head failed, attempting region discovery: {}", err);
+            let retry = crate::s3::retry_in_bucket_region(&s3_state, &active_profile, &bucket_name, crate::s3::region_from_redirect(&err), |client| {
+                client.head_object().bucket(&bucket_name).key(&source_key).send()
+            }).await?;
+
+            match retry {
+                Some(retry) => {
+                    client = retry.client;
+                    retry.result
+                        .map_err(|e| crate::error::AppError::S3Error(format!("Retry head failed: {}", e)))?
+                }
+                None => return Err(crate::error::AppError::S3Error(err.to_string())),
+            }
+        }
+    };
+
+    let destination = client
+        .head_object()
+        .bucket(&bucket_name)
+        .key(&destination_key)
+        .send()
+        .await
+        .map_err(|e| crate::error::AppError::S3Error(format!("Destination '{}' not found: {}", destination_key, e)))?;
+    // Replacing metadata rewrites the object with a single CopyObject
+    if destination.content_length().unwrap_or(0).max(0) as u64 > crate::transfer::multipart::MAX_COPY_OBJECT_SIZE {
+        return Err(crate::error::AppError::InvalidContent(format!(
+            "'{}' is larger than 5 GiB, which is too large to copy metadata onto in place",
+            destination_key
+        )));
+    }
+
+    let mut warnings = Vec::new();
+    let source_tags = match client.get_object_tagging().bucket(&bucket_name).key(&source_key).send().await {
+        Ok(output) => Some(output.tag_set().to_vec()),
+        Err(err) => {
+            warnings.push(format!("Could not read source tags: {}", err));
+            None
+        }
+    };
+
+    let user_metadata: HashMap<String, String> = source.metadata().cloned().unwrap_or_default();
+    let content_type = source.content_type().map(|value| value.to_string());
+    let cache_control = source.cache_control().map(|value| value.to_string());
+
+    // REPLACE drops every header that isn't re-sent, so carry over the destination's
+    // own encoding/disposition/language, storage class and encryption.
+    let copy_source = crate::s3::encode_copy_source(&bucket_name, &destination_key);
+    keep_encryption(client.copy_object(), &destination)
+        .bucket(&bucket_name)
+        .key(&destination_key)
+        .copy_source(copy_source)
+        .metadata_directive(MetadataDirective::Replace)
+        .set_metadata(Some(user_metadata.clone()))
+        .set_content_type(content_type.clone())
+        .set_cache_control(cache_control.clone())
+        .set_content_encoding(destination.content_encoding().map(|value| value.to_string()))
+        .set_content_disposition(destination.content_disposition().map(|value| value.to_string()))
+        .set_content_language(destination.content_language().map(|value| value.to_string()))
+        .set_storage_class(destination.storage_class().cloned())
+        .send()
+        .await
+        .map_err(|e| crate::error::AppError::S3Error(format!("Failed to apply metadata to '{}': {}", destination_key, e)))?;
+
+    let mut tags = HashMap::new();
+    let mut tags_applied = false;
+    if let Some(tag_set) = source_tags {
+        for tag in &tag_set {
+            tags.insert(tag.key().to_string(), tag.value().to_string());
+        }
+        let tagging = build_tagging(tags.iter())?;
+
+        match client
+            .put_object_tagging()
+            .bucket(&bucket_name)
+            .key(&destination_key)
+            .tagging(tagging)
+            .send()
+            .await
+        {
+            Ok(_) => tags_applied = true,
+            Err(err) => warnings.push(format!("Could not apply tags: {}", err)),
+        }
+    }
+
+    {
+        let mut s3_manager = s3_state.write().await;
+        s3_manager.remove_bucket_cache(&active_profile.id, &bucket_name);
+    }
+
+    Ok(CopyMetadataResult {
+        content_type,
+        cache_control,
+        user_metadata,
+        tags,
+        tags_applied,
+        warnings,
+    })
+}
+
+#[derive(serde::Serialize)]
+pub struct StorageClassChangeResult {
+    pub success: bool,
+    /// Already in the target class, so no request was made
+    pub skipped: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct StorageClassProgress {
+    pub bucket: String,
+    pub prefix: String,
+    pub processed: usize,
+    pub changed: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+const STORAGE_CLASS_CONCURRENCY: usize = 8;
+
+pub(crate) fn parse_storage_class(storage_class: &str) -> Result<StorageClass> {
+    let normalized = storage_class.trim().to_ascii_uppercase();
+    StorageClass::values()
+        .iter()
+        .find(|value| **value == normalized)
+        .map(|value| StorageClass::from(*value))
+        .ok_or_else(|| {
+            crate::error::AppError::InvalidContent(format!(
+                "Unsupported storage class '{}'",
+                storage_class
+            ))
+        })
+}
+
+/// Rewrite every object under a prefix in place with a new storage class. Objects
+/// already in that class are skipped. Progress is emitted as `storage-class-progress`
+/// after each listing page.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn set_storage_class_for_prefix(
+    app_handle: AppHandle,
+    bucket_name: String,
+    bucket_region: Option<String>,
+    prefix: String,
+    storage_class: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    activity_state: State<'_, ActivityState>,
+) -> Result<HashMap<String, StorageClassChangeResult>> {
+    let target_class = parse_storage_class(&storage_class)?;
+    let operation = activity_state.register("Storage class change", uuid::Uuid::new_v4().to_string(), s3_target(&bucket_name, &prefix));
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let mut pages = ObjectPages::new(&s3_state, &active_profile, &bucket_name, bucket_region, &prefix).await?;
+
+    let mut results = HashMap::new();
+    let mut progress = StorageClassProgress {
+        bucket: bucket_name.clone(),
+        prefix: prefix.clone(),
+        processed: 0,
+        changed: 0,
+        skipped: 0,
+        failed: 0,
+    };
+    loop {
+        operation.check()?;
+        let Some(page) = pages.next_page().await? else {
+            break;
+        };
+
+        let mut pending = Vec::new();
+        for object in page.contents() {
+            let Some(key) = object.key() else { continue };
+            // Folder markers have no data worth re-tiering
+            if key.ends_with('/') && object.size().unwrap_or(0) == 0 {
+                continue;
+            }
+
+            // Listings omit the class for STANDARD on some providers
+            let current = object.storage_class().map(|class| class.as_str()).unwrap_or("STANDARD");
+            if current == target_class.as_str() {
+                progress.skipped += 1;
+                results.insert(key.to_string(), StorageClassChangeResult {
+                    success: true,
+                    skipped: true,
+                    error: None,
+                });
+            } else {
+                pending.push(key.to_string());
+            }
+        }
+
+        let outcomes: Vec<(String, Option<String>)> = futures::stream::iter(pending)
+            .map(|key| {
+                let client = pages.client().clone();
+                let bucket_name = bucket_name.clone();
+                let target_class = target_class.clone();
+                async move {
+                    let result = copy_within_bucket(&client, &bucket_name, &key, &key, Some(target_class)).await;
+                    (key, result.err().map(|e| e.to_string()))
+                }
+            })
+            .buffer_unordered(STORAGE_CLASS_CONCURRENCY)
+            .collect()
+            .await;
+
+        for (key, error) in outcomes {
+            if error.is_some() {
+                progress.failed += 1;
+            } else {
+                progress.changed += 1;
+            }
+            results.insert(key, StorageClassChangeResult {
+                success: error.is_none(),
+                skipped: false,
+                error,
+            });
+        }
+
+        progress.processed = progress.changed + progress.skipped + progress.failed;
+        let _ = app_handle.emit("storage-class-progress", progress.clone());
+    }
+
+    {
+        let mut s3_manager = s3_state.write().await;
+        s3_manager.remove_bucket_cache(&active_profile.id, &bucket_name);
+    }
+
+    Ok(results)
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct DateOrganizeResult {
+    pub destination_key: String,
+    /// False for dry runs, failures, and objects already in place
+    pub moved: bool,
+    pub error: Option<String>,
+}
+
+const DATE_ORGANIZE_CONCURRENCY: usize = 8;
+
+/// Date prefix for a last-modified timestamp (seconds since the epoch, UTC), e.g.
+/// `2024/03/07/` for day granularity
+fn date_partition(timestamp_secs: i64, granularity: &str) -> Result<String> {
+    let date = chrono::DateTime::from_timestamp(timestamp_secs, 0).ok_or_else(|| {
+        crate::error::AppError::InvalidContent(format!("Invalid timestamp {}", timestamp_secs))
+    })?;
+    let format = match granularity.trim().to_ascii_lowercase().as_str() {
+        "year" => "%Y/",
+        "month" => "%Y/%m/",
+        "day" => "%Y/%m/%d/",
+        "hour" => "%Y/%m/%d/%H/",
+        other => {
+            return Err(crate::error::AppError::InvalidContent(format!(
+                "Unsupported granularity '{}'. Use year, month, day or hour.",
+                other
+            )))
+        }
+    };
+    Ok(date.format(format).to_string())
+}
+
+/// Flag planned moves whose destination another move in the batch shares, or that
+/// `existing` says is already taken
+fn flag_destination_conflicts(planned: &mut [PlannedRename], existing: &HashSet<String>) {
+    let mut destination_counts: HashMap<String, usize> = HashMap::new();
+    for plan in planned.iter() {
+        *destination_counts.entry(plan.destination.clone()).or_default() += 1;
+    }
+    for plan in planned.iter_mut().filter(|plan| plan.conflict.is_none()) {
+        plan.conflict = if destination_counts[&plan.destination] > 1 {
+            Some(RenameConflict::SharedDestination)
+        } else if existing.contains(&plan.destination) {
+            Some(RenameConflict::Exists)
+        } else {
+            None
+        };
+    }
+}
+
+/// Move the objects directly under `src_prefix` into `dest_prefix/YYYY/MM/DD/...`
+/// based on their last-modified date (UTC). Only the prefix's own objects are
+/// moved, not those in subfolders, so re-running with `dest_prefix == src_prefix`
+/// doesn't pick up already-organized objects. Objects whose destination already
+/// exists, or is shared with another object, are left in place and reported as
+/// conflicts. `dry_run` reports the planned destinations without moving anything.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn organize_by_date(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    src_prefix: String,
+    dest_prefix: String,
+    granularity: Option<String>,
+    dry_run: Option<bool>,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    activity_state: State<'_, ActivityState>,
+) -> Result<HashMap<String, DateOrganizeResult>> {
+    let granularity = granularity.unwrap_or_else(|| "day".to_string());
+    // Fail on a bad granularity before listing anything
+    date_partition(0, &granularity)?;
+    let operation = activity_state.register("Date organize", uuid::Uuid::new_v4().to_string(), s3_target(&bucket_name, &src_prefix));
+    let dry_run = dry_run.unwrap_or(false);
+    let dest_prefix = if dest_prefix.is_empty() || dest_prefix.ends_with('/') {
+        dest_prefix
+    } else {
+        format!("{}/", dest_prefix)
+    };
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let mut pages = ObjectPages::new(&s3_state, &active_profile, &bucket_name, bucket_region, &src_prefix)
+        .await?
+        .delimited();
+
+    let mut planned: Vec<PlannedRename> = Vec::new();
+    let mut results = HashMap::new();
+    loop {
+        operation.check()?;
+        let Some(page) = pages.next_page().await? else {
+            break;
+        };
+
+        for object in page.contents() {
+            let Some(key) = object.key() else { continue };
+            if key.ends_with('/') {
+                continue;
+            }
+            let Some(last_modified) = object.last_modified() else {
+                results.insert(key.to_string(), DateOrganizeResult {
+                    destination_key: key.to_string(),
+                    moved: false,
+                    error: Some("Object has no last-modified date".to_string()),
+                });
+                continue;
+            };
+
+            let name = key.strip_prefix(&src_prefix).unwrap_or(key);
+            let destination_key = format!(
+                "{}{}{}",
+                dest_prefix,
+                date_partition(last_modified.secs(), &granularity)?,
+                name
+            );
+            if destination_key == key {
+                results.insert(key.to_string(), DateOrganizeResult {
+                    destination_key,
+                    moved: false,
+                    error: None,
+                });
+                continue;
+            }
+
+            let storage_class = object
+                .storage_class()
+                .map(|class| class.as_str())
+                .filter(|class| *class != "STANDARD")
+                .map(str::to_string);
+            planned.push(PlannedRename {
+                source: key.to_string(),
+                destination: destination_key,
+                storage_class,
+                conflict: None,
+            });
+        }
+    }
+
+    // Destinations can be anywhere in the bucket, so each is checked directly
+    let client_ref = pages.client();
+    let bucket_ref = bucket_name.as_str();
+    let mut checks = futures::stream::iter(planned.iter().map(|plan| plan.destination.clone()))
+        .map(|destination| async move {
+            let found = client_ref.head_object().bucket(bucket_ref).key(&destination).send().await;
+            (destination, found)
+        })
+        .buffer_unordered(DATE_ORGANIZE_CONCURRENCY);
+    let mut existing = HashSet::new();
+    let mut unchecked = HashMap::new();
+    while let Some((destination, found)) = checks.next().await {
+        operation.check()?;
+        match found {
+            Ok(_) => {
+                existing.insert(destination);
+            }
+            Err(err) if err.as_service_error().is_some_and(|e| e.is_not_found()) => {}
+            Err(err) => {
+                unchecked.insert(destination, format!("Couldn't check the destination: {}", err));
+            }
+        }
+    }
+    drop(checks);
+    flag_destination_conflicts(&mut planned, &existing);
+
+    let (planned, blocked): (Vec<PlannedRename>, Vec<PlannedRename>) = planned
+        .into_iter()
+        .partition(|plan| plan.conflict.is_none() && !unchecked.contains_key(&plan.destination));
+    for plan in blocked {
+        let error = match plan.conflict {
+            Some(conflict) => conflict.message().to_string(),
+            None => unchecked[&plan.destination].clone(),
+        };
+        results.insert(plan.source, DateOrganizeResult {
+            destination_key: plan.destination,
+            moved: false,
+            error: Some(error),
+        });
+    }
+
+    if dry_run {
+        for plan in planned {
+            results.insert(plan.source, DateOrganizeResult {
+                destination_key: plan.destination,
+                moved: false,
+                error: None,
+            });
+        }
+        return Ok(results);
+    }
+
+    let cancelled = operation.cancel_flag();
+    let outcomes: Vec<(String, String, Option<String>)> = futures::stream::iter(planned)
+        .map(|plan| {
+            let PlannedRename { source: key, destination: destination_key, storage_class, .. } = plan;
+            let client = pages.client().clone();
+            let bucket_name = bucket_name.clone();
+            async move {
+                if cancelled.load(Ordering::Relaxed) {
+                    return (key, destination_key, Some("Cancelled before moving".to_string()));
+                }
+                let copied = copy_within_bucket(
+                    &client,
+                    &bucket_name,
+                    &key,
+                    &destination_key,
+                    storage_class.as_deref().map(StorageClass::from),
+                )
+                .await;
+                if let Err(e) = copied {
+                    return (key, destination_key, Some(format!("Copy failed: {}", e)));
+                }
+
+                // Only delete the source once the copy is known to exist
+                let deleted = client
+                    .delete_object()
+                    .bucket(&bucket_name)
+                    .key(&key)
+                    .send()
+                    .await;
+                let error = deleted
+                    .err()
+                    .map(|e| format!("Copied, but deleting the source failed: {}", e));
+                (key, destination_key, error)
+            }
+        })
+        .buffer_unordered(DATE_ORGANIZE_CONCURRENCY)
+        .collect()
+        .await;
+
+    for (key, destination_key, error) in outcomes {
+        results.insert(key, DateOrganizeResult {
+            destination_key,
+            moved: error.is_none(),
+            error,
+        });
+    }
+
+    {
+        let mut s3_manager = s3_state.write().await;
+        s3_manager.remove_bucket_cache(&active_profile.id, &bucket_name);
+    }
+
+    Ok(results)
+}
+
+/// How `batch_rename` turns each key below the prefix into its new name
+#[derive(Debug, PartialEq)]
+enum RenameRule {
+    /// Replace every occurrence of `find` in the key below the prefix
+    Replace { find: String, replace: String },
+    /// Rename files (not folders) from a template with `{name}` (file name
+    /// without extension), `{ext}` (extension with its dot) and `{n}` (1-based
+    /// position in key order)
+    Template(String),
+}
+
+impl RenameRule {
+    fn from_args(find: Option<String>, replace: Option<String>, template: Option<String>) -> Result<Self> {
+        match (find.filter(|find| !find.is_empty()), template.filter(|template| !template.is_empty())) {
+            (Some(find), None) => Ok(Self::Replace {
+                find,
+                replace: replace.unwrap_or_default(),
+            }),
+            (None, Some(template)) => Ok(Self::Template(template)),
+            (Some(_), Some(_)) => Err(crate::error::AppError::InvalidContent(
+                "Use either find/replace or a template, not both".into(),
+            )),
+            (None, None) => Err(crate::error::AppError::InvalidContent(
+                "Give the text to find or a template".into(),
+            )),
+        }
+    }
+
+    /// New name for `relative`, a key with the prefix removed, or `None` when the
+    /// rule doesn't apply to it
+    fn apply(&self, relative: &str, index: usize) -> Option<String> {
+        match self {
+            Self::Replace { find, replace } => Some(relative.replace(find.as_str(), replace)),
+            Self::Template(template) => {
+                if relative.ends_with('/') {
+                    return None;
+                }
+                let (dir, file) = match relative.rfind('/') {
+                    Some(idx) => relative.split_at(idx + 1),
+                    None => ("", relative),
+                };
+                let (stem, ext) = match file.rfind('.') {
+                    Some(idx) if idx > 0 => file.split_at(idx),
+                    _ => (file, ""),
+                };
+                Some(format!("{}{}", dir, fill_rename_template(template, stem, ext, index)))
+            }
+        }
+    }
+}
+
+/// Substitute `{name}`, `{ext}` and `{n}` in one pass, so placeholder-like text
+/// in a file name is left alone
+fn fill_rename_template(template: &str, stem: &str, ext: &str, index: usize) -> String {
+    let mut filled = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let consumed = if tail.starts_with("{name}") {
+            filled.push_str(stem);
+            "{name}".len()
+        } else if tail.starts_with("{ext}") {
+            filled.push_str(ext);
+            "{ext}".len()
+        } else if tail.starts_with("{n}") {
+            filled.push_str(&index.to_string());
+            "{n}".len()
+        } else {
+            filled.push('{');
+            1
+        };
+        rest = &tail[consumed..];
+    }
+    filled.push_str(rest);
+    filled
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RenameConflict {
+    /// An object that isn't being renamed already has the new key
+    Exists,
+    /// Another object in the batch is renamed to the same key
+    SharedDestination,
+    /// The new key belongs to an object that is itself being renamed
+    SourceInBatch,
+    /// The new key is empty or turns a file into a folder marker
+    InvalidKey,
+}
+
+impl RenameConflict {
+    fn message(self) -> &'static str {
+        match self {
+            Self::Exists => "An object with the new key already exists",
+            Self::SharedDestination => "Another object would be given the same key",
+            Self::SourceInBatch => "The new key belongs to another object being renamed",
+            Self::InvalidKey => "The new key is not a valid object key",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PlannedRename {
+    source: String,
+    destination: String,
+    storage_class: Option<String>,
+    conflict: Option<RenameConflict>,
+}
+
+/// New keys for `objects` (key and storage class, in key order) under `prefix`.
+/// Objects the rule leaves unchanged are left out.
+fn plan_renames(objects: &[(String, Option<String>)], prefix: &str, rule: &RenameRule) -> Vec<PlannedRename> {
+    let mut planned = Vec::new();
+    let mut index = 0;
+    for (key, storage_class) in objects {
+        let relative = key.strip_prefix(prefix).unwrap_or(key);
+        if relative.is_empty() {
+            continue;
+        }
+        if matches!(rule, RenameRule::Template(_)) && !relative.ends_with('/') {
+            index += 1;
+        }
+        let Some(renamed) = rule.apply(relative, index) else { continue };
+        let destination = format!("{}{}", prefix, renamed);
+        if destination == *key {
+            continue;
+        }
+        let invalid = renamed.is_empty() || (destination.ends_with('/') && !key.ends_with('/'));
+        planned.push(PlannedRename {
+            source: key.clone(),
+            destination,
+            storage_class: storage_class.clone(),
+            conflict: invalid.then_some(RenameConflict::InvalidKey),
+        });
+    }
+
+    let existing: HashSet<&str> = objects.iter().map(|(key, _)| key.as_str()).collect();
+    let sources: HashSet<String> = planned.iter().map(|rename| rename.source.clone()).collect();
+    let mut destination_counts: HashMap<String, usize> = HashMap::new();
+    for rename in &planned {
+        *destination_counts.entry(rename.destination.clone()).or_default() += 1;
+    }
+    for rename in planned.iter_mut().filter(|rename| rename.conflict.is_none()) {
+        rename.conflict = if destination_counts[&rename.destination] > 1 {
+            Some(RenameConflict::SharedDestination)
+        } else if sources.contains(&rename.destination) {
+            Some(RenameConflict::SourceInBatch)
+        } else if existing.contains(rename.destination.as_str()) {
+            Some(RenameConflict::Exists)
+        } else {
+            None
+        };
+    }
+    planned
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct RenameResult {
+    pub destination_key: String,
+    /// False for dry runs and failures
+    pub renamed: bool,
+    pub error: Option<String>,
+}
+
+const BATCH_RENAME_CONCURRENCY: usize = 8;
+
+/// Rename every object under a prefix by replacing `find` with `replace` in the
+/// key below the prefix, or by filling in `template` for each file name. Objects
+/// are copied server-side and the source deleted once the copy exists. Nothing is
+/// renamed if a new key is shared, invalid, or belongs to an object that is itself
+/// being renamed, or if it would overwrite an existing object without `force`.
+/// `dry_run` returns the mapping, with conflicts as errors, without touching
+/// anything. Returns the mapping applied, keyed by old key.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn batch_rename(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    prefix: String,
+    find: Option<String>,
+    replace: Option<String>,
+    template: Option<String>,
+    dry_run: Option<bool>,
+    force: Option<bool>,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    activity_state: State<'_, ActivityState>,
+) -> Result<HashMap<String, RenameResult>> {
+    let rule = RenameRule::from_args(find, replace, template)?;
+    let dry_run = dry_run.unwrap_or(false);
+    let force = force.unwrap_or(false);
+    let operation = activity_state.register("Batch rename", uuid::Uuid::new_v4().to_string(), s3_target(&bucket_name, &prefix));
+
+    let active_profile = require_active_profile(&profile_state).await?;
+
+    let mut pages = ObjectPages::new(&s3_state, &active_profile, &bucket_name, bucket_region, &prefix).await?;
+
+    // (key, storage class to keep)
+    let mut objects: Vec<(String, Option<String>)> = Vec::new();
+    loop {
+        operation.check()?;
+        let Some(page) = pages.next_page().await? else {
+            break;
+        };
+
+        for object in page.contents() {
+            let Some(key) = object.key() else { continue };
+            let storage_class = object
+                .storage_class()
+                .map(|class| class.as_str())
+                .filter(|class| *class != "STANDARD")
+                .map(str::to_string);
+            objects.push((key.to_string(), storage_class));
+        }
+    }
+
+    let planned = plan_renames(&objects, &prefix, &rule);
+
+    if dry_run {
+        return Ok(planned
+            .into_iter()
+            .map(|rename| {
+                (rename.source, RenameResult {
+                    destination_key: rename.destination,
+                    renamed: false,
+                    error: rename.conflict.map(|conflict| conflict.message().to_string()),
+                })
+            })
+            .collect());
+    }
+
+    let blocking: Vec<&PlannedRename> = planned
+        .iter()
+        .filter(|rename| match rename.conflict {
+            Some(RenameConflict::Exists) => !force,
+            Some(_) => true,
+            None => false,
+        })
+        .collect();
+    if let Some(first) = blocking.first() {
+        return Err(crate::error::AppError::InvalidContent(format!(
+            "{} of {} renames conflict, nothing was renamed. '{}' -> '{}': {}{}",
+            blocking.len(),
+            planned.len(),
+            first.source,
+            first.destination,
+            first.conflict.map(RenameConflict::message).unwrap_or_default(),
+            if first.conflict == Some(RenameConflict::Exists) { " (pass force to overwrite)" } else { "" }
+        )));
+    }
+
+    let cancelled = operation.cancel_flag();
+    let outcomes: Vec<(String, String, Option<String>)> = futures::stream::iter(planned)
+        .map(|rename| {
+            let client = pages.client().clone();
+            let bucket_name = bucket_name.clone();
+            async move {
+                let PlannedRename { source, destination, storage_class, .. } = rename;
+                if cancelled.load(Ordering::Relaxed) {
+                    return (source, destination, Some("Cancelled before renaming".to_string()));
+                }
+                let copied = copy_within_bucket(
+                    &client,
+                    &bucket_name,
+                    &source,
+                    &destination,
+                    storage_class.as_deref().map(StorageClass::from),
+                )
+                .await;
+                if let Err(e) = copied {
+                    return (source, destination, Some(format!("Copy failed: {}", e)));
+                }
+
+                // Only delete the source once the copy is known to exist
+                let deleted = client
+                    .delete_object()
+                    .bucket(&bucket_name)
+                    .key(&source)
+                    .send()
+                    .await;
+                let error = deleted
+                    .err()
+                    .map(|e| format!("Copied, but deleting the source failed: {}", e));
+                (source, destination, error)
+            }
+        })
+        .buffer_unordered(BATCH_RENAME_CONCURRENCY)
+        .collect()
+        .await;
+
+    let results = outcomes
+        .into_iter()
+        .map(|(source, destination_key, error)| {
+            (source, RenameResult {
+                destination_key,
+                renamed: error.is_none(),
+                error,
+            })
+        })
+        .collect();
+
+    {
+        let mut s3_manager = s3_state.write().await;
+        s3_manager.remove_bucket_cache(&active_profile.id, &bucket_name);
+    }
+
+    Ok(results)
+}
+
+pub(crate) fn build_tagging<'a>(tags: impl IntoIterator<Item = (&'a String, &'a String)>) -> Result<Tagging> {
+    let tag_set = tags
+        .into_iter()
+        .map(|(key, value)| Tag::builder().key(key).value(value).build())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| crate::error::AppError::InvalidContent(e.to_string()))?;
+
+    Tagging::builder()
+        .set_tag_set(Some(tag_set))
+        .build()
+        .map_err(|e| crate::error::AppError::InvalidContent(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        date_partition, flag_destination_conflicts, parse_storage_class, plan_renames, PlannedRename,
+        RenameConflict, RenameRule,
+        access_control_policy, build_acl_grant, current_delete_markers, AclGrantInput,
+        is_executable_content, is_executable_file_name,
+    };
+    use crate::s3::test_server::{query_param, TestS3};
+    use aws_sdk_s3::types::{Owner, Permission, Type};
+    use std::collections::HashMap;
+    use std::sync::atomic::AtomicBool;
+
+    #[test]
+    fn parse_storage_class_accepts_known_classes_only() {
+        assert_eq!(parse_storage_class("glacier_ir").unwrap().as_str(), "GLACIER_IR");
+        assert!(parse_storage_class("FAST").is_err());
+    }
+
+    #[test]
+    fn date_partition_formats_by_granularity() {
+        // 2024-03-07T05:06:07Z
+        let ts = 1_709_787_967;
+        assert_eq!(date_partition(ts, "year").unwrap(), "2024/");
+        assert_eq!(date_partition(ts, "Month").unwrap(), "2024/03/");
+        assert_eq!(date_partition(ts, "day").unwrap(), "2024/03/07/");
+        assert_eq!(date_partition(ts, "hour").unwrap(), "2024/03/07/05/");
+        assert!(date_partition(ts, "week").is_err());
+    }
+
+    #[test]
+    fn executables_are_recognised_by_name_or_content() {
+        assert!(is_executable_file_name("setup.EXE"));
+        assert!(is_executable_file_name("install.sh"));
+        assert!(is_executable_file_name("app.desktop"));
+        assert!(is_executable_file_name("tool.pyw"));
+        assert!(!is_executable_file_name("report.pdf"));
+        assert!(!is_executable_file_name("exe"));
+
+        assert!(is_executable_content(b"#!/bin/sh\necho hi\n"));
+        let mut elf = vec![0u8; 64];
+        elf[..4].copy_from_slice(b"\x7fELF");
+        assert!(is_executable_content(&elf));
+        assert!(!is_executable_content(b"%PDF-1.7"));
+    }
+
+    #[tokio::test]
+    async fn delete_markers_are_looked_up_under_each_key() {
+        let server = TestS3::start(|_, target| {
+            let prefix = query_param(target, "prefix").unwrap_or_default();
+            let body = format!(
+                "<ListVersionsResult><Name>b</Name><Prefix>{0}</Prefix><IsTruncated>false</IsTruncated>\
+                 <DeleteMarker><Key>{0}</Key><VersionId>v-{0}</VersionId><IsLatest>true</IsLatest></DeleteMarker>\
+                 <DeleteMarker><Key>{0}.old</Key><VersionId>other</VersionId><IsLatest>true</IsLatest></DeleteMarker>\
+                 </ListVersionsResult>",
+                prefix
+            );
+            (200, body)
+        })
+        .await;
+
+        let keys = vec!["alpha/a.txt".to_string(), "zeta/b.txt".to_string()];
+        let lookups: HashMap<String, Option<String>> =
+            current_delete_markers(&server.client(), "b", keys, &AtomicBool::new(false))
+                .await
+                .into_iter()
+                .map(|(key, marker)| (key, marker.unwrap()))
+                .collect();
+
+        assert_eq!(lookups["alpha/a.txt"].as_deref(), Some("v-alpha/a.txt"));
+        assert_eq!(lookups["zeta/b.txt"].as_deref(), Some("v-zeta/b.txt"));
+        let mut prefixes: Vec<String> = server
+            .requests()
+            .iter()
+            .map(|request| query_param(request, "prefix").unwrap_or_default())
+            .collect();
+        prefixes.sort();
+        assert_eq!(prefixes, ["alpha/a.txt", "zeta/b.txt"]);
+    }
+
+    #[test]
+    fn rename_plans_flag_collisions_and_fill_templates() {
+        let objects: Vec<(String, Option<String>)> = ["p/a.txt", "p/a.csv", "p/b.txt", "p/dir/", "p/old.txt"]
+            .iter()
+            .map(|key| (key.to_string(), None))
+            .collect();
+
+        let replace = RenameRule::Replace { find: ".txt".into(), replace: ".csv".into() };
+        let planned = plan_renames(&objects, "p/", &replace);
+        let conflicts: HashMap<&str, Option<RenameConflict>> = planned
+            .iter()
+            .map(|rename| (rename.source.as_str(), rename.conflict))
+            .collect();
+        assert_eq!(conflicts.len(), 3);
+        assert_eq!(conflicts["p/a.txt"], Some(RenameConflict::Exists));
+        assert_eq!(conflicts["p/b.txt"], None);
+
+        let template = RenameRule::Template("{n}-{name}{ext}".into());
+        let planned = plan_renames(&objects, "p/", &template);
+        let destinations: Vec<&str> = planned.iter().map(|rename| rename.destination.as_str()).collect();
+        assert_eq!(destinations, ["p/1-a.txt", "p/2-a.csv", "p/3-b.txt", "p/4-old.txt"]);
+
+        let clash = plan_renames(&objects, "p/", &RenameRule::Template("same".into()));
+        assert!(clash.iter().all(|rename| rename.conflict == Some(RenameConflict::SharedDestination)));
+
+        let chain: Vec<(String, Option<String>)> = vec![("q/a".into(), None), ("q/aa".into(), None)];
+        let planned = plan_renames(&chain, "q/", &RenameRule::Replace { find: "a".into(), replace: "aa".into() });
+        assert_eq!(planned[0].conflict, Some(RenameConflict::SourceInBatch));
+        assert_eq!(planned[1].conflict, None);
+        assert!(RenameRule::from_args(None, None, None).is_err());
+    }
+
+    #[test]
+    fn date_moves_onto_taken_or_shared_keys_are_conflicts() {
+        let plan = |source: &str, destination: &str| PlannedRename {
+            source: source.into(),
+            destination: destination.into(),
+            storage_class: None,
+            conflict: None,
+        };
+        let mut planned = vec![
+            plan("in/a.txt", "out/2024/03/07/a.txt"),
+            plan("in/b.txt", "out/2024/03/07/b.txt"),
+            plan("in/c.txt", "out/2024/03/08/c.txt"),
+            plan("other/c.txt", "out/2024/03/08/c.txt"),
+        ];
+        let existing = std::collections::HashSet::from(["out/2024/03/07/a.txt".to_string()]);
+        flag_destination_conflicts(&mut planned, &existing);
+
+        let conflicts: Vec<Option<RenameConflict>> = planned.iter().map(|plan| plan.conflict).collect();
+        assert_eq!(
+            conflicts,
+            [
+                Some(RenameConflict::Exists),
+                None,
+                Some(RenameConflict::SharedDestination),
+                Some(RenameConflict::SharedDestination),
+            ]
+        );
+    }
+
+    #[test]
+    fn explicit_grants_resolve_groups_and_keep_owner_full_control() {
+        let grant = |grantee_type: &str, grantee: &str, permission: &str| {
+            build_acl_grant(&AclGrantInput {
+                grantee_type: grantee_type.into(),
+                grantee: grantee.into(),
+                permission: permission.into(),
+            })
+        };
+
+        let group = grant("Group", "AllUsers", "read").unwrap();
+        assert_eq!(
+            group.grantee().and_then(|grantee| grantee.uri()),
+            Some("http://acs.amazonaws.com/groups/global/AllUsers")
+        );
+        let email = grant("Email", "someone@example.com", "FULL_CONTROL").unwrap();
+        assert_eq!(email.grantee().map(|grantee| grantee.r#type()), Some(&Type::AmazonCustomerByEmail));
+        assert!(grant("Group", "Everyone", "READ").is_err());
+        assert!(grant("CanonicalUser", "abc", "DELETE").is_err());
+        // WRITE only applies to buckets
+        assert!(grant("CanonicalUser", "abc", "WRITE").is_err());
+        assert!(grant("CanonicalUser", "abc", "write-acp").is_ok());
+
+        let owner = Owner::builder().id("owner-id").build();
+        let policy = access_control_policy(owner.clone(), vec![grant("CanonicalUser", "other-id", "READ").unwrap()]);
+        assert_eq!(policy.grants().len(), 2);
+        assert_eq!(policy.grants()[0].permission(), Some(&Permission::FullControl));
+
+        let policy = access_control_policy(owner, vec![grant("CanonicalUser", "owner-id", "FULL_CONTROL").unwrap()]);
+        assert_eq!(policy.grants().len(), 1);
+    }
+}
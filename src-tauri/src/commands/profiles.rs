@@ -1,4 +1,6 @@
-use crate::credentials::{Profile, ProfileManager};
+use crate::credentials::{Profile, ProfileManager, ProfileRecoveryReport};
+use crate::s3::provider::{EndpointCheck, ProviderDetection};
+use crate::s3::S3State;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tauri::State;
@@ -38,23 +40,55 @@ pub async fn add_profile(
         .map_err(|e| e.to_string())
 }
 
+/// Update a profile and drop its cached S3 clients, which were built from the
+/// old credentials, endpoint and region. Listings cached from a previous endpoint
+/// are dropped as well.
 #[tauri::command]
 pub async fn update_profile(
     id: String,
     profile: Profile,
     state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
 ) -> Result<Profile, String> {
-    let mut manager = state.write().await;
-    manager
-        .update_profile(&id, profile)
-        .await
-        .map_err(|e| e.to_string())
+    let (previous_endpoint, profile) = {
+        let mut manager = state.write().await;
+        let previous_endpoint = manager
+            .get_profile(&id)
+            .await
+            .ok()
+            .and_then(|existing| existing.endpoint_url().map(str::to_string));
+        let profile = manager
+            .update_profile(&id, profile)
+            .await
+            .map_err(|e| e.to_string())?;
+        (previous_endpoint, profile)
+    };
+
+    let mut s3_manager = s3_state.write().await;
+    s3_manager.clear_profile_clients(&id);
+    if profile.endpoint_url() != previous_endpoint.as_deref() {
+        s3_manager.clear_profile_listings(&id);
+    }
+
+    Ok(profile)
 }
 
 #[tauri::command]
-pub async fn delete_profile(id: String, state: State<'_, ProfileState>) -> Result<(), String> {
-    let mut manager = state.write().await;
-    manager.delete_profile(&id).await.map_err(|e| e.to_string())
+pub async fn delete_profile(
+    id: String,
+    state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<(), String> {
+    {
+        let mut manager = state.write().await;
+        manager.delete_profile(&id).await.map_err(|e| e.to_string())?;
+    }
+
+    let mut s3_manager = s3_state.write().await;
+    s3_manager.clear_profile_clients(&id);
+    s3_manager.clear_profile_listings(&id);
+
+    Ok(())
 }
 
 #[tauri::command]
@@ -66,6 +100,55 @@ pub async fn set_active_profile(id: String, state: State<'_, ProfileState>) -> R
         .map_err(|e| e.to_string())
 }
 
+/// Update only a profile's region and drop its cached S3 clients
+#[tauri::command]
+pub async fn set_profile_region(
+    id: String,
+    region: Option<String>,
+    state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<Profile, String> {
+    let profile = {
+        let mut manager = state.write().await;
+        manager
+            .set_profile_region(&id, region)
+            .await
+            .map_err(|e| e.to_string())?
+    };
+
+    let mut s3_manager = s3_state.write().await;
+    s3_manager.clear_profile_clients(&id);
+
+    Ok(profile)
+}
+
+/// Turn S3 Transfer Acceleration on or off for a profile. With it on, transfers
+/// go through the accelerate endpoint and fail for buckets that don't have
+/// acceleration enabled. Only applies to AWS profiles.
+#[tauri::command]
+pub async fn set_transfer_acceleration(
+    id: String,
+    enabled: bool,
+    state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<Profile, String> {
+    let profile = {
+        let mut manager = state.write().await;
+        manager
+            .set_transfer_acceleration(&id, enabled)
+            .await
+            .map_err(|e| e.to_string())?
+    };
+    if enabled && profile.provider_quirks().is_some() {
+        log::warn!("Transfer Acceleration has no effect on custom endpoint profile '{}'", profile.name);
+    }
+
+    let mut s3_manager = s3_state.write().await;
+    s3_manager.clear_profile_clients(&id);
+
+    Ok(profile)
+}
+
 #[tauri::command]
 pub async fn get_active_profile(state: State<'_, ProfileState>) -> Result<Option<Profile>, String> {
     let manager = state.read().await;
@@ -75,16 +158,31 @@ pub async fn get_active_profile(state: State<'_, ProfileState>) -> Result<Option
         .map_err(|e| e.to_string())
 }
 
+/// Verify profiles.json and report which profiles were salvaged after corruption
+#[tauri::command]
+pub async fn recover_profiles(
+    state: State<'_, ProfileState>,
+) -> Result<ProfileRecoveryReport, String> {
+    let mut manager = state.write().await;
+    manager.recover_profiles().map_err(|e| e.to_string())
+}
+
+/// Whether secrets can be kept in the OS keychain, as found by the startup probe
+#[tauri::command]
+pub async fn keychain_available(
+    state: State<'_, ProfileState>,
+) -> Result<crate::credentials::KeychainStatus, String> {
+    if let Some(status) = state.read().await.keychain_status() {
+        return Ok(status);
+    }
+    Ok(state.write().await.probe_keychain())
+}
+
 #[tauri::command]
 pub async fn test_connection(
     mut profile: Profile,
     state: State<'_, ProfileState>,
 ) -> Result<TestConnectionResult, String> {
-    use aws_config::Region;
-    use aws_sdk_s3::config::{RequestChecksumCalculation, ResponseChecksumValidation};
-    use aws_sdk_s3::error::ProvideErrorMetadata;
-    use aws_sdk_s3::Client;
-
     // Hydrate profile secrets from keychain if they are empty
     {
         let manager = state.read().await;
@@ -103,12 +201,16 @@ pub async fn test_connection(
         }
     }
 
-    let region = Region::new(
-        profile
-            .region
-            .clone()
-            .unwrap_or_else(|| "us-east-1".to_string()),
-    );
+    check_profile_connection(&profile).await
+}
+
+/// Build a client for `region` straight from the profile as given, bypassing the
+/// client cache so probes never leave clients behind in it
+async fn uncached_client(profile: &Profile, region: &str) -> Result<aws_sdk_s3::Client, String> {
+    use aws_config::Region;
+    use aws_sdk_s3::Client;
+
+    let region = Region::new(region.to_string());
 
     let config = match &profile.credential_type {
         crate::credentials::CredentialType::Environment => {
@@ -169,14 +271,26 @@ pub async fn test_connection(
         &profile.credential_type
     {
         let normalized_url = crate::s3::client::normalize_endpoint_url(endpoint_url);
-        s3_config_builder = s3_config_builder
-            .endpoint_url(&normalized_url)
-            .force_path_style(true)
-            .request_checksum_calculation(RequestChecksumCalculation::WhenRequired)
-            .response_checksum_validation(ResponseChecksumValidation::WhenRequired);
+        s3_config_builder = s3_config_builder.endpoint_url(&normalized_url);
+    }
+
+    if let Some(quirks) = profile.provider_quirks() {
+        s3_config_builder = crate::s3::provider::apply_provider_quirks(s3_config_builder, &quirks);
     }
 
-    let client = Client::from_conf(s3_config_builder.build());
+    if let Some(ca_bundle_path) = profile.ca_bundle() {
+        let http_client = crate::s3::client::custom_ca_http_client(ca_bundle_path).map_err(|e| e.to_string())?;
+        s3_config_builder = s3_config_builder.http_client(http_client);
+    }
+
+    Ok(Client::from_conf(s3_config_builder.build()))
+}
+
+/// List buckets with a throwaway client built from the profile as given
+async fn check_profile_connection(profile: &Profile) -> Result<TestConnectionResult, String> {
+    use aws_sdk_s3::error::ProvideErrorMetadata;
+
+    let client = uncached_client(profile, profile.region.as_deref().unwrap_or("us-east-1")).await?;
 
     // Test connection by listing buckets
     match client.list_buckets().send().await {
@@ -208,6 +322,16 @@ pub async fn test_connection(
                 });
             }
 
+            // A website or console URL answers with HTML the SDK can't parse
+            if crate::s3::provider::is_non_s3_error(&e) {
+                return Ok(TestConnectionResult {
+                    success: false,
+                    message: format!("Connection failed: {}", crate::s3::provider::NOT_S3_ENDPOINT_MESSAGE),
+                    region: None,
+                    bucket_count: None,
+                });
+            }
+
             let s3_err = e.as_service_error();
             let code = s3_err.and_then(|s| s.code()).unwrap_or("Unknown");
             let message = s3_err.and_then(|s| s.message()).unwrap_or("No message");
@@ -233,6 +357,192 @@ pub async fn test_connection(
     }
 }
 
+/// Time allowed for each profile in `test_all_profiles`, so one unreachable
+/// endpoint or stuck SSO refresh doesn't hold up the rest
+const PROFILE_CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+#[derive(Debug, Serialize)]
+pub struct ProfileConnectionStatus {
+    pub profile_id: String,
+    pub profile_name: String,
+    #[serde(flatten)]
+    pub result: TestConnectionResult,
+}
+
+/// Run the `test_connection` check for every saved profile concurrently
+#[tauri::command]
+pub async fn test_all_profiles(
+    state: State<'_, ProfileState>,
+) -> Result<Vec<ProfileConnectionStatus>, String> {
+    let profiles = {
+        let manager = state.read().await;
+        let profiles = manager.list_profiles().await.map_err(|e| e.to_string())?;
+        profiles
+            .into_iter()
+            .map(|profile| manager.hydrate_profile(profile))
+            .collect::<Vec<_>>()
+    };
+
+    let checks = profiles.into_iter().map(|profile| async move {
+        let result = match tokio::time::timeout(PROFILE_CHECK_TIMEOUT, check_profile_connection(&profile)).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => TestConnectionResult {
+                success: false,
+                message: format!("Connection failed: {}", e),
+                region: None,
+                bucket_count: None,
+            },
+            Err(_) => TestConnectionResult {
+                success: false,
+                message: format!("Connection failed: no response within {}s", PROFILE_CHECK_TIMEOUT.as_secs()),
+                region: None,
+                bucket_count: None,
+            },
+        };
+        ProfileConnectionStatus {
+            profile_id: profile.id,
+            profile_name: profile.name,
+            result,
+        }
+    });
+
+    Ok(futures::future::join_all(checks).await)
+}
+
+/// AWS regions probed when the caller doesn't pick any
+const DEFAULT_LATENCY_REGIONS: &[&str] = &[
+    "us-east-1",
+    "us-east-2",
+    "us-west-1",
+    "us-west-2",
+    "ca-central-1",
+    "eu-west-1",
+    "eu-west-2",
+    "eu-central-1",
+    "eu-north-1",
+    "ap-south-1",
+    "ap-southeast-1",
+    "ap-southeast-2",
+    "ap-northeast-1",
+    "sa-east-1",
+];
+
+#[derive(Debug, Serialize)]
+pub struct RegionLatency {
+    pub region: String,
+    /// Round-trip time of a ListBuckets call, set whenever the endpoint answered
+    pub latency_ms: Option<u64>,
+    /// The endpoint answered, even if it rejected the request (e.g. AccessDenied)
+    pub reachable: bool,
+    pub error: Option<String>,
+}
+
+/// Time a ListBuckets round trip to each region so the fastest one can be picked.
+/// Custom endpoint profiles only have one endpoint, so only that is measured.
+#[tauri::command]
+pub async fn measure_region_latency(
+    profile_id: Option<String>,
+    regions: Option<Vec<String>>,
+    state: State<'_, ProfileState>,
+) -> Result<Vec<RegionLatency>, String> {
+    let profile = {
+        let manager = state.read().await;
+        match profile_id {
+            Some(id) => manager.get_profile(&id).await.map_err(|e| e.to_string())?,
+            None => manager
+                .get_active_profile()
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| "No active profile".to_string())?,
+        }
+    };
+
+    let regions: Vec<String> =
+        if matches!(profile.credential_type, crate::credentials::CredentialType::CustomEndpoint { .. }) {
+            vec![profile.region.clone().unwrap_or_else(|| "us-east-1".to_string())]
+        } else {
+            match regions {
+                Some(regions) if !regions.is_empty() => regions,
+                _ => DEFAULT_LATENCY_REGIONS.iter().map(|r| r.to_string()).collect(),
+            }
+        };
+
+    let timeout = profile.operation_timeout();
+    let profile = &profile;
+    let probes = regions.into_iter().map(|region| async move {
+        let client = match uncached_client(profile, &region).await {
+            Ok(client) => client,
+            Err(error) => {
+                return RegionLatency { region, latency_ms: None, reachable: false, error: Some(error) };
+            }
+        };
+
+        let started = std::time::Instant::now();
+        let result = tokio::time::timeout(timeout, client.list_buckets().send()).await;
+        let elapsed = started.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(Ok(_)) => RegionLatency { region, latency_ms: Some(elapsed), reachable: true, error: None },
+            // A service error still means the request made a full round trip
+            Ok(Err(aws_sdk_s3::error::SdkError::ServiceError(err))) => RegionLatency {
+                region,
+                latency_ms: Some(elapsed),
+                reachable: true,
+                error: Some(
+                    aws_sdk_s3::error::ProvideErrorMetadata::code(err.err())
+                        .unwrap_or("ServiceError")
+                        .to_string(),
+                ),
+            },
+            Ok(Err(err)) => RegionLatency { region, latency_ms: None, reachable: false, error: Some(err.to_string()) },
+            Err(_) => RegionLatency {
+                region,
+                latency_ms: None,
+                reachable: false,
+                error: Some(format!("Timed out after {}s", timeout.as_secs())),
+            },
+        }
+    });
+
+    let mut results = futures::future::join_all(probes).await;
+    results.sort_by_key(|r| r.latency_ms.unwrap_or(u64::MAX));
+    Ok(results)
+}
+
+/// Classify an S3-compatible endpoint and return the quirks its client should use
+#[tauri::command]
+pub async fn detect_provider(endpoint_url: String) -> Result<ProviderDetection, String> {
+    if endpoint_url.trim().is_empty() {
+        return Err("Endpoint URL is required".to_string());
+    }
+
+    let detection = crate::s3::provider::detect_provider(&endpoint_url).await;
+    log::info!(
+        "Detected provider {:?} for '{}' via {}",
+        detection.provider,
+        endpoint_url,
+        detection.detected_by
+    );
+    Ok(detection)
+}
+
+/// Check that an endpoint speaks the S3 API rather than serving web pages
+#[tauri::command]
+pub async fn validate_endpoint(endpoint_url: String) -> Result<EndpointCheck, String> {
+    if endpoint_url.trim().is_empty() {
+        return Err("Endpoint URL is required".to_string());
+    }
+
+    let check = crate::s3::provider::check_endpoint(&endpoint_url).await;
+    log::info!(
+        "Validated endpoint '{}': reachable={}, speaks_s3={}",
+        endpoint_url,
+        check.reachable,
+        check.speaks_s3
+    );
+    Ok(check)
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DiscoveredProfile {
     pub name: String,
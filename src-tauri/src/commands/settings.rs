@@ -0,0 +1,24 @@
+use crate::commands::transfer::TransferState;
+use crate::error::Result;
+use crate::s3::S3State;
+use crate::settings::{Settings, SettingsState};
+use tauri::State;
+
+#[tauri::command]
+pub async fn get_settings(settings_state: State<'_, SettingsState>) -> Result<Settings> {
+    Ok(settings_state.read().await.settings().clone())
+}
+
+/// Apply and save new settings. Returns them as applied, with out-of-range
+/// values clamped.
+#[tauri::command]
+pub async fn update_settings(
+    settings: Settings,
+    settings_state: State<'_, SettingsState>,
+    s3_state: State<'_, S3State>,
+    transfer_state: State<'_, TransferState>,
+) -> Result<Settings> {
+    let mut settings = settings;
+    settings.apply_to(&mut *s3_state.write().await, &transfer_state);
+    settings_state.write().await.replace(settings)
+}
@@ -1,33 +1,53 @@
+use crate::activity::{s3_target, ActivityState, OperationHandle};
+use crate::commands::buckets::{bucket_client, require_active_profile};
+use crate::commands::operations::{FolderOperationSummary, ObjectPages};
 use crate::commands::profiles::ProfileState;
+use crate::s3::metrics::{RequestMetricsSnapshot, TransferMetricsSnapshot};
+use crate::s3::pricing::{self, CostBreakdown, PriceTable};
+use crate::s3::provider::ProviderKind;
 use crate::s3::S3State;
+use crate::settings::SettingsState;
+use crate::transfer::exclude::{is_hidden, ExcludeMatcher, DEFAULT_EXCLUDES};
+use crate::transfer::multipart::MultipartState;
 use crate::transfer::{TransferJob, TransferManager, TransferType};
-use crate::error::Result;
-use tauri::{State, AppHandle};
+use crate::error::{AppError, Result};
+use tauri::{State, AppHandle, Emitter};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 // We need to store the TransferManager in Tauri state
 pub type TransferState = Arc<TransferManager>;
 
-async fn list_folder_objects(
+/// List the objects under a prefix, keeping whatever was listed before a page failed
+async fn list_folder_objects_partial(
     client: &aws_sdk_s3::Client,
     bucket_name: &str,
     prefix: &str,
-) -> Result<Vec<(String, u64)>> {
+    page_size: i32,
+    cancelled: &AtomicBool,
+) -> (Vec<(String, u64)>, Option<crate::error::AppError>) {
     let mut all_objects = Vec::new();
     let mut continuation_token = None;
 
     loop {
+        if cancelled.load(Ordering::Relaxed) {
+            return (all_objects, Some(crate::error::AppError::Cancelled(format!("Listing '{}'", prefix))));
+        }
         let mut req = client.list_objects_v2()
             .bucket(bucket_name)
-            .prefix(prefix);
+            .prefix(prefix)
+            .max_keys(page_size);
 
         if let Some(ref token) = continuation_token {
             req = req.continuation_token(token);
         }
 
-        let resp = req.send().await
-            .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?;
+        let resp = match req.send().await {
+            Ok(resp) => resp,
+            Err(e) => return (all_objects, Some(crate::error::AppError::S3Error(e.to_string()))),
+        };
 
         if let Some(contents) = resp.contents {
             for obj in contents {
@@ -46,7 +66,48 @@ async fn list_folder_objects(
         }
     }
 
-    Ok(all_objects)
+    (all_objects, None)
+}
+
+/// The caller's exclude globs, plus [`DEFAULT_EXCLUDES`] unless turned off
+fn upload_excludes(exclude_globs: Option<Vec<String>>, use_default_excludes: Option<bool>) -> ExcludeMatcher {
+    let mut patterns = exclude_globs.unwrap_or_default();
+    if use_default_excludes.unwrap_or(true) {
+        patterns.extend(DEFAULT_EXCLUDES.iter().map(|p| p.to_string()));
+    }
+    ExcludeMatcher::new(patterns)
+}
+
+/// Walk `root` the way a folder upload does, returning each file to upload with
+/// its size and how many entries were skipped as excluded or hidden. Skipped
+/// folders count once and are not walked. Blocking.
+fn collect_upload_files(root: &std::path::Path, excludes: &ExcludeMatcher, include_hidden: bool) -> (Vec<(PathBuf, u64)>, u32) {
+    let mut skipped = 0u32;
+    let found = walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            // Excludes are evaluated relative to the chosen folder itself
+            let excluded = !excludes.is_empty() && {
+                let walk_rel = entry.path().strip_prefix(root).unwrap_or(entry.path());
+                excludes.is_excluded(&walk_rel.to_string_lossy().replace("\\", "/"))
+            };
+            if excluded || (!include_hidden && is_hidden(entry)) {
+                skipped += 1;
+                return false;
+            }
+            true
+        })
+        .filter_map(|e| e.ok())
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| {
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            (entry.into_path(), size)
+        })
+        .collect();
+    (found, skipped)
 }
 
 fn validate_path(path: &std::path::Path) -> Result<()> {
@@ -115,6 +176,48 @@ pub async fn queue_upload(
     Ok(job_id)
 }
 
+/// Queue an upload that streams an HTTP(S) URL straight into `bucket_name`/`key`
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn import_url_to_s3(
+    url: String,
+    bucket_name: String,
+    bucket_region: Option<String>,
+    key: String,
+    app_handle: AppHandle,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    transfer_state: State<'_, TransferState>,
+) -> Result<String> {
+    let parsed = reqwest::Url::parse(&url).map_err(|e| AppError::InvalidContent(format!("Invalid URL '{}': {}", url, e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(AppError::InvalidContent(format!("Only http and https URLs can be imported, got '{}'", url)));
+    }
+    if key.is_empty() || key.ends_with('/') {
+        return Err(AppError::InvalidContent(format!("'{}' is not a valid object key", key)));
+    }
+
+    let job = TransferJob::url_import(bucket_name, bucket_region, key, parsed.to_string());
+    let job_id = job.id.clone();
+
+    transfer_state.set_app_handle(app_handle.clone()).await;
+    transfer_state.add_job(job).await;
+
+    let t_state = transfer_state.inner().clone();
+    let p_state = profile_state.inner().clone();
+    let s_state = s3_state.inner().clone();
+
+    tauri::async_runtime::spawn(async move {
+        let profile_manager = p_state.read().await;
+        if let Ok(Some(profile)) = profile_manager.get_active_profile().await {
+            drop(profile_manager);
+            t_state.process_queue(s_state, profile).await;
+        }
+    });
+
+    Ok(job_id)
+}
+
 #[tauri::command]
 pub async fn queue_download(
     bucket_name: String,
@@ -168,46 +271,55 @@ pub async fn list_transfers(
     Ok(transfer_state.list_jobs().await)
 }
 
+/// Outcome of walking a folder for upload, emitted before the jobs start
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FolderUploadScan {
+    pub group_id: String,
+    pub queued: u32,
+    /// Excluded or hidden files, plus skipped folders counted once each
+    pub skipped: u32,
+}
+
 #[tauri::command]
 pub async fn queue_folder_upload(
     bucket_name: String,
     bucket_region: Option<String>,
     prefix: String,
     local_path: String,
+    exclude_globs: Option<Vec<String>>,
+    use_default_excludes: Option<bool>,
+    include_hidden: Option<bool>,
     app_handle: AppHandle,
     profile_state: State<'_, ProfileState>,
     s3_state: State<'_, S3State>,
     transfer_state: State<'_, TransferState>,
 ) -> Result<u32> {
-    use walkdir::WalkDir;
-    
     let root = PathBuf::from(&local_path);
     validate_path(&root)?;
     // Calculate parent to determine relative key prefix
     let parent = root.parent().unwrap_or(&root).to_path_buf();
-    
-    let walker = WalkDir::new(&root).into_iter();
+
+    let excludes = upload_excludes(exclude_globs, use_default_excludes);
+    let include_hidden = include_hidden.unwrap_or(true);
     
     // Blocking walk to gather files
     let prefix_clone = prefix.clone();
-    let jobs_data = tauri::async_runtime::spawn_blocking(move || {
-        let mut found = Vec::new();
-        for entry in walker.filter_map(|e| e.ok()) {
-            if entry.path().is_file() {
-                let path = entry.path().to_path_buf();
-                let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
-                
+    let walk_root = root.clone();
+    let (jobs_data, skipped) = tauri::async_runtime::spawn_blocking(move || {
+        let (files, skipped) = collect_upload_files(&walk_root, &excludes, include_hidden);
+        let found: Vec<(PathBuf, u64, String)> = files
+            .into_iter()
+            .map(|(path, size)| {
                 // key = prefix + relative_path_from_parent
                 // e.g. root=/foo/bar, file=/foo/bar/baz.txt. parent=/foo.
                 // relative = bar/baz.txt
                 let rel_path = path.strip_prefix(&parent).unwrap_or(&path);
                 let rel_str = rel_path.to_string_lossy().replace("\\", "/");
                 let key = format!("{}{}", prefix_clone, rel_str);
-                
-                found.push((path, size, key));
-            }
-        }
-        found
+                (path, size, key)
+            })
+            .collect();
+        (found, skipped)
     }).await.map_err(|e| crate::error::AppError::IoError(e.to_string()))?;
     
     let current_manager = transfer_state.clone();
@@ -216,6 +328,15 @@ pub async fn queue_folder_upload(
     let count = jobs_data.len() as u32;
     let group_id = uuid::Uuid::new_v4().to_string();
     let group_name = format!("s3://{}/{}", bucket_name, prefix);
+
+    if skipped > 0 {
+        log::info!("Folder upload {}: queued {} files, skipped {} excluded", local_path, count, skipped);
+    }
+    let _ = app_handle.emit("folder-upload-scanned", FolderUploadScan {
+        group_id: group_id.clone(),
+        queued: count,
+        skipped,
+    });
     
     for (path, size, key) in jobs_data {
         let job = TransferJob::new(
@@ -246,12 +367,21 @@ pub async fn queue_folder_upload(
     Ok(count)
 }
 
+/// Outcome of listing a folder for download, emitted before the jobs start
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FolderDownloadScan {
+    pub group_id: String,
+    #[serde(flatten)]
+    pub summary: FolderOperationSummary,
+}
+
 #[tauri::command]
 pub async fn queue_folder_download(
     bucket_name: String,
     bucket_region: Option<String>,
     prefix: String,
     local_path: String,
+    continue_on_error: Option<bool>,
     app_handle: AppHandle,
     profile_state: State<'_, ProfileState>,
     s3_state: State<'_, S3State>,
@@ -259,59 +389,60 @@ pub async fn queue_folder_download(
 ) -> Result<u32> {
     let root_path = PathBuf::from(&local_path);
     validate_path(&root_path)?;
+    let continue_on_error = continue_on_error.unwrap_or(false);
+    let mut summary = FolderOperationSummary::default();
     
     // 1. List all objects in the prefix
-    let profile_manager = profile_state.read().await;
-    let profile = profile_manager.get_active_profile().await?
-        .ok_or_else(|| crate::error::AppError::ConfigError("No active profile".to_string()))?;
-        
+    let profile = require_active_profile(&profile_state).await?;
+
     let objects = {
-        let resolved_region = {
-            let s3 = s3_state.read().await;
-            s3.get_bucket_region(&bucket_name)
-        }.or(bucket_region.clone());
+        let page_size = s3_state.read().await.list_page_size();
+        let (_, client) = bucket_client(&s3_state, &profile, &bucket_name, bucket_region.clone()).await?;
 
-        let client = {
-            let mut s3 = s3_state.write().await;
-            if let Some(ref region) = resolved_region {
-                s3.get_client_for_region(&profile, region).await?.clone()
-            } else {
-                s3.get_client(&profile).await?.clone()
+        let not_cancelled = AtomicBool::new(false);
+        // A failure on the very first page is most likely the wrong region; later
+        // pages keep whatever was already listed.
+        let list = |client: aws_sdk_s3::Client| {
+            let (bucket_name, prefix, not_cancelled) = (&bucket_name, &prefix, &not_cancelled);
+            async move {
+                match list_folder_objects_partial(&client, bucket_name, prefix, page_size, not_cancelled).await {
+                    (objects, Some(err)) if objects.is_empty() => Err(err),
+                    listed => Ok(listed),
+                }
             }
         };
-
-        match list_folder_objects(&client, &bucket_name, &prefix).await {
-            Ok(objects) => objects,
+        let (objects, listing_error) = match list(client).await {
+            Ok(listed) => listed,
             Err(err) => {
                 log::warn!("queue_folder_download listing failed, attempting region discovery: {}", err);
-                let retry_client = {
-                    let mut s3 = s3_state.write().await;
-                    s3.get_client(&profile).await?.clone()
+                let retry = crate::s3::retry_in_bucket_region(&s3_state, &profile, &bucket_name, None, list).await?;
+                let Some(retry) = retry else {
+                    return Err(err);
                 };
+                retry.result.map_err(|e| crate::error::AppError::S3Error(format!("Retry folder listing failed: {}", e)))?
+            }
+        };
 
-                if let Ok(new_region) = crate::s3::get_bucket_region(&retry_client, &bucket_name).await {
-                    {
-                        let mut s3 = s3_state.write().await;
-                        s3.set_bucket_region(&bucket_name, new_region.clone());
-                    }
-
-                    let retry_client = {
-                        let mut s3 = s3_state.write().await;
-                        s3.get_client_for_region(&profile, &new_region).await?.clone()
-                    };
-
-                    list_folder_objects(&retry_client, &bucket_name, &prefix).await
-                        .map_err(|e| crate::error::AppError::S3Error(format!("Retry folder listing failed: {}", e)))?
-                } else {
-                    return Err(err);
-                }
+        if let Some(err) = listing_error {
+            if !continue_on_error {
+                return Err(err);
             }
+            log::warn!("Folder listing for '{}' stopped after {} objects: {}", prefix, objects.len(), err);
+            summary.failed.push((prefix.clone(), format!("Listing stopped early: {}", err)));
         }
+
+        objects
     };
     
+    // Check the whole folder fits before queueing any of it, rather than
+    // failing partway through the group
+    if transfer_state.checks_free_space() {
+        let total_bytes: u64 = objects.iter().map(|(_, size)| *size).sum();
+        crate::transfer::disk_space::ensure_free_space(&root_path, total_bytes)?;
+    }
+
     let group_id = uuid::Uuid::new_v4().to_string();
     let group_name = format!("s3://{}/{}", bucket_name, prefix);
-    let count = objects.len() as u32;
     let root_path = PathBuf::from(&local_path); // This is the destination folder
     
     transfer_state.set_app_handle(app_handle.clone()).await;
@@ -326,7 +457,13 @@ pub async fn queue_folder_download(
         if relative_key.is_empty() { continue; }
         
         let file_path = root_path.join(relative_key);
-        validate_path(&file_path)?;
+        if let Err(e) = validate_path(&file_path) {
+            if !continue_on_error {
+                return Err(e);
+            }
+            summary.failed.push((key, e.to_string()));
+            continue;
+        }
         
         let job = TransferJob::new(
             TransferType::Download,
@@ -338,7 +475,17 @@ pub async fn queue_folder_download(
         ).with_group(group_id.clone(), group_name.clone());
         
         transfer_state.add_job(job).await;
+        summary.succeeded += 1;
+    }
+
+    let count = summary.succeeded as u32;
+    if !summary.failed.is_empty() {
+        log::warn!("Folder download {}: queued {}, {} failures", group_name, count, summary.failed.len());
     }
+    let _ = app_handle.emit("folder-download-scanned", FolderDownloadScan {
+        group_id: group_id.clone(),
+        summary,
+    });
     
     // Trigger processing
     let t_state = transfer_state.inner().clone();
@@ -356,12 +503,91 @@ pub async fn queue_folder_download(
     Ok(count)
 }
 
+/// Cancel a transfer. A cancelled multipart upload is aborted on S3 so no parts
+/// are left behind; single-part uploads never commit a partial body.
 #[tauri::command]
 pub async fn cancel_transfer(
     job_id: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
     transfer_state: State<'_, TransferState>,
 ) -> Result<bool> {
-    Ok(transfer_state.cancel_job(&job_id).await)
+    if !transfer_state.cancel_job(&job_id).await {
+        return Ok(false);
+    }
+    if let Some(job) = transfer_state.get_job(&job_id).await {
+        abort_cancelled_uploads(&[job], &profile_state, &s3_state, &transfer_state).await?;
+    }
+    Ok(true)
+}
+
+/// Cancel every pending and running transfer in a folder group, aborting the
+/// multipart uploads of cancelled jobs so their parts don't linger on S3.
+/// Returns how many jobs were cancelled.
+#[tauri::command]
+pub async fn cancel_group(
+    group_id: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    transfer_state: State<'_, TransferState>,
+) -> Result<usize> {
+    let cancelled = transfer_state.cancel_group(&group_id).await;
+    abort_cancelled_uploads(&cancelled, &profile_state, &s3_state, &transfer_state).await?;
+    Ok(cancelled.len())
+}
+
+/// Abort the multipart uploads behind cancelled upload jobs. Uploads still being
+/// created abort themselves once they see the job was cancelled.
+async fn abort_cancelled_uploads(
+    jobs: &[TransferJob],
+    profile_state: &State<'_, ProfileState>,
+    s3_state: &State<'_, S3State>,
+    transfer_state: &State<'_, TransferState>,
+) -> Result<()> {
+    let mut uploads = Vec::new();
+    for job in jobs.iter().filter(|job| matches!(job.transfer_type, TransferType::Upload)) {
+        if let Some(state) = transfer_state.multipart_state_for(job).await {
+            uploads.push(state);
+        }
+    }
+    if uploads.is_empty() {
+        return Ok(());
+    }
+
+    let profile = {
+        let profile_manager = profile_state.read().await;
+        profile_manager.get_active_profile().await?
+    };
+    for state in uploads {
+        let Some(profile) = profile.as_ref().filter(|profile| profile.id == state.profile_id) else {
+            continue;
+        };
+        let client = {
+            let mut s3_manager = s3_state.write().await;
+            match state.bucket_region.as_deref() {
+                Some(region) => s3_manager.get_client_for_region(profile, region).await?.clone(),
+                None => s3_manager.get_client(profile).await?.clone(),
+            }
+        };
+        if let Err(e) = transfer_state.abort_upload(&state, &client).await {
+            log::warn!("Could not abort multipart upload of '{}': {}", state.key, e);
+        }
+    }
+    Ok(())
+}
+
+/// Give a transfer group a readable name in place of its `s3://bucket/prefix` label
+#[tauri::command]
+pub async fn rename_group(
+    group_id: String,
+    name: String,
+    transfer_state: State<'_, TransferState>,
+) -> Result<usize> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(crate::error::AppError::InvalidContent("Group name cannot be empty".into()));
+    }
+    Ok(transfer_state.rename_group(&group_id, name).await)
 }
 
 #[tauri::command]
@@ -392,6 +618,137 @@ pub async fn retry_transfer(
     Ok(new_id)
 }
 
+/// Multipart uploads interrupted by a failure, cancellation or restart that can
+/// still be continued with `resume_upload`
+#[tauri::command]
+pub async fn list_resumable_uploads(
+    transfer_state: State<'_, TransferState>,
+) -> Result<Vec<MultipartState>> {
+    Ok(transfer_state.resumable_uploads().await)
+}
+
+/// Queue an interrupted multipart upload again. Parts already on S3 are checked
+/// with ListParts and only the missing ones are sent.
+#[tauri::command]
+pub async fn resume_upload(
+    bucket_name: String,
+    key: String,
+    local_path: String,
+    app_handle: AppHandle,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    transfer_state: State<'_, TransferState>,
+) -> Result<String> {
+    let state = transfer_state
+        .resumable_uploads()
+        .await
+        .into_iter()
+        .find(|state| state.bucket == bucket_name && state.key == key && state.local_path == local_path)
+        .ok_or_else(|| crate::error::AppError::InvalidContent(format!("No interrupted upload of '{}' to resume", key)))?;
+
+    let profile = {
+        let profile_manager = profile_state.read().await;
+        profile_manager
+            .get_active_profile()
+            .await?
+            .ok_or_else(|| crate::error::AppError::ProfileNotFound("No active profile".into()))?
+    };
+    if profile.id != state.profile_id {
+        return Err(crate::error::AppError::InvalidContent(
+            "This upload was started with a different profile. Switch to it to resume.".to_string(),
+        ));
+    }
+
+    let job = TransferJob::new(
+        TransferType::Upload,
+        state.bucket,
+        state.bucket_region,
+        state.key,
+        PathBuf::from(&state.local_path),
+        state.file_size,
+    );
+    let job_id = job.id.clone();
+
+    transfer_state.set_app_handle(app_handle.clone()).await;
+    transfer_state.add_job(job).await;
+
+    let t_state = transfer_state.inner().clone();
+    let s_state = s3_state.inner().clone();
+    tauri::async_runtime::spawn(async move {
+        t_state.process_queue(s_state, profile).await;
+    });
+
+    Ok(job_id)
+}
+
+/// Load saved multipart upload state and abort uploads abandoned for longer than
+/// `STALE_UPLOAD_AGE`, so their parts stop accruing storage charges
+pub async fn clean_up_stale_uploads(
+    config_dir: PathBuf,
+    profile_state: ProfileState,
+    s3_state: S3State,
+    transfer_state: TransferState,
+) {
+    transfer_state.load_multipart_state(&config_dir).await;
+
+    for state in transfer_state.stale_uploads().await {
+        let profile = {
+            let profile_manager = profile_state.read().await;
+            profile_manager.get_profile(&state.profile_id).await.ok()
+        };
+        let Some(profile) = profile else {
+            // The profile is gone, so there are no credentials to abort with
+            transfer_state.forget_upload(&state).await;
+            continue;
+        };
+
+        let client = {
+            let mut s3_manager = s3_state.write().await;
+            match state.bucket_region.as_deref() {
+                Some(region) => s3_manager.get_client_for_region(&profile, region).await.cloned(),
+                None => s3_manager.get_client(&profile).await.cloned(),
+            }
+        };
+        let result = match client {
+            Ok(client) => transfer_state.abort_upload(&state, &client).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => log::info!("Aborted abandoned upload of '{}' to '{}'", state.key, state.bucket),
+            Err(e) => log::warn!("Could not abort abandoned upload of '{}': {}", state.key, e),
+        }
+    }
+}
+
+/// Retry all failed transfers (and cancelled ones if requested), returning how many were requeued
+#[tauri::command]
+pub async fn retry_all_failed(
+    include_cancelled: Option<bool>,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    transfer_state: State<'_, TransferState>,
+) -> Result<usize> {
+    let retried = transfer_state
+        .retry_all_failed(include_cancelled.unwrap_or(false))
+        .await;
+
+    if retried > 0 {
+        let t_state = transfer_state.inner().clone();
+        let p_state = profile_state.inner().clone();
+        let s_state = s3_state.inner().clone();
+
+        tauri::async_runtime::spawn(async move {
+            let profile_manager = p_state.read().await;
+            if let Ok(Some(profile)) = profile_manager.get_active_profile().await {
+                drop(profile_manager);
+                t_state.process_queue(s_state, profile).await;
+            }
+        });
+    }
+
+    Ok(retried)
+}
+
 #[tauri::command]
 pub async fn remove_transfer(
     job_id: String,
@@ -407,11 +764,401 @@ pub async fn clear_completed_transfers(
     Ok(transfer_state.clear_completed().await)
 }
 
+/// Set the `max_keys` page size used when listing whole folders for downloads,
+/// copies, moves and permission changes. Returns the clamped value in effect.
+#[tauri::command]
+pub async fn set_list_page_size(
+    page_size: i32,
+    s3_state: State<'_, S3State>,
+    settings_state: State<'_, SettingsState>,
+) -> Result<i32> {
+    let applied = s3_state.write().await.set_list_page_size(page_size);
+    settings_state.write().await.modify(|settings| settings.list_page_size = applied)?;
+    Ok(applied)
+}
+
+/// Set how many times a request that hit the wrong region is retried in the
+/// bucket's own region, and the pause that grows between those retries. Returns
+/// the clamped policy in effect.
+#[tauri::command]
+pub async fn set_region_redirect_retries(
+    max_attempts: u32,
+    backoff_ms: u64,
+    s3_state: State<'_, S3State>,
+    settings_state: State<'_, SettingsState>,
+) -> Result<crate::s3::RegionRedirectPolicy> {
+    let applied = s3_state.write().await.set_redirect_policy(max_attempts, backoff_ms);
+    settings_state.write().await.modify(|settings| settings.region_redirect = applied)?;
+    Ok(applied)
+}
+
+/// Set how many objects a bucket listing needs before it is cached on disk
+/// instead of in memory. Returns the value in effect.
+#[tauri::command]
+pub async fn set_disk_cache_threshold(
+    object_count: usize,
+    s3_state: State<'_, S3State>,
+    settings_state: State<'_, SettingsState>,
+) -> Result<usize> {
+    let applied = s3_state.write().await.set_disk_cache_threshold(object_count);
+    settings_state.write().await.modify(|settings| settings.disk_cache_threshold = applied)?;
+    Ok(applied)
+}
+
+/// Move a bucket's cached listing from memory to disk. Returns false when the
+/// bucket isn't cached in memory.
+#[tauri::command]
+pub async fn move_bucket_cache_to_disk(
+    bucket_name: String,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+) -> Result<bool> {
+    let profile_manager = profile_state.read().await;
+    let profile = profile_manager
+        .get_active_profile()
+        .await?
+        .ok_or_else(|| crate::error::AppError::ProfileNotFound("No active profile".into()))?;
+    drop(profile_manager);
+
+    Ok(crate::s3::client::move_cache_to_disk(&s3_state, &profile.id, &bucket_name).await)
+}
+
 #[tauri::command]
 pub async fn set_transfer_concurrency(
     max_concurrency: u32,
     transfer_state: State<'_, TransferState>,
+    settings_state: State<'_, SettingsState>,
 ) -> Result<()> {
-    transfer_state.set_max_concurrency(max_concurrency as usize);
+    let applied = transfer_state.set_max_concurrency(max_concurrency as usize);
+    settings_state.write().await.modify(|settings| settings.transfer_concurrency = applied)?;
     Ok(())
 }
+
+/// Turn the free disk space check before downloads on or off
+#[tauri::command]
+pub async fn set_free_space_check(
+    enabled: bool,
+    transfer_state: State<'_, TransferState>,
+    settings_state: State<'_, SettingsState>,
+) -> Result<()> {
+    transfer_state.set_check_free_space(enabled);
+    settings_state.write().await.modify(|settings| settings.check_free_space = enabled)?;
+    Ok(())
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct OperationMetrics {
+    pub s3: RequestMetricsSnapshot,
+    pub transfers: TransferMetricsSnapshot,
+}
+
+/// Request, cache and transfer counters since startup, for telling network
+/// slowness apart from cache churn or contention on the shared client manager
+#[tauri::command]
+pub async fn get_metrics(
+    s3_state: State<'_, S3State>,
+    transfer_state: State<'_, TransferState>,
+) -> Result<OperationMetrics> {
+    Ok(OperationMetrics {
+        s3: s3_state.read().await.metrics(),
+        transfers: transfer_state.metrics(),
+    })
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct DiffEntry {
+    /// Path relative to the local folder / remote prefix, using `/` separators
+    pub path: String,
+    pub local_size: Option<u64>,
+    pub remote_size: Option<u64>,
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct LocalRemoteDiff {
+    pub local_only: Vec<DiffEntry>,
+    pub remote_only: Vec<DiffEntry>,
+    pub size_mismatch: Vec<DiffEntry>,
+    pub identical_count: usize,
+}
+
+fn diff_local_remote(local: &BTreeMap<String, u64>, remote: &BTreeMap<String, u64>) -> LocalRemoteDiff {
+    let mut diff = LocalRemoteDiff::default();
+
+    for (path, &local_size) in local {
+        match remote.get(path) {
+            Some(&remote_size) if remote_size == local_size => diff.identical_count += 1,
+            Some(&remote_size) => diff.size_mismatch.push(DiffEntry {
+                path: path.clone(),
+                local_size: Some(local_size),
+                remote_size: Some(remote_size),
+            }),
+            None => diff.local_only.push(DiffEntry {
+                path: path.clone(),
+                local_size: Some(local_size),
+                remote_size: None,
+            }),
+        }
+    }
+
+    for (path, &remote_size) in remote {
+        if !local.contains_key(path) {
+            diff.remote_only.push(DiffEntry {
+                path: path.clone(),
+                local_size: None,
+                remote_size: Some(remote_size),
+            });
+        }
+    }
+
+    diff
+}
+
+/// Compare the contents of a local folder with a remote prefix without transferring
+/// anything. Files are matched by their path relative to `local_path` and `prefix`,
+/// and counted as identical when the sizes agree. Local files are picked the way
+/// `queue_folder_upload` would pick them with the same exclude options.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn compare_local_remote(
+    local_path: String,
+    bucket_name: String,
+    bucket_region: Option<String>,
+    prefix: String,
+    exclude_globs: Option<Vec<String>>,
+    use_default_excludes: Option<bool>,
+    include_hidden: Option<bool>,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    activity_state: State<'_, ActivityState>,
+) -> Result<LocalRemoteDiff> {
+    let root = PathBuf::from(&local_path);
+    validate_path(&root)?;
+    if !root.is_dir() {
+        return Err(crate::error::AppError::IoError(format!("'{}' is not a folder", local_path)));
+    }
+
+    let prefix = if prefix.is_empty() || prefix.ends_with('/') {
+        prefix
+    } else {
+        format!("{}/", prefix)
+    };
+    let operation = activity_state.register("Local comparison", uuid::Uuid::new_v4().to_string(), s3_target(&bucket_name, &prefix));
+
+    let excludes = upload_excludes(exclude_globs, use_default_excludes);
+    let include_hidden = include_hidden.unwrap_or(true);
+    let local_files = tauri::async_runtime::spawn_blocking(move || {
+        let (files, _) = collect_upload_files(&root, &excludes, include_hidden);
+        files
+            .into_iter()
+            .map(|(path, size)| {
+                let rel_path = path.strip_prefix(&root).unwrap_or(&path);
+                (rel_path.to_string_lossy().replace("\\", "/"), size)
+            })
+            .collect::<BTreeMap<_, _>>()
+    }).await.map_err(|e| crate::error::AppError::IoError(e.to_string()))?;
+
+    let profile = require_active_profile(&profile_state).await?;
+    let mut pages = ObjectPages::new(&s3_state, &profile, &bucket_name, bucket_region, &prefix).await?;
+
+    let mut remote_files = BTreeMap::new();
+    loop {
+        operation.check()?;
+        let Some(page) = pages.next_page().await? else {
+            break;
+        };
+
+        for obj in page.contents() {
+            let Some(key) = obj.key() else { continue };
+            let Some(relative) = key.strip_prefix(&prefix) else { continue };
+            if !relative.is_empty() && !key.ends_with('/') {
+                remote_files.insert(relative.to_string(), obj.size().unwrap_or(0).max(0) as u64);
+            }
+        }
+    }
+
+    Ok(diff_local_remote(&local_files, &remote_files))
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct TransferEstimate {
+    pub direction: String,
+    pub provider: ProviderKind,
+    pub object_count: u64,
+    pub total_bytes: u64,
+    pub total_size_formatted: String,
+    pub request_count: u64,
+    #[serde(flatten)]
+    pub cost: CostBreakdown,
+    pub prices: PriceTable,
+}
+
+/// Count the objects and bytes under a prefix without keeping the keys, stopping
+/// between pages once `operation` is cancelled.
+/// Returns `(objects, bytes, list requests made)`.
+async fn count_prefix_objects(
+    pages: &mut ObjectPages<'_>,
+    operation: &OperationHandle,
+) -> Result<(u64, u64, u64)> {
+    let (mut objects, mut bytes, mut requests) = (0u64, 0u64, 0u64);
+
+    loop {
+        operation.check()?;
+        let Some(page) = pages.next_page().await? else {
+            return Ok((objects, bytes, requests));
+        };
+        requests += 1;
+
+        for obj in page.contents() {
+            if obj.key().is_some_and(|key| !key.ends_with('/')) {
+                objects += 1;
+                bytes += obj.size().unwrap_or(0).max(0) as u64;
+            }
+        }
+    }
+}
+
+/// Estimate what a transfer would cost with the active profile's provider prices.
+/// `direction` is `download` (egress), `copy` (server-side, no data charge) or
+/// `upload`, which counts the files under `local_path` that `queue_folder_upload`
+/// would queue with the same exclude options. Costs are rough: multipart parts and
+/// tiered pricing aren't modelled.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn estimate_transfer(
+    bucket_name: String,
+    bucket_region: Option<String>,
+    prefix: String,
+    direction: String,
+    local_path: Option<String>,
+    exclude_globs: Option<Vec<String>>,
+    use_default_excludes: Option<bool>,
+    include_hidden: Option<bool>,
+    profile_state: State<'_, ProfileState>,
+    s3_state: State<'_, S3State>,
+    activity_state: State<'_, ActivityState>,
+) -> Result<TransferEstimate> {
+    let direction = direction.trim().to_ascii_lowercase();
+    if !matches!(direction.as_str(), "download" | "upload" | "copy") {
+        return Err(crate::error::AppError::InvalidContent(format!(
+            "Unknown direction '{}'. Use download, upload or copy.",
+            direction
+        )));
+    }
+
+    let profile = require_active_profile(&profile_state).await?;
+    let provider = profile.provider_kind();
+    let prices = pricing::price_table_for(profile_state.read().await.config_dir(), provider);
+
+    let (object_count, total_bytes, list_requests) = if direction == "upload" {
+        let local_path = local_path.ok_or_else(|| {
+            crate::error::AppError::InvalidContent("local_path is required to estimate an upload".to_string())
+        })?;
+        let root = PathBuf::from(&local_path);
+        validate_path(&root)?;
+        let excludes = upload_excludes(exclude_globs, use_default_excludes);
+        let include_hidden = include_hidden.unwrap_or(true);
+        tauri::async_runtime::spawn_blocking(move || {
+            let (files, _) = collect_upload_files(&root, &excludes, include_hidden);
+            let bytes = files.iter().map(|(_, size)| size).sum();
+            (files.len() as u64, bytes, 0)
+        }).await.map_err(|e| crate::error::AppError::IoError(e.to_string()))?
+    } else {
+        let operation = activity_state.register("Transfer estimate", uuid::Uuid::new_v4().to_string(), s3_target(&bucket_name, &prefix));
+        let mut pages = ObjectPages::new(&s3_state, &profile, &bucket_name, bucket_region, &prefix).await?;
+        count_prefix_objects(&mut pages, &operation).await?
+    };
+
+    // LIST is billed like PUT; a copy is one PUT-class request per object
+    let (get_requests, put_requests, per_gb) = match direction.as_str() {
+        "download" => (object_count, list_requests, prices.egress_per_gb),
+        "upload" => (0, object_count, prices.ingress_per_gb),
+        _ => (0, object_count + list_requests, 0.0),
+    };
+
+    Ok(TransferEstimate {
+        direction,
+        provider,
+        object_count,
+        total_bytes,
+        total_size_formatted: crate::s3::format_size(total_bytes),
+        request_count: get_requests + put_requests,
+        cost: pricing::estimate_cost(get_requests, put_requests, total_bytes, per_gb, &prices),
+        prices,
+    })
+}
+
+/// Price tables used by `estimate_transfer`, with any user edits applied
+#[tauri::command]
+pub async fn get_price_tables(
+    profile_state: State<'_, ProfileState>,
+) -> Result<std::collections::HashMap<ProviderKind, PriceTable>> {
+    let profile_manager = profile_state.read().await;
+    Ok(pricing::load_price_tables(profile_manager.config_dir()))
+}
+
+/// Override a provider's prices; pass no table to restore the defaults
+#[tauri::command]
+pub async fn set_price_table(
+    provider: ProviderKind,
+    prices: Option<PriceTable>,
+    profile_state: State<'_, ProfileState>,
+) -> Result<PriceTable> {
+    let profile_manager = profile_state.read().await;
+    pricing::save_price_override(profile_manager.config_dir(), provider, prices)?;
+    Ok(pricing::price_table_for(profile_manager.config_dir(), provider))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_upload_files, diff_local_remote};
+    use crate::transfer::exclude::{ExcludeMatcher, DEFAULT_EXCLUDES};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn diff_local_remote_buckets_files_by_relative_path_and_size() {
+        let local = BTreeMap::from([
+            ("a.txt".to_string(), 10),
+            ("dir/b.txt".to_string(), 20),
+            ("only-local.txt".to_string(), 1),
+        ]);
+        let remote = BTreeMap::from([
+            ("a.txt".to_string(), 10),
+            ("dir/b.txt".to_string(), 21),
+            ("only-remote.txt".to_string(), 2),
+        ]);
+
+        let diff = diff_local_remote(&local, &remote);
+        assert_eq!(diff.identical_count, 1);
+        assert_eq!(diff.size_mismatch[0].path, "dir/b.txt");
+        assert_eq!(diff.local_only[0].path, "only-local.txt");
+        assert_eq!(diff.remote_only[0].path, "only-remote.txt");
+    }
+
+    #[test]
+    fn upload_walk_prunes_excluded_and_hidden_folders() {
+        let root = std::env::temp_dir().join(format!("brows3-upload-walk-{}", uuid::Uuid::new_v4()));
+        for dir in ["src", "node_modules/react", ".cache"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in ["src/main.rs", "node_modules/react/index.js", ".cache/blob", ".env", "README.md"] {
+            std::fs::write(root.join(file), b"x").unwrap();
+        }
+
+        let excludes = ExcludeMatcher::new(DEFAULT_EXCLUDES);
+        let (files, skipped) = collect_upload_files(&root, &excludes, false);
+        let mut names: Vec<String> = files
+            .iter()
+            .map(|(path, _)| path.strip_prefix(&root).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["README.md", "src/main.rs"]);
+        // node_modules, .cache and .env
+        assert_eq!(skipped, 3);
+
+        let (files, skipped) = collect_upload_files(&root, &excludes, true);
+        assert_eq!(files.len(), 4);
+        assert_eq!(skipped, 1);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -7,6 +7,7 @@ use std::path::{Path, PathBuf};
 
 const SERVICE_NAME: &str = "brows3-credentials";
 const FALLBACK_SECRETS_FILE: &str = "secrets.json";
+const PROBE_KEY: &str = "keychain-probe";
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 struct SecretsData {
@@ -14,6 +15,16 @@ struct SecretsData {
     secrets: HashMap<String, String>,
 }
 
+/// Result of checking whether the OS keychain can actually store secrets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeychainStatus {
+    /// The native keychain accepted a store, read and delete of a sentinel secret
+    pub native_available: bool,
+    /// Secrets are being kept in the local secrets file instead of the keychain
+    pub using_fallback: bool,
+    pub error: Option<String>,
+}
+
 /// Secure credential storage using OS keychain
 pub struct KeychainStorage {
     app_name: String,
@@ -153,6 +164,51 @@ impl KeychainStorage {
     pub fn exists(&self, key: &str) -> bool {
         self.get(key).is_ok()
     }
+
+    fn probe_native(&self) -> std::result::Result<(), String> {
+        let sentinel = uuid::Uuid::new_v4().to_string();
+        let entry = self.get_entry(PROBE_KEY).map_err(|e| e.to_string())?;
+
+        entry
+            .set_password(&sentinel)
+            .map_err(|e| format!("store failed: {}", e))?;
+        let read_back = entry.get_password().map_err(|e| format!("read failed: {}", e));
+        let deleted = entry
+            .delete_credential()
+            .map_err(|e| format!("delete failed: {}", e));
+
+        if read_back? != sentinel {
+            return Err("read returned a different value than was stored".to_string());
+        }
+        deleted
+    }
+
+    /// Round-trip a sentinel secret through the native keychain and report whether
+    /// it works. The storage mode is left alone: a failure may be transient (a
+    /// locked keychain, a dismissed prompt), and secrets already in the keychain
+    /// must stay readable.
+    pub fn probe(&self) -> KeychainStatus {
+        if self.force_fallback {
+            return KeychainStatus {
+                native_available: false,
+                using_fallback: true,
+                error: None,
+            };
+        }
+
+        match self.probe_native() {
+            Ok(()) => KeychainStatus {
+                native_available: true,
+                using_fallback: false,
+                error: None,
+            },
+            Err(err) => KeychainStatus {
+                native_available: false,
+                using_fallback: false,
+                error: Some(err),
+            },
+        }
+    }
 }
 
 #[cfg(test)]
@@ -173,4 +229,16 @@ mod tests {
 
         let _ = std::fs::remove_dir_all(config_dir);
     }
+
+    #[test]
+    fn forced_fallback_probe_skips_native_keychain() {
+        let config_dir =
+            std::env::temp_dir().join(format!("brows3-keychain-test-{}", uuid::Uuid::new_v4()));
+        let storage = KeychainStorage::new("brows3-test", &config_dir, true);
+
+        let status = storage.probe();
+        assert!(!status.native_available);
+        assert!(status.using_fallback);
+        assert!(status.error.is_none());
+    }
 }
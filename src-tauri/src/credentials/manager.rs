@@ -1,10 +1,12 @@
 use crate::error::{AppError, Result};
+use crate::s3::provider::{classify_host, endpoint_host, ProviderKind, ProviderQuirks};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use uuid::Uuid;
 
 const PROFILES_FILE: &str = "profiles.json";
+const PROFILES_BACKUP_FILE: &str = "profiles.json.bak";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -43,8 +45,35 @@ pub struct Profile {
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
     #[serde(default)]
     pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Provider behind a custom endpoint, detected from the hostname unless the
+    /// user picked one
+    #[serde(default)]
+    pub provider: Option<ProviderKind>,
+    /// The user picked `provider`; otherwise it follows the endpoint's hostname
+    #[serde(default)]
+    pub provider_pinned: bool,
+    /// User overrides for the provider's default quirks
+    #[serde(default)]
+    pub quirk_overrides: Option<ProviderQuirks>,
+    /// PEM bundle of extra CA certificates trusted for this profile's endpoint,
+    /// for gateways and proxies that present a certificate from an internal CA
+    #[serde(default)]
+    pub ca_bundle_path: Option<String>,
+    /// When the profile was last made active
+    #[serde(default)]
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Send transfers for AWS buckets with acceleration enabled through the
+    /// S3 Transfer Acceleration endpoint
+    #[serde(default)]
+    pub use_transfer_acceleration: bool,
+    /// Longest a single list or search request may take before it is abandoned,
+    /// defaults to `DEFAULT_OPERATION_TIMEOUT_SECS`
+    #[serde(default)]
+    pub operation_timeout_secs: Option<u64>,
 }
 
+pub const DEFAULT_OPERATION_TIMEOUT_SECS: u64 = 60;
+
 impl Profile {
     pub fn new(name: String, credential_type: CredentialType, region: Option<String>) -> Self {
         let now = chrono::Utc::now();
@@ -56,6 +85,101 @@ impl Profile {
             is_default: false,
             created_at: Some(now),
             updated_at: Some(now),
+            provider: None,
+            provider_pinned: false,
+            quirk_overrides: None,
+            ca_bundle_path: None,
+            last_used_at: None,
+            use_transfer_acceleration: false,
+            operation_timeout_secs: None,
+        }
+    }
+
+    /// Quirks to apply when building a client for a custom endpoint profile.
+    /// Returns `None` for AWS credential types, which need no adjustments.
+    pub fn provider_quirks(&self) -> Option<ProviderQuirks> {
+        match &self.credential_type {
+            CredentialType::CustomEndpoint { .. } => {
+                Some(self.quirk_overrides.unwrap_or_else(|| self.provider_kind().quirks()))
+            }
+            _ => None,
+        }
+    }
+
+    /// How long one list or search request may run before it is abandoned
+    pub fn operation_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(
+            self.operation_timeout_secs
+                .filter(|secs| *secs > 0)
+                .unwrap_or(DEFAULT_OPERATION_TIMEOUT_SECS),
+        )
+    }
+
+    /// Endpoint URL of a custom endpoint profile
+    pub fn endpoint_url(&self) -> Option<&str> {
+        match &self.credential_type {
+            CredentialType::CustomEndpoint { endpoint_url, .. } => Some(endpoint_url),
+            _ => None,
+        }
+    }
+
+    /// The provider behind this profile; every non-custom-endpoint profile is AWS
+    pub fn provider_kind(&self) -> ProviderKind {
+        match &self.credential_type {
+            CredentialType::CustomEndpoint { endpoint_url, .. } => self
+                .provider
+                .unwrap_or_else(|| classify_host(&endpoint_host(endpoint_url))),
+            _ => ProviderKind::Aws,
+        }
+    }
+
+    /// Whether GetBucketLocation should be skipped in favor of the profile region
+    pub fn skips_bucket_location(&self) -> bool {
+        self.provider_quirks()
+            .map(|quirks| quirks.skip_bucket_location)
+            .unwrap_or(false)
+    }
+
+    /// The CA bundle to trust, if one is set; a cleared field may arrive as ""
+    pub fn ca_bundle(&self) -> Option<&str> {
+        self.ca_bundle_path
+            .as_deref()
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+    }
+
+    /// Store a blank CA bundle path as no bundle
+    fn normalize_ca_bundle(&mut self) {
+        self.ca_bundle_path = self.ca_bundle().map(str::to_string);
+    }
+
+    /// Work out whether the submitted provider is the user's choice. Edits send
+    /// back the whole profile, so only a provider that differs from the saved one
+    /// counts as newly picked; clearing it goes back to detection.
+    fn pin_provider_choice(&mut self, previous: Option<&Profile>) {
+        self.provider_pinned = match (self.provider, previous) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(provider), Some(previous)) => {
+                previous.provider != Some(provider) || previous.provider_pinned || self.provider_pinned
+            }
+        };
+    }
+
+    /// Detect the provider for custom endpoints from the hostname, so it follows
+    /// endpoint edits, unless the user picked one.
+    fn select_provider(&mut self) {
+        match &self.credential_type {
+            CredentialType::CustomEndpoint { endpoint_url, .. } => {
+                if !self.provider_pinned {
+                    self.provider = Some(classify_host(&endpoint_host(endpoint_url)));
+                }
+            }
+            _ => {
+                self.provider = None;
+                self.provider_pinned = false;
+                self.quirk_overrides = None;
+            }
         }
     }
 }
@@ -67,10 +191,23 @@ struct ProfilesData {
     active_profile_id: Option<String>,
 }
 
+/// Outcome of verifying (and if needed repairing) profiles.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileRecoveryReport {
+    /// "healthy" if the file parsed cleanly, "recovered" if it had to be repaired
+    pub status: String,
+    pub backup_path: Option<String>,
+    pub recovered_profiles: Vec<String>,
+    pub lost_entries: Vec<String>,
+    pub error: Option<String>,
+}
+
 pub struct ProfileManager {
     config_dir: PathBuf,
     data: ProfilesData,
     keychain: super::KeychainStorage,
+    keychain_status: Option<super::KeychainStatus>,
+    recovery_report: Option<ProfileRecoveryReport>,
 }
 
 impl ProfileManager {
@@ -81,6 +218,7 @@ impl ProfileManager {
             profiles_path
         );
 
+        let mut recovery_report = None;
         let data = if profiles_path.exists() {
             log::info!("Found existing profiles file.");
             let content = std::fs::read_to_string(&profiles_path)?;
@@ -90,8 +228,10 @@ impl ProfileManager {
                     d
                 }
                 Err(e) => {
-                    log::error!("Failed to parse profiles.json: {}. Starting fresh.", e);
-                    ProfilesData::default()
+                    log::error!("Failed to parse profiles.json: {}. Attempting recovery.", e);
+                    let (data, report) = Self::repair_profiles_file(&config_dir, &content, e)?;
+                    recovery_report = Some(report);
+                    data
                 }
             }
         } else {
@@ -101,11 +241,206 @@ impl ProfileManager {
 
         let keychain = super::KeychainStorage::new("brows3", &config_dir, force_secret_fallback);
 
-        Ok(Self {
+        let manager = Self {
             config_dir,
             data,
             keychain,
-        })
+            keychain_status: None,
+            recovery_report,
+        };
+
+        if manager.recovery_report.is_some() {
+            manager.save()?;
+        }
+
+        Ok(manager)
+    }
+
+    /// Check that the OS keychain works and remember the result.
+    pub fn probe_keychain(&mut self) -> super::KeychainStatus {
+        let status = self.keychain.probe();
+        self.keychain_status = Some(status.clone());
+        status
+    }
+
+    /// Directory holding profiles.json and other app config files
+    pub fn config_dir(&self) -> &std::path::Path {
+        &self.config_dir
+    }
+
+    /// Result of the startup keychain probe, if it has run
+    pub fn keychain_status(&self) -> Option<super::KeychainStatus> {
+        self.keychain_status.clone()
+    }
+
+    /// Write a damaged profiles.json to profiles.json.bak, or to a timestamped
+    /// backup when an earlier one is already there, so no backup is overwritten
+    fn write_profiles_backup(config_dir: &std::path::Path, content: &str) -> Result<PathBuf> {
+        let mut backup_path = config_dir.join(PROFILES_BACKUP_FILE);
+        if backup_path.exists() {
+            let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%3f");
+            backup_path = config_dir.join(format!("profiles.json.{}.bak", stamp));
+        }
+        std::fs::write(&backup_path, content)?;
+        Ok(backup_path)
+    }
+
+    /// Back up a corrupt profiles.json and salvage whatever profile entries still parse.
+    fn repair_profiles_file(
+        config_dir: &std::path::Path,
+        content: &str,
+        error: serde_json::Error,
+    ) -> Result<(ProfilesData, ProfileRecoveryReport)> {
+        let backup_path = Self::write_profiles_backup(config_dir, content)?;
+        log::warn!("Backed up corrupt profiles file to {:?}", backup_path);
+
+        let (data, lost_entries) = Self::salvage_profiles_data(content);
+        let mut recovered_profiles: Vec<String> =
+            data.profiles.values().map(|p| p.name.clone()).collect();
+        recovered_profiles.sort();
+
+        log::warn!(
+            "Recovered {} profile(s) from corrupt profiles.json, {} entr(ies) lost",
+            recovered_profiles.len(),
+            lost_entries.len()
+        );
+
+        Ok((
+            data,
+            ProfileRecoveryReport {
+                status: "recovered".to_string(),
+                backup_path: Some(backup_path.to_string_lossy().into_owned()),
+                recovered_profiles,
+                lost_entries,
+                error: Some(error.to_string()),
+            },
+        ))
+    }
+
+    /// Best-effort recovery of individual profiles from a file that failed to parse.
+    /// Returns the salvaged data and a description of every entry that was dropped.
+    fn salvage_profiles_data(content: &str) -> (ProfilesData, Vec<String>) {
+        let mut profiles = HashMap::new();
+        let mut lost = Vec::new();
+        let mut active_profile_id = None;
+
+        if let Ok(value) = serde_json::from_str::<serde_json::Value>(content) {
+            let entries = match &value {
+                serde_json::Value::Object(map) if map.contains_key("profiles") => {
+                    active_profile_id = map
+                        .get("active_profile_id")
+                        .and_then(|id| id.as_str())
+                        .map(|id| id.to_string());
+                    map.get("profiles").cloned().unwrap_or_default()
+                }
+                other => other.clone(),
+            };
+
+            let labeled: Vec<(String, serde_json::Value)> = match entries {
+                serde_json::Value::Object(map) => map.into_iter().collect(),
+                serde_json::Value::Array(items) => items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, item)| (format!("#{}", index), item))
+                    .collect(),
+                other => {
+                    lost.push(format!("Unexpected profiles value: {}", other));
+                    Vec::new()
+                }
+            };
+
+            for (label, entry) in labeled {
+                let name = entry
+                    .get("name")
+                    .and_then(|name| name.as_str())
+                    .map(|name| name.to_string());
+                match serde_json::from_value::<Profile>(entry) {
+                    Ok(mut profile) => {
+                        if profile.id.is_empty() && !label.starts_with('#') {
+                            profile.id = label;
+                        }
+                        if profile.id.is_empty() {
+                            profile.id = Uuid::new_v4().to_string();
+                        }
+                        profiles.insert(profile.id.clone(), profile);
+                    }
+                    Err(e) => lost.push(format!("{}: {}", name.unwrap_or(label), e)),
+                }
+            }
+        } else {
+            // Not valid JSON at all (e.g. truncated write). Scan for any embedded
+            // objects that still deserialize as a complete profile.
+            let mut offset = 0;
+            while let Some(start) = content[offset..].find('{').map(|i| offset + i) {
+                let mut stream =
+                    serde_json::Deserializer::from_str(&content[start..]).into_iter::<Profile>();
+                match stream.next() {
+                    Some(Ok(profile)) if !profile.id.is_empty() => {
+                        offset = start + stream.byte_offset();
+                        profiles.insert(profile.id.clone(), profile);
+                    }
+                    _ => offset = start + 1,
+                }
+            }
+            lost.push("File is not valid JSON; any entries not listed as recovered were lost".to_string());
+        }
+
+        let data = Self::normalize_profiles_data(ProfilesData {
+            profiles,
+            active_profile_id,
+        });
+        (data, lost)
+    }
+
+    /// Verify profiles.json on disk and repair it if it can no longer be parsed.
+    /// If a repair already happened at startup, that report is returned instead.
+    pub fn recover_profiles(&mut self) -> Result<ProfileRecoveryReport> {
+        if let Some(report) = &self.recovery_report {
+            return Ok(report.clone());
+        }
+
+        let profiles_path = self.config_dir.join(PROFILES_FILE);
+        let content = if profiles_path.exists() {
+            std::fs::read_to_string(&profiles_path)?
+        } else {
+            String::new()
+        };
+
+        let parse_error = if content.is_empty() {
+            None
+        } else {
+            Self::load_profiles_data(&content).err()
+        };
+
+        let mut recovered_profiles: Vec<String> =
+            self.data.profiles.values().map(|p| p.name.clone()).collect();
+        recovered_profiles.sort();
+
+        match parse_error {
+            None => Ok(ProfileRecoveryReport {
+                status: "healthy".to_string(),
+                backup_path: None,
+                recovered_profiles,
+                lost_entries: Vec::new(),
+                error: None,
+            }),
+            Some(err) => {
+                // The in-memory profiles are authoritative; back up the damaged
+                // file and rewrite it from memory.
+                let backup_path = Self::write_profiles_backup(&self.config_dir, &content)?;
+                self.save()?;
+
+                let report = ProfileRecoveryReport {
+                    status: "recovered".to_string(),
+                    backup_path: Some(backup_path.to_string_lossy().into_owned()),
+                    recovered_profiles,
+                    lost_entries: Vec::new(),
+                    error: Some(err.to_string()),
+                };
+                self.recovery_report = Some(report.clone());
+                Ok(report)
+            }
+        }
     }
 
     fn load_profiles_data(content: &str) -> std::result::Result<ProfilesData, serde_json::Error> {
@@ -238,6 +573,9 @@ impl ProfileManager {
 
         // Store secret in keychain for manual/custom endpoint credentials
         self.store_secret(&profile)?;
+        profile.pin_provider_choice(None);
+        profile.select_provider();
+        profile.normalize_ca_bundle();
 
         // Set timestamps
         let now = chrono::Utc::now();
@@ -285,8 +623,12 @@ impl ProfileManager {
 
         profile.id = id.to_string();
         profile.created_at = existing_profile.created_at;
+        profile.last_used_at = existing_profile.last_used_at;
         profile.is_default = self.data.active_profile_id.as_deref() == Some(id);
         profile.updated_at = Some(chrono::Utc::now());
+        profile.pin_provider_choice(Some(&existing_profile));
+        profile.select_provider();
+        profile.normalize_ca_bundle();
 
         // Keep previous secret if the edit payload omitted it.
         match (
@@ -384,9 +726,12 @@ impl ProfileManager {
     }
 
     pub async fn set_active_profile(&mut self, id: &str) -> Result<()> {
-        if !self.data.profiles.contains_key(id) {
-            return Err(AppError::ProfileNotFound(id.to_string()));
-        }
+        let profile = self
+            .data
+            .profiles
+            .get_mut(id)
+            .ok_or_else(|| AppError::ProfileNotFound(id.to_string()))?;
+        profile.last_used_at = Some(chrono::Utc::now());
 
         self.data.active_profile_id = Some(id.to_string());
         self.sync_default_flags();
@@ -394,6 +739,39 @@ impl ProfileManager {
         Ok(())
     }
 
+    /// Change only a profile's region, leaving credentials and other settings untouched
+    pub async fn set_profile_region(&mut self, id: &str, region: Option<String>) -> Result<Profile> {
+        let profile = self
+            .data
+            .profiles
+            .get_mut(id)
+            .ok_or_else(|| AppError::ProfileNotFound(id.to_string()))?;
+
+        profile.region = region
+            .map(|region| region.trim().to_string())
+            .filter(|region| !region.is_empty());
+        profile.updated_at = Some(chrono::Utc::now());
+        let profile = profile.clone();
+
+        self.save()?;
+        Ok(self.hydrate_profile(profile))
+    }
+
+    pub async fn set_transfer_acceleration(&mut self, id: &str, enabled: bool) -> Result<Profile> {
+        let profile = self
+            .data
+            .profiles
+            .get_mut(id)
+            .ok_or_else(|| AppError::ProfileNotFound(id.to_string()))?;
+
+        profile.use_transfer_acceleration = enabled;
+        profile.updated_at = Some(chrono::Utc::now());
+        let profile = profile.clone();
+
+        self.save()?;
+        Ok(self.hydrate_profile(profile))
+    }
+
     pub async fn get_active_profile(&self) -> Result<Option<Profile>> {
         match &self.data.active_profile_id {
             Some(id) => {
@@ -470,6 +848,7 @@ impl ProfileManager {
 #[cfg(test)]
 mod tests {
     use super::{CredentialType, Profile, ProfileManager};
+    use crate::s3::provider::ProviderKind;
     use std::collections::HashMap;
     use std::path::PathBuf;
 
@@ -593,6 +972,13 @@ mod tests {
                 is_default: false,
                 created_at: None,
                 updated_at: None,
+                provider: None,
+                provider_pinned: false,
+                quirk_overrides: None,
+                ca_bundle_path: None,
+                last_used_at: None,
+                use_transfer_acceleration: false,
+                operation_timeout_secs: None,
             },
         );
 
@@ -611,6 +997,112 @@ mod tests {
         assert!(profile.is_default);
     }
 
+    #[test]
+    fn salvage_keeps_valid_entries_and_reports_invalid_ones() {
+        let json = r#"{
+            "profiles": {
+                "good": {
+                    "id": "good",
+                    "name": "Good",
+                    "credential_type": { "type": "Environment" },
+                    "region": "us-east-1",
+                    "is_default": true
+                },
+                "bad": {
+                    "id": "bad",
+                    "name": "Bad",
+                    "credential_type": { "type": "Nonsense" },
+                    "region": null,
+                    "is_default": false
+                }
+            },
+            "active_profile_id": "good"
+        }"#;
+
+        assert!(ProfileManager::load_profiles_data(json).is_err());
+        let (data, lost) = ProfileManager::salvage_profiles_data(json);
+        assert_eq!(data.profiles.len(), 1);
+        assert!(data.profiles.contains_key("good"));
+        assert_eq!(data.active_profile_id.as_deref(), Some("good"));
+        assert_eq!(lost.len(), 1);
+        assert!(lost[0].starts_with("Bad:"));
+    }
+
+    #[test]
+    fn corrupt_file_is_backed_up_and_partially_recovered() {
+        let config_dir = temp_config_dir("corrupt-profiles");
+        let truncated = r#"{"profiles":{"p1":{"id":"p1","name":"Kept","credential_type":{"type":"Environment"},"region":null,"is_default":true},"p2":{"id":"p2","name":"Tru"#;
+        std::fs::write(config_dir.join("profiles.json"), truncated).unwrap();
+
+        let mut manager =
+            ProfileManager::new(config_dir.clone(), true).expect("manager should initialize");
+        let report = manager.recover_profiles().expect("report should be available");
+
+        assert_eq!(report.status, "recovered");
+        assert_eq!(report.recovered_profiles, vec!["Kept".to_string()]);
+        assert_eq!(
+            std::fs::read_to_string(config_dir.join("profiles.json.bak")).unwrap(),
+            truncated
+        );
+        let rewritten = std::fs::read_to_string(config_dir.join("profiles.json")).unwrap();
+        assert!(ProfileManager::load_profiles_data(&rewritten).is_ok());
+
+        // A later corruption gets its own backup instead of replacing the first
+        let second = ProfileManager::write_profiles_backup(&config_dir, "{broken").unwrap();
+        assert_ne!(second, config_dir.join("profiles.json.bak"));
+        assert_eq!(std::fs::read_to_string(second).unwrap(), "{broken");
+        assert_eq!(
+            std::fs::read_to_string(config_dir.join("profiles.json.bak")).unwrap(),
+            truncated
+        );
+    }
+
+    #[tokio::test]
+    async fn add_custom_endpoint_profile_selects_provider_from_hostname() {
+        let config_dir = temp_config_dir("select-provider");
+        let mut manager = ProfileManager::new(config_dir, true).expect("manager should initialize");
+
+        let created = manager
+            .add_profile(Profile::new(
+                "Linode".to_string(),
+                CredentialType::CustomEndpoint {
+                    endpoint_url: "us-east-1.linodeobjects.com".to_string(),
+                    access_key_id: "access".to_string(),
+                    secret_access_key: "secret".to_string(),
+                },
+                Some("us-east-1".to_string()),
+            ))
+            .await
+            .expect("profile should be added");
+
+        assert_eq!(created.provider, Some(ProviderKind::Linode));
+        assert!(created.skips_bucket_location());
+
+        // Moving the endpoint to another provider re-detects it
+        let mut edited = created.clone();
+        edited.credential_type = CredentialType::CustomEndpoint {
+            endpoint_url: "https://s3.wasabisys.com".to_string(),
+            access_key_id: "access".to_string(),
+            secret_access_key: String::new(),
+        };
+        let edited = manager.update_profile(&created.id, edited).await.unwrap();
+        assert_eq!(edited.provider, Some(ProviderKind::Wasabi));
+
+        // A provider the user picks sticks through later endpoint edits
+        let mut pinned = edited.clone();
+        pinned.provider = Some(ProviderKind::Minio);
+        let pinned = manager.update_profile(&created.id, pinned).await.unwrap();
+        let mut moved = pinned.clone();
+        moved.credential_type = CredentialType::CustomEndpoint {
+            endpoint_url: "us-east-1.linodeobjects.com".to_string(),
+            access_key_id: "access".to_string(),
+            secret_access_key: String::new(),
+        };
+        let moved = manager.update_profile(&created.id, moved).await.unwrap();
+        assert_eq!(moved.provider, Some(ProviderKind::Minio));
+        assert!(moved.provider_pinned);
+    }
+
     #[tokio::test]
     async fn update_preserves_secret_when_switching_manual_to_custom_endpoint() {
         let config_dir = temp_config_dir("manual-to-custom");
@@ -1,8 +1,8 @@
 pub mod keychain;
 pub mod manager;
 
-pub use keychain::KeychainStorage;
-pub use manager::{CredentialType, Profile, ProfileManager};
+pub use keychain::{KeychainStatus, KeychainStorage};
+pub use manager::{CredentialType, Profile, ProfileManager, ProfileRecoveryReport};
 
 use crate::error::Result;
 use std::path::PathBuf;
@@ -42,7 +42,14 @@ pub fn init<R: tauri::Runtime>(app: &AppHandle<R>) -> Result<()> {
     // Ensure config directory exists
     std::fs::create_dir_all(&config_dir)?;
 
-    let manager = ProfileManager::new(config_dir, force_secret_fallback)?;
+    let mut manager = ProfileManager::new(config_dir, force_secret_fallback)?;
+    let keychain_status = manager.probe_keychain();
+    if !keychain_status.native_available && !force_secret_fallback {
+        log::warn!(
+            "Secure storage unavailable: {}",
+            keychain_status.error.as_deref().unwrap_or("unknown error")
+        );
+    }
     let state = Arc::new(RwLock::new(manager));
 
     app.manage(state);
@@ -34,6 +34,19 @@ pub enum AppError {
 
     #[error("Invalid content: {0}")]
     InvalidContent(String),
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+
+    #[error(
+        "Not enough disk space: {} needed, {} available",
+        crate::s3::format_size(*needed),
+        crate::s3::format_size(*available)
+    )]
+    InsufficientSpace { needed: u64, available: u64 },
 }
 
 impl From<std::io::Error> for AppError {
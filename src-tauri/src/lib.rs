@@ -1,10 +1,15 @@
+pub mod activity;
 pub mod commands;
 pub mod credentials;
 pub mod error;
 pub mod s3;
+pub mod settings;
 pub mod transfer;
 
-use commands::{buckets, objects, operations, profiles, transfer as transfer_cmd};
+use commands::{
+    analytics, buckets, manifest, object_lock, objects, operations, profiles, settings as settings_cmd,
+    transfer as transfer_cmd,
+};
 use s3::S3ClientManager;
 use serde::Serialize;
 use std::sync::Arc;
@@ -53,12 +58,13 @@ pub fn run() {
     configure_linux_webkit_environment();
 
     tauri::Builder::default()
-        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_clipboard_manager::init())
         .manage(Arc::new(RwLock::new(S3ClientManager::new())))
         .manage(Arc::new(TransferManager::new()))
+        .manage(Arc::new(activity::ActivityRegistry::default()))
         .setup(|app| {
             // Add native menu on macOS to enable Copy/Paste/Cut/SelectAll/Undo/Redo shortcuts
             // Add native menu to enable standard shortcuts and window controls
@@ -185,6 +191,44 @@ pub fn run() {
             // Initialize credentials manager synchronously before any profile commands can run.
             credentials::init(&app.handle())?;
 
+            let profile_state = app.state::<commands::profiles::ProfileState>().inner().clone();
+            let config_dir = tauri::async_runtime::block_on(async {
+                profile_state.read().await.config_dir().to_path_buf()
+            });
+
+            // Load saved settings and apply them before any command reads them
+            let mut app_settings = settings::SettingsManager::new(config_dir.clone());
+            {
+                let s3_state = app.state::<s3::S3State>().inner().clone();
+                let transfer_state = app.state::<transfer_cmd::TransferState>().inner().clone();
+                let mut loaded = app_settings.settings().clone();
+                tauri::async_runtime::block_on(async {
+                    loaded.apply_to(&mut *s3_state.write().await, &transfer_state);
+                });
+                if &loaded != app_settings.settings() {
+                    if let Err(e) = app_settings.replace(loaded) {
+                        log::warn!("Could not save adjusted settings: {}", e);
+                    }
+                }
+            }
+            app.manage(Arc::new(RwLock::new(app_settings)));
+
+            // Listings of very large buckets are cached on disk instead of in memory
+            if let Ok(cache_dir) = app.path().app_cache_dir() {
+                let s3_state = app.state::<s3::S3State>().inner().clone();
+                tauri::async_runtime::block_on(async move {
+                    s3_state.write().await.set_disk_cache_dir(cache_dir.join("listings"));
+                });
+            }
+
+            // Pick up interrupted multipart uploads and abort the abandoned ones
+            tauri::async_runtime::spawn(transfer_cmd::clean_up_stale_uploads(
+                config_dir,
+                profile_state,
+                app.state::<s3::S3State>().inner().clone(),
+                app.state::<transfer_cmd::TransferState>().inner().clone(),
+            ));
+
             // Show the main window after initialization to prevent white flash
             if let Some(window) = app.get_webview_window("main") {
                 let _ = window.show();
@@ -201,42 +245,107 @@ pub fn run() {
             profiles::update_profile,
             profiles::delete_profile,
             profiles::set_active_profile,
+            profiles::set_profile_region,
+            profiles::set_transfer_acceleration,
             profiles::get_active_profile,
+            profiles::recover_profiles,
+            profiles::keychain_available,
             profiles::test_connection,
+            profiles::test_all_profiles,
+            profiles::measure_region_latency,
             profiles::discover_local_profiles,
             profiles::check_aws_environment,
+            profiles::detect_provider,
+            profiles::validate_endpoint,
             // Bucket commands
             buckets::list_buckets,
             buckets::list_buckets_with_regions,
+            buckets::list_buckets_by_region,
             buckets::get_bucket_region,
+            buckets::resolve_bucket_region,
             buckets::refresh_s3_client,
+            buckets::rebuild_clients,
+            buckets::check_transfer_acceleration,
+            buckets::check_public_access,
+            buckets::get_bucket_notification,
+            buckets::get_bucket_encryption,
+            buckets::put_bucket_encryption,
             // Object commands
             objects::list_objects,
+            objects::invalidate_object,
+            objects::list_recent_objects,
+            objects::list_objects_raw,
+            objects::list_top_level_prefixes,
+            objects::cache_bucket_listing,
             objects::search_objects,
             objects::get_presigned_url,
+            objects::presign_request,
+            objects::validate_presigned_url,
+            objects::generate_share_manifest,
             objects::get_object_content,
+            objects::read_object_window,
+            objects::sniff_content_type,
             objects::put_object_content,
             // File operations
             operations::put_object,
             operations::get_object,
+            operations::download_and_open,
             operations::delete_object,
             operations::copy_object,
             operations::move_object,
             operations::delete_objects,
+            operations::delete_prefix,
             operations::get_object_metadata,
+            operations::get_object_checksums,
+            analytics::compute_prefix_digest,
+            analytics::top_n_largest,
+            analytics::extension_breakdown,
+            analytics::find_duplicates,
+            analytics::export_inventory,
+            operations::list_active_operations,
+            operations::cancel_operation,
+            operations::copy_metadata,
+            manifest::apply_tags_from_manifest,
+            operations::set_storage_class_for_prefix,
+            object_lock::set_retention_for_prefix,
+            object_lock::clear_legal_hold_for_prefix,
+            operations::organize_by_date,
+            operations::batch_rename,
+            manifest::run_key_manifest,
             operations::get_object_permissions,
             operations::set_object_permissions,
+            operations::list_delete_markers,
+            operations::remove_delete_markers,
             // Transfer commands
             transfer_cmd::queue_upload,
+            transfer_cmd::import_url_to_s3,
             transfer_cmd::queue_download,
             transfer_cmd::list_transfers,
             transfer_cmd::queue_folder_upload,
             transfer_cmd::queue_folder_download,
+            transfer_cmd::compare_local_remote,
             transfer_cmd::cancel_transfer,
+            transfer_cmd::cancel_group,
+            transfer_cmd::rename_group,
             transfer_cmd::retry_transfer,
+            transfer_cmd::list_resumable_uploads,
+            transfer_cmd::resume_upload,
+            transfer_cmd::retry_all_failed,
             transfer_cmd::remove_transfer,
             transfer_cmd::clear_completed_transfers,
             transfer_cmd::set_transfer_concurrency,
+            transfer_cmd::set_free_space_check,
+            transfer_cmd::set_list_page_size,
+            transfer_cmd::set_region_redirect_retries,
+            transfer_cmd::set_disk_cache_threshold,
+            transfer_cmd::move_bucket_cache_to_disk,
+            transfer_cmd::get_metrics,
+            transfer_cmd::estimate_transfer,
+            transfer_cmd::get_price_tables,
+            transfer_cmd::set_price_table,
+            // Settings commands
+            settings_cmd::get_settings,
+            settings_cmd::update_settings,
             get_log_file_info,
         ])
         .run(tauri::generate_context!())
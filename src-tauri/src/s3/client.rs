@@ -1,11 +1,16 @@
 use crate::credentials::{CredentialType, Profile};
 use crate::error::{AppError, Result};
+use crate::s3::disk_cache::{DiskListing, DiskListingWriter};
+use crate::s3::metrics::{MetricsInterceptor, RequestMetrics, RequestMetricsSnapshot};
+use crate::s3::provider::apply_provider_quirks;
 use aws_config::Region;
-use aws_sdk_s3::config::{RequestChecksumCalculation, ResponseChecksumValidation};
 use aws_sdk_s3::Client;
 use serde::{Deserialize, Serialize};
 
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 /// Normalize an endpoint URL to ensure it has a scheme.
 /// Many S3-compatible providers (Linode, DigitalOcean, etc.) may be configured
@@ -19,10 +24,42 @@ pub(crate) fn normalize_endpoint_url(url: &str) -> String {
     }
 }
 
+/// Build an HTTPS client that trusts the certificates in a PEM bundle on top of
+/// the platform roots, so verification stays on for everything else.
+pub(crate) fn custom_ca_http_client(
+    ca_bundle_path: &str,
+) -> Result<aws_sdk_s3::config::SharedHttpClient> {
+    use aws_smithy_http_client::tls;
+
+    let pem = std::fs::read(ca_bundle_path).map_err(|e| {
+        AppError::ConfigError(format!("Failed to read CA bundle '{}': {}", ca_bundle_path, e))
+    })?;
+    if !String::from_utf8_lossy(&pem).contains("-----BEGIN CERTIFICATE-----") {
+        return Err(AppError::ConfigError(format!(
+            "CA bundle '{}' does not contain any PEM certificates",
+            ca_bundle_path
+        )));
+    }
+
+    let tls_context = tls::TlsContext::builder()
+        .with_trust_store(tls::TrustStore::default().with_pem_certificate(pem))
+        .build()
+        .map_err(|e| AppError::ConfigError(format!("Invalid CA bundle '{}': {}", ca_bundle_path, e)))?;
+
+    Ok(aws_smithy_http_client::Builder::new()
+        .tls_provider(tls::Provider::Rustls(tls::rustls_provider::CryptoMode::AwsLc))
+        .tls_context(tls_context)
+        .build_https())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct S3Object {
     pub key: String,
     pub last_modified: Option<String>,
+    /// `last_modified` as milliseconds since the Unix epoch, so the UI doesn't
+    /// have to parse the provider's date format
+    #[serde(default)]
+    pub last_modified_epoch_ms: Option<i64>,
     pub size: i64,
     pub storage_class: Option<String>,
 }
@@ -39,9 +76,218 @@ pub struct S3ClientManager {
     object_cache: HashMap<(String, String), Vec<S3Object>>, // (profile_id, bucket_name) -> objects
     folder_cache: HashMap<(String, String, String), FolderContent>, // (profile_id, bucket_name, prefix) -> children
     sorted_folder_cache: HashMap<(String, String, String, String, String), FolderContent>, // (profile_id, bucket_name, prefix, sort_field, sort_direction) -> ordered children
-    bucket_regions: HashMap<String, String>,                        // bucket_name -> region
+    bucket_regions: HashMap<(String, String), String>,              // (profile_id, bucket_name) -> region
+    accelerate_enabled: HashMap<(String, String), AccelerateStatus>, // (profile_id, bucket_name) -> acceleration status
+    disk_listings: HashMap<(String, String), DiskListing>,          // (profile_id, bucket_name) -> listing spilled to disk
+    disk_cache_dir: Option<PathBuf>,
+    disk_cache_threshold: usize,
+    list_page_size: i32,
+    redirect_policy: RegionRedirectPolicy,
+    metrics: Arc<RequestMetrics>,
 }
 
+/// A bucket's Transfer Acceleration setting as last read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccelerateStatus {
+    Enabled,
+    Disabled,
+    /// The profile isn't allowed to read the setting
+    Unknown,
+}
+
+/// How a client addresses buckets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Addressing {
+    /// `bucket.s3.region.amazonaws.com`, the AWS default
+    VirtualHost,
+    /// `s3.region.amazonaws.com/bucket`
+    PathStyle,
+    /// `bucket.s3-accelerate.amazonaws.com`
+    Accelerate,
+}
+
+impl Addressing {
+    fn cache_suffix(self) -> &'static str {
+        match self {
+            Addressing::VirtualHost => "",
+            Addressing::PathStyle => "#path",
+            Addressing::Accelerate => "#accelerate",
+        }
+    }
+}
+
+pub(crate) fn acceleration_disabled_error(profile: &Profile, bucket_name: &str) -> AppError {
+    AppError::ConfigError(format!(
+        "Transfer Acceleration is turned on for profile '{}' but not enabled on bucket '{}'. \
+         Enable it in the bucket's properties or turn it off for the profile.",
+        profile.name, bucket_name
+    ))
+}
+
+/// Pick the addressing style for an AWS bucket. Dotted names don't match the
+/// `*.s3.amazonaws.com` certificate over HTTPS and can't be accelerated, so they
+/// always go path-style; everything else is virtual-hosted, or accelerated on request.
+pub fn bucket_addressing(bucket_name: &str, accelerate: bool) -> Addressing {
+    if bucket_name.contains('.') {
+        Addressing::PathStyle
+    } else if accelerate {
+        Addressing::Accelerate
+    } else {
+        Addressing::VirtualHost
+    }
+}
+
+/// Group a bucket listing into the children of every folder, sorted by key
+fn folder_tree(objects: &[S3Object]) -> HashMap<String, FolderContent> {
+    // Build folder cache in a single pass O(N)
+    let mut folders: HashMap<String, FolderContent> = HashMap::new();
+    // Ensure root exists
+    folders.insert(
+        "".to_string(),
+        FolderContent {
+            objects: Vec::new(),
+            common_prefixes: Vec::new(),
+        },
+    );
+
+    // Track unique prefixes per folder to avoid O(M) contains check (M = parts)
+    let mut folder_prefixes: HashMap<String, std::collections::HashSet<String>> =
+        HashMap::new();
+
+    for obj in objects {
+        let key = &obj.key;
+
+        // Find the immediate parent prefix
+        let parent_prefix = if let Some(last_slash) = key.rfind('/') {
+            // If the key itself ends with /, the parent is the substring BEFORE that slash (if any)
+            if key.ends_with('/') {
+                let without_last = &key[..last_slash];
+                if let Some(prev_slash) = without_last.rfind('/') {
+                    &key[..prev_slash + 1]
+                } else {
+                    ""
+                }
+            } else {
+                &key[..last_slash + 1]
+            }
+        } else {
+            ""
+        };
+
+        // Add object to its parent folder (if it's not a folder placeholder)
+        if !key.ends_with('/') {
+            folders
+                .entry(parent_prefix.to_string())
+                .or_insert_with(|| FolderContent {
+                    objects: Vec::new(),
+                    common_prefixes: Vec::new(),
+                })
+                .objects
+                .push(obj.clone());
+        }
+
+        // Build the prefix tree up to the root
+        // We only need to iterate if there are slashes
+        if key.contains('/') {
+            let parts_vec: Vec<&str> = key.split('/').collect();
+            // If it's a folder "a/b/", parts are ["a", "b", ""]
+            // If it's a file "a/b/c.txt", parts are ["a", "b", "c.txt"]
+            let depth = if key.ends_with('/') {
+                parts_vec.len() - 2
+            } else {
+                parts_vec.len() - 1
+            };
+
+            let mut path = String::new();
+            for i in 0..depth {
+                let parent = path.clone();
+                path.push_str(parts_vec[i]);
+                path.push('/');
+
+                // Add this folder to parent's common_prefixes
+                let seen_prefixes = folder_prefixes.entry(parent.clone()).or_default();
+                if !seen_prefixes.contains(&path) {
+                    seen_prefixes.insert(path.clone());
+                    folders
+                        .entry(parent)
+                        .or_default()
+                        .common_prefixes
+                        .push(path.clone());
+                }
+            }
+        }
+    }
+
+    for content in folders.values_mut() {
+        content.objects.sort_by(|a, b| a.key.cmp(&b.key));
+        content.common_prefixes.sort();
+    }
+
+    folders
+}
+
+/// The folder a key is listed under: `a/b/` for both `a/b/c.txt` and `a/b/c/`
+fn parent_prefix(key: &str) -> &str {
+    let trimmed = key.strip_suffix('/').unwrap_or(key);
+    match trimmed.rfind('/') {
+        Some(idx) => &key[..idx + 1],
+        None => "",
+    }
+}
+
+/// Milliseconds since the Unix epoch for an SDK timestamp
+pub fn epoch_millis(date: &aws_sdk_s3::primitives::DateTime) -> Option<i64> {
+    date.to_millis().ok()
+}
+
+/// Await a network call for at most `timeout`. The SDK's own timeouts don't cover
+/// every way a misbehaving endpoint can hang, so commands that list or search wrap
+/// their requests in this to always return in bounded time.
+pub async fn with_operation_timeout<F: std::future::Future>(
+    timeout: std::time::Duration,
+    operation: &str,
+    future: F,
+) -> Result<F::Output> {
+    tokio::time::timeout(timeout, future).await.map_err(|_| {
+        AppError::Timeout(format!(
+            "{} got no response within {}s",
+            operation,
+            timeout.as_secs()
+        ))
+    })
+}
+
+/// Page size S3 itself defaults to; also the AWS maximum.
+pub const DEFAULT_LIST_PAGE_SIZE: i32 = 1000;
+/// Upper bound for providers that allow larger listing pages than AWS.
+pub const MAX_LIST_PAGE_SIZE: i32 = 10_000;
+/// Upper bound on how often a request is retried in a bucket's discovered region.
+pub const MAX_REGION_REDIRECT_ATTEMPTS: u32 = 5;
+/// Upper bound on the wait before each further region-redirect attempt.
+pub const MAX_REGION_REDIRECT_BACKOFF_MS: u64 = 10_000;
+
+/// How a request that failed in the wrong region is retried in the bucket's own
+/// region. The first retry goes out straight away; each later one waits
+/// `backoff_ms` times the number of retries already made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegionRedirectPolicy {
+    pub max_attempts: u32,
+    pub backoff_ms: u64,
+}
+
+impl Default for RegionRedirectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            backoff_ms: 250,
+        }
+    }
+}
+
+/// Bucket listings with at least this many objects are cached on disk rather
+/// than in memory.
+pub const DEFAULT_DISK_CACHE_THRESHOLD: usize = 250_000;
+
 impl S3ClientManager {
     pub fn new() -> Self {
         Self {
@@ -50,7 +296,64 @@ impl S3ClientManager {
             folder_cache: HashMap::new(),
             sorted_folder_cache: HashMap::new(),
             bucket_regions: HashMap::new(),
+            accelerate_enabled: HashMap::new(),
+            disk_listings: HashMap::new(),
+            disk_cache_dir: None,
+            disk_cache_threshold: DEFAULT_DISK_CACHE_THRESHOLD,
+            list_page_size: DEFAULT_LIST_PAGE_SIZE,
+            redirect_policy: RegionRedirectPolicy::default(),
+            metrics: Arc::new(RequestMetrics::default()),
+        }
+    }
+
+    /// Request and cache counters since startup
+    pub fn metrics(&self) -> RequestMetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// `max_keys` used by the recursive listers
+    pub fn list_page_size(&self) -> i32 {
+        self.list_page_size
+    }
+
+    /// Set the recursive listing page size, clamped to `1..=MAX_LIST_PAGE_SIZE`.
+    /// Returns the value actually applied.
+    pub fn set_list_page_size(&mut self, page_size: i32) -> i32 {
+        self.list_page_size = page_size.clamp(1, MAX_LIST_PAGE_SIZE);
+        self.list_page_size
+    }
+
+    pub fn redirect_policy(&self) -> RegionRedirectPolicy {
+        self.redirect_policy
+    }
+
+    /// Set the region-redirect retry policy, clamped to `1..=MAX_REGION_REDIRECT_ATTEMPTS`
+    /// attempts and at most `MAX_REGION_REDIRECT_BACKOFF_MS` between them. Returns
+    /// the policy actually applied.
+    pub fn set_redirect_policy(&mut self, max_attempts: u32, backoff_ms: u64) -> RegionRedirectPolicy {
+        self.redirect_policy = RegionRedirectPolicy {
+            max_attempts: max_attempts.clamp(1, MAX_REGION_REDIRECT_ATTEMPTS),
+            backoff_ms: backoff_ms.min(MAX_REGION_REDIRECT_BACKOFF_MS),
+        };
+        self.redirect_policy
+    }
+
+    /// Directory large bucket listings are written to. Listings left there by an
+    /// earlier run are stale and get deleted.
+    pub fn set_disk_cache_dir(&mut self, dir: PathBuf) {
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                log::warn!("Could not clear listing cache {}: {}", dir.display(), e);
+            }
         }
+        self.disk_cache_dir = Some(dir);
+    }
+
+    /// Object count from which bucket listings are cached on disk. Applies to
+    /// listings cached from now on.
+    pub fn set_disk_cache_threshold(&mut self, object_count: usize) -> usize {
+        self.disk_cache_threshold = object_count.max(1);
+        self.disk_cache_threshold
     }
 
     /// Get or create an S3 client for the given profile's default region
@@ -68,10 +371,85 @@ impl S3ClientManager {
         profile: &Profile,
         region: &str,
     ) -> Result<&Client> {
-        let key = (profile.id.clone(), region.to_string());
+        self.get_client_with_addressing(profile, region, Addressing::VirtualHost).await
+    }
+
+    /// Get or create a client suited to one bucket. For AWS profiles dotted bucket
+    /// names use path-style addressing, and when the profile asks for Transfer
+    /// Acceleration the bucket must have it enabled, otherwise this fails with an
+    /// error saying so. When the setting can't be read the regional endpoint is
+    /// used. Custom endpoints keep their provider's addressing.
+    ///
+    /// The bucket's acceleration setting must already be cached; use
+    /// [`client_for_bucket`] to read it without holding the manager's lock.
+    pub async fn get_client_for_bucket(
+        &mut self,
+        profile: &Profile,
+        bucket_name: &str,
+        region: &str,
+    ) -> Result<&Client> {
+        if profile.provider_quirks().is_some() {
+            return self.get_client_for_region(profile, region).await;
+        }
+
+        let mut addressing = bucket_addressing(bucket_name, profile.use_transfer_acceleration);
+        if profile.use_transfer_acceleration && addressing == Addressing::PathStyle {
+            log::warn!(
+                "Bucket '{}' has dots in its name and can't use Transfer Acceleration, using the regional endpoint",
+                bucket_name
+            );
+        }
+        if addressing == Addressing::Accelerate {
+            match self.accelerate_status(&profile.id, bucket_name) {
+                Some(AccelerateStatus::Enabled) => {}
+                Some(AccelerateStatus::Disabled) => return Err(acceleration_disabled_error(profile, bucket_name)),
+                Some(AccelerateStatus::Unknown) => {
+                    log::warn!(
+                        "Transfer Acceleration setting of bucket '{}' can't be read, using the regional endpoint",
+                        bucket_name
+                    );
+                    addressing = Addressing::VirtualHost;
+                }
+                None => {
+                    return Err(AppError::ConfigError(format!(
+                        "The Transfer Acceleration setting of bucket '{}' hasn't been read yet",
+                        bucket_name
+                    )))
+                }
+            }
+        }
+
+        self.get_client_with_addressing(profile, region, addressing).await
+    }
+
+    /// A bucket's cached Transfer Acceleration setting
+    pub fn accelerate_status(&self, profile_id: &str, bucket_name: &str) -> Option<AccelerateStatus> {
+        self.accelerate_enabled
+            .get(&(profile_id.to_string(), bucket_name.to_string()))
+            .copied()
+    }
+
+    pub fn set_accelerate_status(&mut self, profile_id: &str, bucket_name: &str, status: AccelerateStatus) {
+        self.accelerate_enabled
+            .insert((profile_id.to_string(), bucket_name.to_string()), status);
+    }
+
+    /// Forget a bucket's cached Transfer Acceleration setting so it is read again
+    pub fn forget_accelerate_status(&mut self, profile_id: &str, bucket_name: &str) {
+        self.accelerate_enabled
+            .remove(&(profile_id.to_string(), bucket_name.to_string()));
+    }
+
+    async fn get_client_with_addressing(
+        &mut self,
+        profile: &Profile,
+        region: &str,
+        addressing: Addressing,
+    ) -> Result<&Client> {
+        let key = (profile.id.clone(), format!("{}{}", region, addressing.cache_suffix()));
 
         if !self.clients.contains_key(&key) {
-            let client = self.build_client(profile, Some(region.to_string())).await?;
+            let client = self.build_client(profile, Some(region.to_string()), addressing).await?;
             self.clients.insert(key.clone(), client);
         }
 
@@ -83,6 +461,7 @@ impl S3ClientManager {
         &self,
         profile: &Profile,
         override_region: Option<String>,
+        addressing: Addressing,
     ) -> Result<Client> {
         let region_str = override_region
             .or_else(|| profile.region.clone())
@@ -146,11 +525,24 @@ impl S3ClientManager {
 
         if let CredentialType::CustomEndpoint { endpoint_url, .. } = &profile.credential_type {
             let normalized_url = normalize_endpoint_url(endpoint_url);
-            s3_config_builder = s3_config_builder
-                .endpoint_url(&normalized_url)
-                .force_path_style(true)
-                .request_checksum_calculation(RequestChecksumCalculation::WhenRequired)
-                .response_checksum_validation(ResponseChecksumValidation::WhenRequired);
+            s3_config_builder = s3_config_builder.endpoint_url(&normalized_url);
+        }
+
+        if let Some(quirks) = profile.provider_quirks() {
+            s3_config_builder = apply_provider_quirks(s3_config_builder, &quirks);
+        }
+
+        s3_config_builder = s3_config_builder.interceptor(MetricsInterceptor::new(self.metrics.clone()));
+        self.metrics.record_client_built();
+
+        s3_config_builder = match addressing {
+            Addressing::VirtualHost => s3_config_builder,
+            Addressing::PathStyle => s3_config_builder.force_path_style(true),
+            Addressing::Accelerate => s3_config_builder.accelerate(true),
+        };
+
+        if let Some(ca_bundle_path) = profile.ca_bundle() {
+            s3_config_builder = s3_config_builder.http_client(custom_ca_http_client(ca_bundle_path)?);
         }
 
         Ok(Client::from_conf(s3_config_builder.build()))
@@ -160,29 +552,72 @@ impl S3ClientManager {
     pub fn clear_cache(&mut self) {
         self.clients.clear();
         self.object_cache.clear();
+        for listing in self.disk_listings.values() {
+            listing.remove();
+        }
+        self.disk_listings.clear();
         self.folder_cache.clear();
         self.sorted_folder_cache.clear();
         self.bucket_regions.clear();
+        self.accelerate_enabled.clear();
     }
 
-    /// Get cached region for a bucket
-    pub fn get_bucket_region(&self, bucket_name: &str) -> Option<String> {
-        self.bucket_regions.get(bucket_name).cloned()
+    /// Drop every cached client so the next request rebuilds it with current
+    /// settings, keeping object listings and bucket regions. Returns how many
+    /// clients were dropped.
+    pub fn clear_clients(&mut self) -> usize {
+        let dropped = self.clients.len();
+        self.clients.clear();
+        dropped
     }
 
-    /// Cache the region for a bucket
-    pub fn set_bucket_region(&mut self, bucket_name: &str, region: String) {
-        self.bucket_regions.insert(bucket_name.to_string(), region);
+    /// Drop every cached client for a profile so the next request rebuilds it.
+    /// The profile's bucket regions are cleared too, since they may have been
+    /// derived from its old region.
+    pub fn clear_profile_clients(&mut self, profile_id: &str) {
+        self.clients.retain(|(p, _), _| p != profile_id);
+        self.bucket_regions.retain(|(p, _), _| p != profile_id);
+        self.accelerate_enabled.retain(|(p, _), _| p != profile_id);
     }
 
-    /// Cache the same region for a set of buckets.
-    pub fn set_bucket_regions<I>(&mut self, bucket_names: I, region: &str)
+    /// Drop every listing cached for a profile, in memory and on disk
+    pub fn clear_profile_listings(&mut self, profile_id: &str) {
+        self.object_cache.retain(|(p, _), _| p != profile_id);
+        self.folder_cache.retain(|(p, _, _), _| p != profile_id);
+        self.sorted_folder_cache.retain(|(p, _, _, _, _), _| p != profile_id);
+        self.disk_listings.retain(|(p, _), listing| {
+            if p == profile_id {
+                listing.remove();
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Get the cached region of a bucket as seen by a profile. Regions are kept
+    /// per profile because profiles for different accounts or endpoints can see
+    /// different buckets under the same name.
+    pub fn get_bucket_region(&self, profile_id: &str, bucket_name: &str) -> Option<String> {
+        self.bucket_regions
+            .get(&(profile_id.to_string(), bucket_name.to_string()))
+            .cloned()
+    }
+
+    /// Cache the region of a bucket as seen by a profile
+    pub fn set_bucket_region(&mut self, profile_id: &str, bucket_name: &str, region: String) {
+        self.bucket_regions
+            .insert((profile_id.to_string(), bucket_name.to_string()), region);
+    }
+
+    /// Cache the same region for a set of a profile's buckets.
+    pub fn set_bucket_regions<I>(&mut self, profile_id: &str, bucket_names: I, region: &str)
     where
         I: IntoIterator,
         I::Item: AsRef<str>,
     {
         for bucket_name in bucket_names {
-            self.set_bucket_region(bucket_name.as_ref(), region.to_string());
+            self.set_bucket_region(profile_id, bucket_name.as_ref(), region.to_string());
         }
     }
 
@@ -192,22 +627,29 @@ impl S3ClientManager {
         profile_id: &str,
         bucket_name: &str,
     ) -> Option<&Vec<S3Object>> {
-        self.object_cache
-            .get(&(profile_id.to_string(), bucket_name.to_string()))
+        let objects = self
+            .object_cache
+            .get(&(profile_id.to_string(), bucket_name.to_string()));
+        self.metrics.record_cache_lookup(objects.is_some());
+        objects
     }
 
-    /// Get cached folder content
+    /// Get cached folder content, read from disk for buckets cached there
     pub fn get_folder_content(
         &self,
         profile_id: &str,
         bucket_name: &str,
         prefix: &str,
-    ) -> Option<&FolderContent> {
-        self.folder_cache.get(&(
-            profile_id.to_string(),
-            bucket_name.to_string(),
-            prefix.to_string(),
-        ))
+    ) -> Option<Cow<'_, FolderContent>> {
+        let content = match self.disk_listings.get(&(profile_id.to_string(), bucket_name.to_string())) {
+            Some(listing) => listing.folder(prefix).map(Cow::Owned),
+            None => self
+                .folder_cache
+                .get(&(profile_id.to_string(), bucket_name.to_string(), prefix.to_string()))
+                .map(Cow::Borrowed),
+        };
+        self.metrics.record_cache_lookup(content.is_some());
+        content
     }
 
     pub fn get_sorted_folder_content(
@@ -218,13 +660,15 @@ impl S3ClientManager {
         sort_field: &str,
         sort_direction: &str,
     ) -> Option<&FolderContent> {
-        self.sorted_folder_cache.get(&(
+        let content = self.sorted_folder_cache.get(&(
             profile_id.to_string(),
             bucket_name.to_string(),
             prefix.to_string(),
             sort_field.to_string(),
             sort_direction.to_string(),
-        ))
+        ));
+        self.metrics.record_cache_lookup(content.is_some());
+        content
     }
 
     pub fn set_sorted_folder_content(
@@ -248,7 +692,8 @@ impl S3ClientManager {
         );
     }
 
-    /// Set cached objects for a bucket
+    /// Cache a bucket's listing in memory. Listings big enough for the disk cache
+    /// go through [`cache_bucket_objects`], which writes them as they're listed.
     pub fn set_cached_objects(
         &mut self,
         profile_id: &str,
@@ -257,90 +702,12 @@ impl S3ClientManager {
     ) {
         let profile_id_str = profile_id.to_string();
         let bucket_name_str = bucket_name.to_string();
+        let folders = folder_tree(&objects);
 
-        // Build folder cache in a single pass O(N)
-        let mut folders: HashMap<String, FolderContent> = HashMap::new();
-        // Ensure root exists
-        folders.insert(
-            "".to_string(),
-            FolderContent {
-                objects: Vec::new(),
-                common_prefixes: Vec::new(),
-            },
-        );
-
-        // Track unique prefixes per folder to avoid O(M) contains check (M = parts)
-        let mut folder_prefixes: HashMap<String, std::collections::HashSet<String>> =
-            HashMap::new();
-
-        for obj in &objects {
-            let key = &obj.key;
-
-            // Find the immediate parent prefix
-            let parent_prefix = if let Some(last_slash) = key.rfind('/') {
-                // If the key itself ends with /, the parent is the substring BEFORE that slash (if any)
-                if key.ends_with('/') {
-                    let without_last = &key[..last_slash];
-                    if let Some(prev_slash) = without_last.rfind('/') {
-                        &key[..prev_slash + 1]
-                    } else {
-                        ""
-                    }
-                } else {
-                    &key[..last_slash + 1]
-                }
-            } else {
-                ""
-            };
-
-            // Add object to its parent folder (if it's not a folder placeholder)
-            if !key.ends_with('/') {
-                folders
-                    .entry(parent_prefix.to_string())
-                    .or_insert_with(|| FolderContent {
-                        objects: Vec::new(),
-                        common_prefixes: Vec::new(),
-                    })
-                    .objects
-                    .push(obj.clone());
-            }
-
-            // Build the prefix tree up to the root
-            // We only need to iterate if there are slashes
-            if key.contains('/') {
-                let parts_vec: Vec<&str> = key.split('/').collect();
-                // If it's a folder "a/b/", parts are ["a", "b", ""]
-                // If it's a file "a/b/c.txt", parts are ["a", "b", "c.txt"]
-                let depth = if key.ends_with('/') {
-                    parts_vec.len() - 2
-                } else {
-                    parts_vec.len() - 1
-                };
-
-                let mut path = String::new();
-                for i in 0..depth {
-                    let parent = path.clone();
-                    path.push_str(parts_vec[i]);
-                    path.push('/');
-
-                    // Add this folder to parent's common_prefixes
-                    let seen_prefixes = folder_prefixes.entry(parent.clone()).or_default();
-                    if !seen_prefixes.contains(&path) {
-                        seen_prefixes.insert(path.clone());
-                        folders
-                            .entry(parent)
-                            .or_default()
-                            .common_prefixes
-                            .push(path.clone());
-                    }
-                }
-            }
-        }
+        self.remove_disk_listing(profile_id, bucket_name);
 
         // Store in the manager's folder_cache
-        for (prefix, mut content) in folders {
-            content.objects.sort_by(|a, b| a.key.cmp(&b.key));
-            content.common_prefixes.sort();
+        for (prefix, content) in folders {
             self.folder_cache.insert(
                 (profile_id_str.clone(), bucket_name_str.clone(), prefix),
                 content,
@@ -351,10 +718,43 @@ impl S3ClientManager {
             .insert((profile_id_str, bucket_name_str), objects);
     }
 
+    /// Use a listing already written to disk for a bucket, dropping any copy in memory
+    pub fn set_disk_listing(&mut self, profile_id: &str, bucket_name: &str, listing: DiskListing) {
+        let profile_id_str = profile_id.to_string();
+        let bucket_name_str = bucket_name.to_string();
+        self.object_cache.remove(&(profile_id_str.clone(), bucket_name_str.clone()));
+        self.folder_cache
+            .retain(|(p, b, _), _| p != &profile_id_str || b != &bucket_name_str);
+        self.sorted_folder_cache
+            .retain(|(p, b, _, _, _), _| p != &profile_id_str || b != &bucket_name_str);
+        // The files were replaced in place, so the old handle is just dropped
+        self.disk_listings.insert((profile_id_str, bucket_name_str), listing);
+    }
+
+    /// Handle to a bucket's listing cached on disk, for reading it without the lock
+    pub fn disk_listing(&self, profile_id: &str, bucket_name: &str) -> Option<DiskListing> {
+        self.disk_listings
+            .get(&(profile_id.to_string(), bucket_name.to_string()))
+            .cloned()
+    }
+
     /// Check if a bucket is cached
     pub fn has_cache(&self, profile_id: &str, bucket_name: &str) -> bool {
-        self.object_cache
-            .contains_key(&(profile_id.to_string(), bucket_name.to_string()))
+        let cache_key = (profile_id.to_string(), bucket_name.to_string());
+        self.object_cache.contains_key(&cache_key) || self.disk_listings.contains_key(&cache_key)
+    }
+
+    fn remove_disk_listing(&mut self, profile_id: &str, bucket_name: &str) -> bool {
+        match self
+            .disk_listings
+            .remove(&(profile_id.to_string(), bucket_name.to_string()))
+        {
+            Some(listing) => {
+                listing.remove();
+                true
+            }
+            None => false,
+        }
     }
 
     /// Get a single object from cache by key
@@ -364,6 +764,12 @@ impl S3ClientManager {
         bucket_name: &str,
         key: &str,
     ) -> Option<S3Object> {
+        if let Some(listing) = self
+            .disk_listings
+            .get(&(profile_id.to_string(), bucket_name.to_string()))
+        {
+            return listing.object(key);
+        }
         if let Some(objects) = self
             .object_cache
             .get(&(profile_id.to_string(), bucket_name.to_string()))
@@ -373,11 +779,141 @@ impl S3ClientManager {
         None
     }
 
+    /// Drop one object from the cached listing of a bucket, leaving the rest of
+    /// the bucket cached. Folders left with nothing in them disappear from their
+    /// parent, as they would in a fresh listing. Returns whether the key was cached.
+    /// Listings cached on disk aren't edited in place; the whole listing is dropped.
+    /// Sorted folder views holding the key are dropped even when the bucket itself
+    /// isn't cached, since they are filled from live listings.
+    pub fn invalidate_object(&mut self, profile_id: &str, bucket_name: &str, key: &str) -> bool {
+        self.drop_sorted_ancestors(profile_id, bucket_name, key);
+        if self.remove_disk_listing(profile_id, bucket_name) {
+            return true;
+        }
+        let cache_key = (profile_id.to_string(), bucket_name.to_string());
+        let Some(objects) = self.object_cache.get_mut(&cache_key) else {
+            return false;
+        };
+        let before = objects.len();
+        objects.retain(|obj| obj.key != key);
+        let removed = objects.len() != before;
+
+        let mut folder = parent_prefix(key).to_string();
+        if !key.ends_with('/') {
+            if let Some(content) = self.folder_cache.get_mut(&(
+                profile_id.to_string(),
+                bucket_name.to_string(),
+                folder.clone(),
+            )) {
+                content.objects.retain(|obj| obj.key != key);
+            }
+        }
+        self.drop_sorted_folder(profile_id, bucket_name, &folder);
+
+        // Walk up while folders are left empty, unless a marker object still keeps them
+        let mut emptied = if key.ends_with('/') { key.to_string() } else { String::new() };
+        loop {
+            if !emptied.is_empty() {
+                let still_listed = self.folder_cache
+                    .get(&(profile_id.to_string(), bucket_name.to_string(), emptied.clone()))
+                    .map(|content| !content.objects.is_empty() || !content.common_prefixes.is_empty())
+                    .unwrap_or(false)
+                    || self.object_cache[&cache_key].iter().any(|obj| obj.key == emptied);
+                if still_listed {
+                    break;
+                }
+                self.folder_cache
+                    .remove(&(profile_id.to_string(), bucket_name.to_string(), emptied.clone()));
+                if let Some(content) = self.folder_cache.get_mut(&(
+                    profile_id.to_string(),
+                    bucket_name.to_string(),
+                    folder.clone(),
+                )) {
+                    content.common_prefixes.retain(|prefix| prefix != &emptied);
+                }
+                self.drop_sorted_folder(profile_id, bucket_name, &folder);
+            }
+            if folder.is_empty() {
+                break;
+            }
+            emptied = folder.clone();
+            folder = parent_prefix(&folder).to_string();
+        }
+
+        removed
+    }
+
+    /// Put a fresh copy of one object into the cached listing of a bucket, adding
+    /// any folders it implies. Sorted folder views that would show it are dropped;
+    /// otherwise does nothing when the bucket isn't cached, and drops listings
+    /// cached on disk.
+    pub fn upsert_cached_object(&mut self, profile_id: &str, bucket_name: &str, object: S3Object) {
+        self.drop_sorted_ancestors(profile_id, bucket_name, &object.key);
+        if self.remove_disk_listing(profile_id, bucket_name) {
+            return;
+        }
+        let cache_key = (profile_id.to_string(), bucket_name.to_string());
+        let Some(objects) = self.object_cache.get_mut(&cache_key) else {
+            return;
+        };
+        objects.retain(|obj| obj.key != object.key);
+        objects.push(object.clone());
+
+        let mut folder = parent_prefix(&object.key).to_string();
+        if !object.key.ends_with('/') {
+            let content = self
+                .folder_cache
+                .entry((profile_id.to_string(), bucket_name.to_string(), folder.clone()))
+                .or_default();
+            content.objects.retain(|obj| obj.key != object.key);
+            let idx = content.objects.partition_point(|obj| obj.key < object.key);
+            content.objects.insert(idx, object.clone());
+        }
+        self.drop_sorted_folder(profile_id, bucket_name, &folder);
+
+        let mut child = if object.key.ends_with('/') { object.key.clone() } else { folder.clone() };
+        if child == folder {
+            if folder.is_empty() {
+                return;
+            }
+            folder = parent_prefix(&folder).to_string();
+        }
+        loop {
+            let content = self
+                .folder_cache
+                .entry((profile_id.to_string(), bucket_name.to_string(), folder.clone()))
+                .or_default();
+            if let Err(idx) = content.common_prefixes.binary_search(&child) {
+                content.common_prefixes.insert(idx, child.clone());
+                self.drop_sorted_folder(profile_id, bucket_name, &folder);
+            }
+            if folder.is_empty() {
+                break;
+            }
+            child = folder.clone();
+            folder = parent_prefix(&folder).to_string();
+        }
+    }
+
+    fn drop_sorted_folder(&mut self, profile_id: &str, bucket_name: &str, prefix: &str) {
+        self.sorted_folder_cache
+            .retain(|(p, b, pre, _, _), _| p != profile_id || b != bucket_name || pre != prefix);
+    }
+
+    /// Drop the sorted views of every folder above `key`, any of which may list it
+    /// or one of its parent folders
+    fn drop_sorted_ancestors(&mut self, profile_id: &str, bucket_name: &str, key: &str) {
+        self.sorted_folder_cache.retain(|(p, b, pre, _, _), _| {
+            p != profile_id || b != bucket_name || pre.len() >= key.len() || !key.starts_with(pre.as_str())
+        });
+    }
+
     /// Remove cache for a specific bucket
     pub fn remove_bucket_cache(&mut self, profile_id: &str, bucket_name: &str) {
         // Remove object list
         self.object_cache
             .remove(&(profile_id.to_string(), bucket_name.to_string()));
+        self.remove_disk_listing(profile_id, bucket_name);
 
         // Remove all folder entries for this bucket
         // Since folder_cache keys are (profile, bucket, prefix), we need to retain others
@@ -392,7 +928,7 @@ impl S3ClientManager {
             .retain(|(p, b, _), _| p != &pid || b != &bname);
         self.sorted_folder_cache
             .retain(|(p, b, _, _, _), _| p != &pid || b != &bname);
-        self.bucket_regions.remove(&bname);
+        self.bucket_regions.remove(&(pid, bname));
     }
 }
 
@@ -480,52 +1016,528 @@ pub async fn get_bucket_region(client: &Client, bucket_name: &str) -> Result<Str
     Ok(region)
 }
 
-/// List all objects in a bucket recursively
-pub async fn list_all_objects_recursive(client: &Client, bucket: &str) -> Result<Vec<S3Object>> {
+/// Pull the bucket's real region out of a failed request, if the provider said so.
+///
+/// A region mismatch comes back as `301 PermanentRedirect` (or a 400 with
+/// `AuthorizationHeaderMalformed`) carrying the region in the `x-amz-bucket-region`
+/// header, a `<Region>` element, or the `<Endpoint>` to use instead. Using that
+/// directly avoids a GetBucketLocation round trip, which some providers reject.
+pub fn region_from_redirect<E>(
+    err: &aws_sdk_s3::error::SdkError<E, aws_sdk_s3::config::http::HttpResponse>,
+) -> Option<String> {
+    let response = err.raw_response()?;
+
+    if let Some(region) = response.headers().get("x-amz-bucket-region") {
+        let region = region.trim();
+        if !region.is_empty() {
+            return Some(region.to_string());
+        }
+    }
+
+    let body = std::str::from_utf8(response.body().bytes()?).ok()?;
+    if let Some(region) = xml_element(body, "Region") {
+        return Some(region.to_string());
+    }
+
+    xml_element(body, "Endpoint").and_then(region_from_endpoint_host)
+}
+
+/// Whether a request failed because it went to the wrong region, so repeating it
+/// in the bucket's own region could succeed. Other failures (access denied,
+/// missing keys, throttling) would fail the same way anywhere.
+pub fn is_region_mismatch<E: aws_sdk_s3::error::ProvideErrorMetadata>(
+    err: &aws_sdk_s3::error::SdkError<E, aws_sdk_s3::config::http::HttpResponse>,
+) -> bool {
+    use aws_sdk_s3::error::ProvideErrorMetadata;
+
+    if region_from_redirect(err).is_some() {
+        return true;
+    }
+    if matches!(err.code(), Some("PermanentRedirect") | Some("AuthorizationHeaderMalformed")) {
+        return true;
+    }
+    // HEAD responses have no body to carry a code
+    err.raw_response().is_some_and(|response| response.status().as_u16() == 301)
+}
+
+fn xml_element<'a>(body: &'a str, name: &str) -> Option<&'a str> {
+    let open = format!("<{}>", name);
+    let close = format!("</{}>", name);
+    let start = body.find(&open)? + open.len();
+    let end = start + body[start..].find(&close)?;
+    let value = body[start..end].trim();
+    (!value.is_empty()).then_some(value)
+}
+
+/// Region encoded in an AWS S3 endpoint host such as `bucket.s3.eu-west-1.amazonaws.com`
+/// or the legacy `bucket.s3-eu-west-1.amazonaws.com`. The global endpoint maps to us-east-1.
+pub(crate) fn region_from_endpoint_host(host: &str) -> Option<String> {
+    let host = host.trim().trim_end_matches('.').to_ascii_lowercase();
+    let host = host
+        .split_once("://")
+        .map(|(_, rest)| rest.to_string())
+        .unwrap_or(host);
+    let host = host.split(['/', ':']).next()?;
+    let labels: Vec<&str> = host.strip_suffix(".amazonaws.com")?.split('.').collect();
+
+    let s3_index = labels
+        .iter()
+        .rposition(|label| *label == "s3" || label.starts_with("s3-"))?;
+    let s3_label = labels[s3_index];
+
+    if let Some(region) = s3_label.strip_prefix("s3-") {
+        // s3-external-1 is the legacy name of the us-east-1 endpoint.
+        let region = if region == "external-1" { "us-east-1" } else { region };
+        return Some(region.to_string());
+    }
+
+    let rest = &labels[s3_index + 1..];
+    let region = match rest {
+        [] => return Some("us-east-1".to_string()),
+        ["dualstack", region, ..] => region,
+        [region, ..] => region,
+    };
+    Some(region.to_string())
+}
+
+/// Resolve a bucket's region with HeadBucket, falling back to GetBucketLocation
+/// only for AWS profiles.
+///
+/// Many S3-compatible providers don't implement GetBucketLocation but do report
+/// the region in HeadBucket's `x-amz-bucket-region` header, including on the
+/// 301/403 responses returned when the request went to the wrong region.
+pub async fn resolve_bucket_region(
+    client: &Client,
+    profile: &Profile,
+    bucket_name: &str,
+) -> Result<String> {
+    let result = tokio::time::timeout(
+        std::time::Duration::from_secs(10),
+        client.head_bucket().bucket(bucket_name).send(),
+    )
+    .await;
+
+    let head_error = match result {
+        Ok(Ok(output)) => match output.bucket_region() {
+            Some(region) if !region.is_empty() => return Ok(region.to_string()),
+            _ => format!("HeadBucket for '{}' returned no region", bucket_name),
+        },
+        Ok(Err(e)) => {
+            if let Some(region) = region_from_redirect(&e) {
+                return Ok(region);
+            }
+            format!("HeadBucket failed for '{}': {}", bucket_name, e)
+        }
+        Err(_) => format!("HeadBucket timed out for '{}'", bucket_name),
+    };
+
+    if profile.provider_quirks().is_some() {
+        return Err(AppError::S3Error(head_error));
+    }
+
+    log::debug!("{}, falling back to GetBucketLocation", head_error);
+    get_bucket_region(client, bucket_name).await
+}
+
+/// Work out a bucket's region after a failed request: the redirect hint when the
+/// error carried one, otherwise HeadBucket (and GetBucketLocation on AWS).
+pub async fn discover_bucket_region(
+    client: &Client,
+    profile: &Profile,
+    bucket_name: &str,
+    redirect_region: Option<String>,
+) -> Option<String> {
+    if let Some(region) = redirect_region {
+        log::info!("Using redirect region '{}' for bucket '{}'", region, bucket_name);
+        return Some(region);
+    }
+    resolve_bucket_region(client, profile, bucket_name).await.ok()
+}
+
+/// Read a bucket's Transfer Acceleration setting from S3. Many policies allow
+/// uploads but not s3:GetAccelerateConfiguration, so a denied read is `Unknown`
+/// rather than an error.
+pub async fn read_accelerate_status(client: &Client, bucket_name: &str) -> Result<AccelerateStatus> {
+    use aws_sdk_s3::error::ProvideErrorMetadata;
+
+    match client
+        .get_bucket_accelerate_configuration()
+        .bucket(bucket_name)
+        .send()
+        .await
+    {
+        Ok(output) => Ok(
+            if output.status() == Some(&aws_sdk_s3::types::BucketAccelerateStatus::Enabled) {
+                AccelerateStatus::Enabled
+            } else {
+                AccelerateStatus::Disabled
+            },
+        ),
+        Err(e) if e.code() == Some("AccessDenied") => {
+            log::warn!(
+                "Not allowed to read the Transfer Acceleration setting of bucket '{}': {}",
+                bucket_name,
+                e.into_service_error()
+            );
+            Ok(AccelerateStatus::Unknown)
+        }
+        Err(e) => Err(AppError::S3Error(format!(
+            "Couldn't read the Transfer Acceleration setting of bucket '{}': {}",
+            bucket_name,
+            e.into_service_error()
+        ))),
+    }
+}
+
+/// Read and cache a bucket's Transfer Acceleration setting if the profile would
+/// accelerate it and it isn't cached yet. The manager's lock is released during
+/// the request, so a slow endpoint doesn't hold up every other command.
+pub async fn load_accelerate_status(
+    s3_state: &crate::s3::S3State,
+    profile: &Profile,
+    bucket_name: &str,
+    region: &str,
+) -> Result<()> {
+    if profile.provider_quirks().is_some()
+        || bucket_addressing(bucket_name, profile.use_transfer_acceleration) != Addressing::Accelerate
+    {
+        return Ok(());
+    }
+
+    let client = {
+        let mut s3_manager = s3_state.write().await;
+        if s3_manager.accelerate_status(&profile.id, bucket_name).is_some() {
+            return Ok(());
+        }
+        s3_manager.get_client_for_region(profile, region).await?.clone()
+    };
+    let status = read_accelerate_status(&client, bucket_name).await?;
+    s3_state
+        .write()
+        .await
+        .set_accelerate_status(&profile.id, bucket_name, status);
+    Ok(())
+}
+
+/// [`S3ClientManager::get_client_for_bucket`], reading the bucket's Transfer
+/// Acceleration setting first if needed
+pub async fn client_for_bucket(
+    s3_state: &crate::s3::S3State,
+    profile: &Profile,
+    bucket_name: &str,
+    region: &str,
+) -> Result<Client> {
+    load_accelerate_status(s3_state, profile, bucket_name, region).await?;
+    Ok(s3_state
+        .write()
+        .await
+        .get_client_for_bucket(profile, bucket_name, region)
+        .await?
+        .clone())
+}
+
+/// Start a disk listing with the objects listed so far, on a blocking thread.
+/// The objects are handed back when it can't be written, so they can stay in memory.
+async fn start_disk_listing(
+    dir: PathBuf,
+    profile_id: &str,
+    bucket_name: &str,
+    objects: Vec<S3Object>,
+) -> std::result::Result<DiskListingWriter, (std::io::Error, Vec<S3Object>)> {
+    let (profile_id, bucket_name) = (profile_id.to_string(), bucket_name.to_string());
+    let started = tokio::task::spawn_blocking(move || {
+        let mut writer = match DiskListingWriter::create(&dir, &profile_id, &bucket_name) {
+            Ok(writer) => writer,
+            Err(e) => return Err((e, objects)),
+        };
+        match writer.append(&objects) {
+            Ok(()) => Ok(writer),
+            Err(e) => {
+                writer.discard();
+                Err((e, objects))
+            }
+        }
+    })
+    .await;
+    started.unwrap_or_else(|e| Err((std::io::Error::other(e), Vec::new())))
+}
+
+/// Add a page to a disk listing on a blocking thread, discarding the listing if it fails
+async fn append_to_disk_listing(
+    mut writer: DiskListingWriter,
+    objects: Vec<S3Object>,
+) -> std::io::Result<DiskListingWriter> {
+    let appended = tokio::task::spawn_blocking(move || match writer.append(&objects) {
+        Ok(()) => Ok(writer),
+        Err(e) => {
+            writer.discard();
+            Err(e)
+        }
+    })
+    .await;
+    appended.unwrap_or_else(|e| Err(std::io::Error::other(e)))
+}
+
+async fn finish_disk_listing(writer: DiskListingWriter) -> std::io::Result<DiskListing> {
+    tokio::task::spawn_blocking(move || writer.finish())
+        .await
+        .unwrap_or_else(|e| Err(std::io::Error::other(e)))
+}
+
+/// One page of a whole-bucket listing, with the token for the next one if there is one
+async fn list_bucket_page(
+    client: &Client,
+    bucket: &str,
+    page_size: i32,
+    timeout: std::time::Duration,
+    token: Option<String>,
+) -> Result<(Vec<S3Object>, Option<String>)> {
+    let response = with_operation_timeout(
+        timeout,
+        "ListObjectsV2",
+        client
+            .list_objects_v2()
+            .bucket(bucket)
+            .max_keys(page_size)
+            .set_continuation_token(token)
+            .send(),
+    )
+    .await?
+    .map_err(|e| AppError::S3Error(e.to_string()))?;
+
+    let objects = response
+        .contents()
+        .iter()
+        .map(|obj| S3Object {
+            key: obj.key().unwrap_or_default().to_string(),
+            last_modified: obj
+                .last_modified()
+                .map(|d: &aws_sdk_s3::primitives::DateTime| d.to_string()),
+            last_modified_epoch_ms: obj.last_modified().and_then(epoch_millis),
+            size: obj.size().unwrap_or_default(),
+            storage_class: obj
+                .storage_class()
+                .map(|s: &aws_sdk_s3::types::ObjectStorageClass| s.as_str().to_string()),
+        })
+        .collect();
+    let next_token = response
+        .next_continuation_token()
+        .filter(|_| response.is_truncated().unwrap_or(false))
+        .map(str::to_string);
+    Ok((objects, next_token))
+}
+
+/// List a whole bucket into the cache and return how many objects it holds.
+/// There is no object limit: pages are collected in memory until they reach the
+/// disk cache threshold, after which they and every later page are written
+/// straight to a listing on disk, without holding the manager's lock. Each page
+/// request is bounded by `timeout`, and the walk stops between pages once
+/// `cancelled` is set.
+pub async fn cache_bucket_objects(
+    s3_state: &crate::s3::S3State,
+    client: &Client,
+    profile_id: &str,
+    bucket_name: &str,
+    page_size: i32,
+    timeout: std::time::Duration,
+    cancelled: &std::sync::atomic::AtomicBool,
+) -> Result<usize> {
+    let (mut disk_dir, threshold) = {
+        let s3_manager = s3_state.read().await;
+        (s3_manager.disk_cache_dir.clone(), s3_manager.disk_cache_threshold)
+    };
     let mut objects = Vec::new();
+    let mut disk_listing: Option<DiskListingWriter> = None;
     let mut token = None;
 
     loop {
-        let mut builder = client.list_objects_v2().bucket(bucket);
-        if let Some(t) = token {
-            builder = builder.continuation_token(t);
+        let listed = if cancelled.load(std::sync::atomic::Ordering::Relaxed) {
+            Err(AppError::Cancelled(format!("Listing of '{}'", bucket_name)))
+        } else {
+            list_bucket_page(client, bucket_name, page_size, timeout, token.take()).await
+        };
+        let (page, next_token) = match listed {
+            Ok(listed) => listed,
+            Err(e) => {
+                if let Some(writer) = disk_listing {
+                    let _ = tokio::task::spawn_blocking(move || writer.discard()).await;
+                }
+                return Err(e);
+            }
+        };
+
+        if let Some(writer) = disk_listing.take() {
+            let writer = append_to_disk_listing(writer, page)
+                .await
+                .map_err(|e| AppError::IoError(format!("Could not cache '{}' on disk: {}", bucket_name, e)))?;
+            disk_listing = Some(writer);
+        } else {
+            objects.extend(page);
+            if let Some(dir) = disk_dir.clone().filter(|_| objects.len() >= threshold) {
+                match start_disk_listing(dir, profile_id, bucket_name, std::mem::take(&mut objects)).await {
+                    Ok(writer) => disk_listing = Some(writer),
+                    Err((e, kept)) => {
+                        log::warn!("Could not cache '{}' on disk, keeping it in memory: {}", bucket_name, e);
+                        objects = kept;
+                        disk_dir = None;
+                    }
+                }
+            }
         }
 
-        let response = builder
-            .send()
-            .await
-            .map_err(|e| AppError::S3Error(e.to_string()))?;
-
-        for obj in response.contents() {
-            objects.push(S3Object {
-                key: obj.key().unwrap_or_default().to_string(),
-                last_modified: obj
-                    .last_modified()
-                    .map(|d: &aws_sdk_s3::primitives::DateTime| d.to_string()),
-                size: obj.size().unwrap_or_default(),
-                storage_class: obj
-                    .storage_class()
-                    .map(|s: &aws_sdk_s3::types::ObjectStorageClass| s.as_str().to_string()),
-            });
-        }
-
-        // SAFEGUARD: Don't load more than 100k objects into memory
-        if objects.len() >= 100_000 {
+        match next_token {
+            Some(next_token) => token = Some(next_token),
+            None => break,
+        }
+    }
+
+    let Some(writer) = disk_listing else {
+        let count = objects.len();
+        s3_state.write().await.set_cached_objects(profile_id, bucket_name, objects);
+        return Ok(count);
+    };
+    let listing = finish_disk_listing(writer)
+        .await
+        .map_err(|e| AppError::IoError(format!("Could not cache '{}' on disk: {}", bucket_name, e)))?;
+    let count = listing.object_count();
+    log::info!("Cached {} objects of '{}' on disk", count, bucket_name);
+    s3_state.write().await.set_disk_listing(profile_id, bucket_name, listing);
+    Ok(count)
+}
+
+/// Move a bucket's in-memory listing to disk regardless of its size. Returns
+/// false when the bucket isn't cached in memory, no cache directory is set or
+/// the files couldn't be written.
+pub async fn move_cache_to_disk(s3_state: &crate::s3::S3State, profile_id: &str, bucket_name: &str) -> bool {
+    let (dir, objects) = {
+        let s3_manager = s3_state.read().await;
+        let Some(dir) = s3_manager.disk_cache_dir.clone() else {
+            return false;
+        };
+        let Some(objects) = s3_manager
+            .object_cache
+            .get(&(profile_id.to_string(), bucket_name.to_string()))
+        else {
+            return false;
+        };
+        (dir, objects.clone())
+    };
+
+    let written = match start_disk_listing(dir, profile_id, bucket_name, objects).await {
+        Ok(writer) => finish_disk_listing(writer).await,
+        Err((e, _)) => Err(e),
+    };
+    match written {
+        Ok(listing) => {
+            log::info!("Moved {} cached objects of '{}' to disk", listing.object_count(), bucket_name);
+            s3_state.write().await.set_disk_listing(profile_id, bucket_name, listing);
+            true
+        }
+        Err(e) => {
+            log::warn!("Could not move the cached listing of '{}' to disk: {}", bucket_name, e);
+            false
+        }
+    }
+}
+
+/// Outcome of the last request made by [`retry_in_bucket_region`]
+pub struct RegionRetry<T, E> {
+    pub result: std::result::Result<T, E>,
+    /// Client for the bucket's region, for follow-up requests
+    pub client: Client,
+    pub region: String,
+}
+
+/// Retry a request that failed because it went to the wrong region. The bucket's
+/// region is taken from `redirect_region` when the failure carried one, otherwise
+/// discovered, and cached; the request is then repeated against a client for the
+/// bucket in that region, up to the manager's [`RegionRedirectPolicy`] number of times with a
+/// growing pause in between, so a flaky provider doesn't turn one transient error
+/// into a hard failure.
+///
+/// Returns `None` when the region couldn't be discovered, leaving the caller to
+/// report its original error, and otherwise the last attempt's result.
+pub async fn retry_in_bucket_region<T, E, F, Fut>(
+    s3_state: &crate::s3::S3State,
+    profile: &Profile,
+    bucket_name: &str,
+    redirect_region: Option<String>,
+    mut request: F,
+) -> Result<Option<RegionRetry<T, E>>>
+where
+    F: FnMut(Client) -> Fut,
+    Fut: std::future::Future<Output = std::result::Result<T, E>>,
+    E: std::fmt::Display,
+{
+    let policy = s3_state.read().await.redirect_policy();
+    let mut redirect_region = redirect_region;
+    let mut region: Option<String> = None;
+    let mut last = None;
+
+    for attempt in 1..=policy.max_attempts {
+        if attempt > 1 {
+            let pause = policy.backoff_ms.saturating_mul(u64::from(attempt - 1));
+            tokio::time::sleep(std::time::Duration::from_millis(pause)).await;
+        }
+
+        let target_region = match &region {
+            Some(region) => region.clone(),
+            None => {
+                let discovery_client = s3_state.write().await.get_client(profile).await?.clone();
+                let Some(discovered) =
+                    discover_bucket_region(&discovery_client, profile, bucket_name, redirect_region.take()).await
+                else {
+                    log::warn!(
+                        "Region discovery for bucket '{}' failed (attempt {}/{})",
+                        bucket_name,
+                        attempt,
+                        policy.max_attempts
+                    );
+                    continue;
+                };
+                s3_state.write().await.set_bucket_region(&profile.id, bucket_name, discovered.clone());
+                region = Some(discovered.clone());
+                discovered
+            }
+        };
+
+        let client = client_for_bucket(s3_state, profile, bucket_name, &target_region).await?;
+        let result = request(client.clone()).await;
+        if let Err(e) = &result {
             log::warn!(
-                "Bucket {} is too large. Truncating listing at 100,000 objects to prevent OOM.",
-                bucket
+                "Request to bucket '{}' in region '{}' failed (attempt {}/{}): {}",
+                bucket_name,
+                target_region,
+                attempt,
+                policy.max_attempts,
+                e
             );
-            break;
         }
-
-        if response.is_truncated().unwrap_or(false) {
-            token = response.next_continuation_token().map(|t| t.to_string());
-        } else {
+        let succeeded = result.is_ok();
+        last = Some(RegionRetry {
+            result,
+            client,
+            region: target_region,
+        });
+        if succeeded {
             break;
         }
     }
 
-    Ok(objects)
+    Ok(last)
+}
+
+/// Build a CopyObject `x-amz-copy-source` value. Each key segment is percent-encoded
+/// on its own so `/` separators survive; encoding the whole key turns them into
+/// `%2F`, which several S3-compatible providers don't decode. Spaces become `%20`
+/// rather than `+`, and `+`, `#`, `?` and non-ASCII characters are escaped.
+pub fn encode_copy_source(bucket: &str, key: &str) -> String {
+    let encoded_key = key
+        .split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/");
+    format!("{}/{}", bucket, encoded_key)
 }
 
 /// Format bytes to human-readable size
@@ -550,7 +1562,7 @@ pub fn format_size(bytes: u64) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::normalize_endpoint_url;
+    use super::{bucket_addressing, encode_copy_source, normalize_endpoint_url, region_from_endpoint_host, Addressing};
 
     #[test]
     fn normalize_endpoint_url_preserves_existing_scheme() {
@@ -579,4 +1591,191 @@ mod tests {
             "https://us-east-1.linodeobjects.com"
         );
     }
+
+    #[test]
+    fn region_from_endpoint_host_handles_aws_host_styles() {
+        assert_eq!(
+            region_from_endpoint_host("bucket.s3.eu-west-1.amazonaws.com").as_deref(),
+            Some("eu-west-1")
+        );
+        assert_eq!(
+            region_from_endpoint_host("bucket.s3-ap-southeast-2.amazonaws.com").as_deref(),
+            Some("ap-southeast-2")
+        );
+        assert_eq!(
+            region_from_endpoint_host("my.dotted.bucket.s3.dualstack.us-west-2.amazonaws.com").as_deref(),
+            Some("us-west-2")
+        );
+        assert_eq!(
+            region_from_endpoint_host("bucket.s3.amazonaws.com").as_deref(),
+            Some("us-east-1")
+        );
+        assert_eq!(region_from_endpoint_host("minio.local:9000"), None);
+    }
+
+    #[test]
+    fn encode_copy_source_keeps_separators_and_escapes_special_characters() {
+        assert_eq!(
+            encode_copy_source("bucket", "a b+c#d/\u{e9}.txt"),
+            "bucket/a%20b%2Bc%23d/%C3%A9.txt"
+        );
+        assert_eq!(encode_copy_source("bucket", "dir/?x=1&y"), "bucket/dir/%3Fx%3D1%26y");
+        assert_eq!(encode_copy_source("bucket", "trailing/"), "bucket/trailing/");
+    }
+
+    #[tokio::test]
+    async fn presigned_urls_encode_special_characters_in_keys() {
+        use aws_sdk_s3::presigning::PresigningConfig;
+
+        let config = aws_sdk_s3::Config::builder()
+            .behavior_version_latest()
+            .region(aws_config::Region::new("us-east-1"))
+            .credentials_provider(aws_credential_types::Credentials::new("AKID", "SECRET", None, None, "test"))
+            .build();
+        let client = aws_sdk_s3::Client::from_conf(config);
+        let presigned = client
+            .get_object()
+            .bucket("bucket")
+            .key("a b+c#d/\u{e9}.txt")
+            .presigned(PresigningConfig::expires_in(std::time::Duration::from_secs(60)).unwrap())
+            .await
+            .unwrap();
+
+        assert!(presigned.uri().contains("/a%20b%2Bc%23d/%C3%A9.txt?"), "{}", presigned.uri());
+    }
+
+    #[test]
+    fn bucket_addressing_sends_dotted_buckets_path_style() {
+        assert_eq!(bucket_addressing("logs.example.com", false), Addressing::PathStyle);
+        assert_eq!(bucket_addressing("logs.example.com", true), Addressing::PathStyle);
+        assert_eq!(bucket_addressing("logs-example", false), Addressing::VirtualHost);
+        assert_eq!(bucket_addressing("logs-example", true), Addressing::Accelerate);
+    }
+
+    #[test]
+    fn invalidate_and_upsert_keep_folder_cache_in_step() {
+        let object = |key: &str| super::S3Object {
+            key: key.to_string(),
+            last_modified: None,
+            last_modified_epoch_ms: None,
+            size: 1,
+            storage_class: None,
+        };
+        let mut manager = super::S3ClientManager::new();
+        manager.set_cached_objects("p", "b", vec![object("a/b/c.txt"), object("top.txt")]);
+
+        assert!(manager.invalidate_object("p", "b", "a/b/c.txt"));
+        assert!(manager.get_folder_content("p", "b", "a/b/").is_none());
+        assert_eq!(manager.get_folder_content("p", "b", "").unwrap().common_prefixes, Vec::<String>::new());
+
+        manager.upsert_cached_object("p", "b", object("a/b/d.txt"));
+        assert_eq!(manager.get_folder_content("p", "b", "").unwrap().common_prefixes, vec!["a/"]);
+        assert_eq!(manager.get_folder_content("p", "b", "a/").unwrap().common_prefixes, vec!["a/b/"]);
+        assert_eq!(manager.get_folder_content("p", "b", "a/b/").unwrap().objects[0].key, "a/b/d.txt");
+    }
+
+    #[tokio::test]
+    async fn listings_past_the_threshold_are_cached_on_disk() {
+        use crate::s3::test_server::{query_param, TestS3};
+
+        let server = TestS3::start(|_, target| {
+            let body = match query_param(target, "continuation-token") {
+                None => "<ListBucketResult><Name>b</Name><IsTruncated>true</IsTruncated>\
+                         <NextContinuationToken>page-2</NextContinuationToken>\
+                         <Contents><Key>a/one.txt</Key><Size>1</Size></Contents>\
+                         <Contents><Key>a/two.txt</Key><Size>2</Size></Contents></ListBucketResult>",
+                Some(_) => "<ListBucketResult><Name>b</Name><IsTruncated>false</IsTruncated>\
+                            <Contents><Key>top.txt</Key><Size>3</Size></Contents></ListBucketResult>",
+            };
+            (200, body.to_string())
+        })
+        .await;
+
+        let mut manager = super::S3ClientManager::new();
+        manager.set_disk_cache_dir(std::env::temp_dir().join(format!("brows3-listing-cache-{}", uuid::Uuid::new_v4())));
+        manager.set_disk_cache_threshold(2);
+        let s3_state: crate::s3::S3State = std::sync::Arc::new(tokio::sync::RwLock::new(manager));
+        // The first page reaches the threshold, so the second goes straight to disk
+        let cached = super::cache_bucket_objects(
+            &s3_state,
+            &server.client(),
+            "p",
+            "b",
+            2,
+            std::time::Duration::from_secs(5),
+            &std::sync::atomic::AtomicBool::new(false),
+        )
+        .await
+        .unwrap();
+        assert_eq!(cached, 3);
+
+        let manager = s3_state.read().await;
+        let listing = manager.disk_listing("p", "b").expect("listing should be on disk");
+        assert!(manager.get_cached_objects("p", "b").is_none());
+        assert_eq!(listing.object_count(), 3);
+        let root = manager.get_folder_content("p", "b", "").unwrap();
+        assert_eq!(root.common_prefixes, vec!["a/"]);
+        assert_eq!(root.objects[0].key, "top.txt");
+        assert_eq!(manager.get_folder_content("p", "b", "a/").unwrap().objects.len(), 2);
+        listing.remove();
+    }
+
+    #[tokio::test]
+    async fn invalidated_objects_drop_out_of_the_next_listing() {
+        use crate::s3::test_server::TestS3;
+        use std::sync::{Arc, Mutex};
+
+        let keys = Arc::new(Mutex::new(vec!["logs/a.txt", "logs/b.txt"]));
+        let listed = keys.clone();
+        let server = TestS3::start(move |_, _| {
+            let contents: String = listed
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|key| format!("<Contents><Key>{}</Key><Size>1</Size></Contents>", key))
+                .collect();
+            (200, format!("<ListBucketResult><Name>b</Name><IsTruncated>false</IsTruncated>{}</ListBucketResult>", contents))
+        })
+        .await;
+        let (client, not_cancelled) = (server.client(), std::sync::atomic::AtomicBool::new(false));
+        let timeout = std::time::Duration::from_secs(5);
+
+        let s3_state: crate::s3::S3State = Arc::new(tokio::sync::RwLock::new(super::S3ClientManager::new()));
+        super::cache_bucket_objects(&s3_state, &client, "p", "b", 1000, timeout, &not_cancelled).await.unwrap();
+        let folder_keys = |manager: &super::S3ClientManager| -> Vec<String> {
+            let content = manager.get_folder_content("p", "b", "logs/").unwrap();
+            content.objects.iter().map(|obj| obj.key.clone()).collect()
+        };
+        assert_eq!(folder_keys(&*s3_state.read().await), ["logs/a.txt", "logs/b.txt"]);
+
+        keys.lock().unwrap().retain(|key| *key != "logs/a.txt");
+        let mut manager = s3_state.write().await;
+        manager.set_sorted_folder_content("p", "b", "logs/", "size", "desc", super::FolderContent::default());
+        assert!(manager.invalidate_object("p", "b", "logs/a.txt"));
+        assert!(manager.get_sorted_folder_content("p", "b", "logs/", "size", "desc").is_none());
+        let (fresh, _) = super::list_bucket_page(&client, "b", 1000, timeout, None).await.unwrap();
+        let fresh: Vec<String> = fresh.into_iter().map(|obj| obj.key).collect();
+        assert_eq!(folder_keys(&*manager), fresh);
+
+        // Sorted views come from live listings, so they go even without a cached bucket
+        manager.remove_bucket_cache("p", "b");
+        manager.set_sorted_folder_content("p", "b", "", "date", "asc", super::FolderContent::default());
+        manager.invalidate_object("p", "b", "logs/b.txt");
+        assert!(manager.get_sorted_folder_content("p", "b", "", "date", "asc").is_none());
+    }
+
+    #[test]
+    fn bucket_regions_are_kept_per_profile() {
+        let mut manager = super::S3ClientManager::new();
+        manager.set_bucket_region("work", "backups", "eu-west-1".to_string());
+        manager.set_bucket_region("personal", "backups", "us-west-2".to_string());
+
+        assert_eq!(manager.get_bucket_region("work", "backups").as_deref(), Some("eu-west-1"));
+        assert_eq!(manager.get_bucket_region("personal", "backups").as_deref(), Some("us-west-2"));
+        assert!(manager.get_bucket_region("other", "backups").is_none());
+
+        manager.clear_profile_clients("work");
+        assert!(manager.get_bucket_region("work", "backups").is_none());
+        assert_eq!(manager.get_bucket_region("personal", "backups").as_deref(), Some("us-west-2"));
+    }
 }
@@ -0,0 +1,301 @@
+use crate::s3::client::{FolderContent, S3Object};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const OBJECTS_FILE: &str = "objects.jsonl";
+const FOLDERS_DIR: &str = "folders";
+
+fn hex_digest(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_bytes());
+        hasher.update([0u8]);
+    }
+    hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// A bucket listing kept on disk instead of in memory: one JSON file per folder
+/// for browsing, plus every object as a JSON line for key lookups and search.
+/// Clones refer to the same files.
+#[derive(Debug, Clone)]
+pub struct DiskListing {
+    dir: PathBuf,
+    object_count: usize,
+}
+
+impl DiskListing {
+    fn folder_path(&self, prefix: &str) -> PathBuf {
+        self.dir.join(FOLDERS_DIR).join(format!("{}.json", hex_digest(&[prefix])))
+    }
+
+    pub fn object_count(&self) -> usize {
+        self.object_count
+    }
+
+    /// Children of one folder, or `None` when the listing has no such folder
+    pub fn folder(&self, prefix: &str) -> Option<FolderContent> {
+        let file = File::open(self.folder_path(prefix)).ok()?;
+        match serde_json::from_reader(BufReader::new(file)) {
+            Ok(content) => Some(content),
+            Err(e) => {
+                log::warn!("Unreadable cached folder '{}' in {}: {}", prefix, self.dir.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Call `f` for every object under `prefix`, reading the listing a line at a time
+    pub fn for_each_object(&self, prefix: &str, mut f: impl FnMut(&S3Object)) -> std::io::Result<()> {
+        let reader = BufReader::new(File::open(self.dir.join(OBJECTS_FILE))?);
+        for line in reader.lines() {
+            let object: S3Object = serde_json::from_str(&line?)?;
+            if object.key.starts_with(prefix) {
+                f(&object);
+            }
+        }
+        Ok(())
+    }
+
+    /// Look up one object by key. Files are found through their folder; folder
+    /// markers aren't listed there, so those fall back to scanning every object.
+    pub fn object(&self, key: &str) -> Option<S3Object> {
+        if !key.ends_with('/') {
+            let parent = key.rfind('/').map(|idx| &key[..idx + 1]).unwrap_or("");
+            return self
+                .folder(parent)?
+                .objects
+                .into_iter()
+                .find(|object| object.key == key);
+        }
+
+        let mut found = None;
+        if let Err(e) = self.for_each_object(key, |object| {
+            if found.is_none() && object.key == key {
+                found = Some(object.clone());
+            }
+        }) {
+            log::warn!("Could not read cached listing in {}: {}", self.dir.display(), e);
+        }
+        found
+    }
+
+    pub fn remove(&self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.dir) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                log::warn!("Could not remove cached listing {}: {}", self.dir.display(), e);
+            }
+        }
+    }
+}
+
+/// Builds a [`DiskListing`] while the listing is still arriving, so a large
+/// bucket is never held in memory as a whole. Objects are appended a page at a
+/// time and each folder's file is written once the listing has moved past it,
+/// which, with keys listed in order, is when a key outside the folder turns up.
+/// Everything goes into a staging directory that replaces the bucket's listing
+/// on [`finish`](Self::finish).
+pub struct DiskListingWriter {
+    staging: DiskListing,
+    dir: PathBuf,
+    objects: BufWriter<File>,
+    /// Folders along the last key's path, outermost first, with their children so far
+    open: Vec<(String, FolderContent)>,
+    /// Folders already written, read back if a provider lists a key out of order
+    written: HashSet<String>,
+}
+
+impl DiskListingWriter {
+    pub fn create(root: &Path, profile_id: &str, bucket_name: &str) -> std::io::Result<Self> {
+        let dir = root.join(hex_digest(&[profile_id, bucket_name]));
+        let staging = DiskListing {
+            dir: dir.with_extension("partial"),
+            object_count: 0,
+        };
+        if staging.dir.exists() {
+            std::fs::remove_dir_all(&staging.dir)?;
+        }
+        std::fs::create_dir_all(staging.dir.join(FOLDERS_DIR))?;
+        let objects = match File::create(staging.dir.join(OBJECTS_FILE)) {
+            Ok(file) => BufWriter::new(file),
+            Err(e) => {
+                staging.remove();
+                return Err(e);
+            }
+        };
+
+        Ok(Self {
+            staging,
+            dir,
+            objects,
+            open: vec![(String::new(), FolderContent::default())],
+            written: HashSet::new(),
+        })
+    }
+
+    /// Add the next page of the listing
+    pub fn append(&mut self, objects: &[S3Object]) -> std::io::Result<()> {
+        for object in objects {
+            serde_json::to_writer(&mut self.objects, object)?;
+            self.objects.write_all(b"\n")?;
+            self.staging.object_count += 1;
+            self.add_to_folders(object)?;
+        }
+        Ok(())
+    }
+
+    /// File a key the way a listing held in memory groups it: a file under its
+    /// folder, and every folder above it under its parent. A folder marker only
+    /// adds the folders above it.
+    fn add_to_folders(&mut self, object: &S3Object) -> std::io::Result<()> {
+        let key = object.key.as_str();
+        let is_marker = key.ends_with('/');
+        let mut paths: Vec<&str> = key.match_indices('/').map(|(idx, _)| &key[..idx + 1]).collect();
+        if is_marker {
+            paths.pop();
+        }
+        // `open[depth]` is the folder at that depth of the key's path, the root being 0
+        let deepest = if is_marker { paths.len().saturating_sub(1) } else { paths.len() };
+
+        let still_open = self
+            .open
+            .iter()
+            .take_while(|(prefix, _)| key.starts_with(prefix.as_str()))
+            .count();
+        for folder in self.open.split_off(still_open) {
+            self.write_folder(folder)?;
+        }
+        for depth in self.open.len()..=deepest {
+            let prefix = paths[depth - 1].to_string();
+            let content = if self.written.contains(&prefix) {
+                self.staging.folder(&prefix).unwrap_or_default()
+            } else {
+                FolderContent::default()
+            };
+            self.open.push((prefix, content));
+        }
+
+        for (depth, path) in paths.iter().enumerate() {
+            let common_prefixes = &mut self.open[depth].1.common_prefixes;
+            if common_prefixes.last().map(String::as_str) != Some(*path) {
+                common_prefixes.push(path.to_string());
+            }
+        }
+        if !is_marker {
+            self.open[paths.len()].1.objects.push(object.clone());
+        }
+        Ok(())
+    }
+
+    fn write_folder(&mut self, (prefix, mut content): (String, FolderContent)) -> std::io::Result<()> {
+        content.objects.sort_by(|a, b| a.key.cmp(&b.key));
+        content.common_prefixes.sort();
+        content.common_prefixes.dedup();
+
+        let mut writer = BufWriter::new(File::create(self.staging.folder_path(&prefix))?);
+        serde_json::to_writer(&mut writer, &content)?;
+        writer.flush()?;
+        self.written.insert(prefix);
+        Ok(())
+    }
+
+    /// Write the folders still open and put the listing in place of any earlier
+    /// one for the same bucket
+    pub fn finish(mut self) -> std::io::Result<DiskListing> {
+        let mut written = self.objects.flush();
+        for folder in std::mem::take(&mut self.open) {
+            written = written.and_then(|_| self.write_folder(folder));
+        }
+
+        let Self { staging, dir, objects, .. } = self;
+        drop(objects);
+        let replaced = written.and_then(|_| {
+            if dir.exists() {
+                std::fs::remove_dir_all(&dir)?;
+            }
+            std::fs::rename(&staging.dir, &dir)
+        });
+        match replaced {
+            Ok(()) => Ok(DiskListing {
+                dir,
+                object_count: staging.object_count,
+            }),
+            Err(e) => {
+                staging.remove();
+                Err(e)
+            }
+        }
+    }
+
+    /// Drop a listing that won't be finished, with its files
+    pub fn discard(self) {
+        let Self { staging, objects, .. } = self;
+        drop(objects);
+        staging.remove();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DiskListingWriter;
+    use crate::s3::client::S3Object;
+
+    fn object(key: &str) -> S3Object {
+        S3Object {
+            key: key.to_string(),
+            last_modified: None,
+            last_modified_epoch_ms: None,
+            size: 1,
+            storage_class: None,
+        }
+    }
+
+    fn objects(keys: &[&str]) -> Vec<S3Object> {
+        keys.iter().map(|key| object(key)).collect()
+    }
+
+    #[test]
+    fn reads_back_folders_objects_and_markers() {
+        let root = std::env::temp_dir().join(format!("brows3-disk-cache-{}", std::process::id()));
+        let mut writer = DiskListingWriter::create(&root, "p", "b").unwrap();
+        writer.append(&objects(&["a/", "a/b.txt"])).unwrap();
+        writer.append(&objects(&["a/c/d.txt", "c.txt"])).unwrap();
+        let listing = writer.finish().unwrap();
+
+        assert_eq!(listing.object_count(), 4);
+        let root_folder = listing.folder("").unwrap();
+        assert_eq!(root_folder.common_prefixes, vec!["a/"]);
+        assert_eq!(root_folder.objects.len(), 1);
+        assert_eq!(listing.folder("a/").unwrap().common_prefixes, vec!["a/c/"]);
+        assert_eq!(listing.folder("a/c/").unwrap().objects[0].key, "a/c/d.txt");
+        assert!(listing.folder("missing/").is_none());
+        assert_eq!(listing.object("a/b.txt").unwrap().key, "a/b.txt");
+        assert_eq!(listing.object("a/").unwrap().key, "a/");
+
+        let mut under_a = Vec::new();
+        listing.for_each_object("a/", |object| under_a.push(object.key.clone())).unwrap();
+        assert_eq!(under_a, ["a/", "a/b.txt", "a/c/d.txt"]);
+
+        listing.remove();
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn keys_listed_out_of_order_keep_every_child() {
+        let root = std::env::temp_dir().join(format!("brows3-disk-cache-order-{}", std::process::id()));
+        let mut writer = DiskListingWriter::create(&root, "p", "b").unwrap();
+        writer.append(&objects(&["a/2.txt", "b/1.txt", "a/1.txt", "a/x/1.txt"])).unwrap();
+        let listing = writer.finish().unwrap();
+
+        let a = listing.folder("a/").unwrap();
+        let keys: Vec<_> = a.objects.iter().map(|object| object.key.as_str()).collect();
+        assert_eq!(keys, ["a/1.txt", "a/2.txt"]);
+        assert_eq!(a.common_prefixes, vec!["a/x/"]);
+        assert_eq!(listing.folder("").unwrap().common_prefixes, vec!["a/", "b/"]);
+
+        listing.remove();
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
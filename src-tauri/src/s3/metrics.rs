@@ -0,0 +1,229 @@
+use aws_sdk_s3::config::interceptors::{BeforeTransmitInterceptorContextRef, FinalizerInterceptorContextRef};
+use aws_sdk_s3::config::{ConfigBag, Intercept, RuntimeComponents};
+use aws_sdk_s3::error::BoxError;
+use serde::Serialize;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+
+/// Counters for S3 requests and listing cache lookups, shared by every client
+/// the `S3ClientManager` builds
+#[derive(Debug, Default)]
+pub struct RequestMetrics {
+    requests: AtomicU64,
+    request_errors: AtomicU64,
+    in_flight: AtomicI64,
+    /// Sum of end times minus sum of start times, in nanoseconds since `epoch()`.
+    /// Adding `in_flight * now` gives the total time spent in requests without
+    /// having to pair each response with its request.
+    latency_balance_nanos: AtomicI64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    clients_built: AtomicU64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestMetricsSnapshot {
+    pub requests: u64,
+    pub request_errors: u64,
+    pub in_flight: u64,
+    pub average_latency_ms: f64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub cache_hit_rate: f64,
+    pub clients_built: u64,
+}
+
+fn epoch() -> Instant {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    *EPOCH.get_or_init(Instant::now)
+}
+
+fn nanos_since_epoch() -> i64 {
+    epoch().elapsed().as_nanos() as i64
+}
+
+impl RequestMetrics {
+    pub fn record_cache_lookup(&self, hit: bool) {
+        if hit {
+            self.cache_hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.cache_misses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn record_client_built(&self) {
+        self.clients_built.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn request_started(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        self.latency_balance_nanos
+            .fetch_sub(nanos_since_epoch(), Ordering::AcqRel);
+    }
+
+    fn request_finished(&self, failed: bool) {
+        if failed {
+            self.request_errors.fetch_add(1, Ordering::Relaxed);
+        }
+        self.latency_balance_nanos
+            .fetch_add(nanos_since_epoch(), Ordering::AcqRel);
+        self.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+
+    pub fn snapshot(&self) -> RequestMetricsSnapshot {
+        let requests = self.requests.load(Ordering::Relaxed);
+        let in_flight = self.in_flight.load(Ordering::Acquire).max(0);
+        let total_nanos = self.latency_balance_nanos.load(Ordering::Acquire)
+            + in_flight * nanos_since_epoch();
+        let cache_hits = self.cache_hits.load(Ordering::Relaxed);
+        let cache_misses = self.cache_misses.load(Ordering::Relaxed);
+        let lookups = cache_hits + cache_misses;
+
+        RequestMetricsSnapshot {
+            requests,
+            request_errors: self.request_errors.load(Ordering::Relaxed),
+            in_flight: in_flight as u64,
+            average_latency_ms: if requests == 0 {
+                0.0
+            } else {
+                total_nanos.max(0) as f64 / requests as f64 / 1_000_000.0
+            },
+            cache_hits,
+            cache_misses,
+            cache_hit_rate: if lookups == 0 {
+                0.0
+            } else {
+                cache_hits as f64 / lookups as f64
+            },
+            clients_built: self.clients_built.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Counts every HTTP attempt a client makes, retries included
+#[derive(Debug)]
+pub struct MetricsInterceptor {
+    metrics: Arc<RequestMetrics>,
+}
+
+impl MetricsInterceptor {
+    pub fn new(metrics: Arc<RequestMetrics>) -> Self {
+        Self { metrics }
+    }
+}
+
+impl Intercept for MetricsInterceptor {
+    fn name(&self) -> &'static str {
+        "MetricsInterceptor"
+    }
+
+    fn read_before_transmit(
+        &self,
+        _context: &BeforeTransmitInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        _cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        self.metrics.request_started();
+        Ok(())
+    }
+
+    fn read_after_attempt(
+        &self,
+        context: &FinalizerInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        _cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        // Attempts that failed before reaching the wire were never counted as started
+        let Some(response) = context.response() else {
+            if context.request().is_none() {
+                self.metrics.request_finished(true);
+            }
+            return Ok(());
+        };
+        self.metrics
+            .request_finished(!response.status().is_success());
+        Ok(())
+    }
+}
+
+/// Counters kept by the `TransferManager`
+#[derive(Debug, Default)]
+pub struct TransferMetrics {
+    bytes_uploaded: AtomicU64,
+    bytes_downloaded: AtomicU64,
+    jobs_completed: AtomicU64,
+    jobs_failed: AtomicU64,
+    client_lock_wait_nanos: AtomicU64,
+    client_lock_acquisitions: AtomicU64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferMetricsSnapshot {
+    pub bytes_uploaded: u64,
+    pub bytes_downloaded: u64,
+    pub jobs_completed: u64,
+    pub jobs_failed: u64,
+    /// Average time a job waited for the shared client manager lock
+    pub average_client_lock_wait_ms: f64,
+}
+
+impl TransferMetrics {
+    pub fn record_completed(&self, upload: bool, bytes: u64) {
+        self.jobs_completed.fetch_add(1, Ordering::Relaxed);
+        if upload {
+            self.bytes_uploaded.fetch_add(bytes, Ordering::Relaxed);
+        } else {
+            self.bytes_downloaded.fetch_add(bytes, Ordering::Relaxed);
+        }
+    }
+
+    pub fn record_failed(&self) {
+        self.jobs_failed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_client_lock_wait(&self, started: Instant) {
+        self.client_lock_wait_nanos
+            .fetch_add(started.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        self.client_lock_acquisitions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> TransferMetricsSnapshot {
+        let acquisitions = self.client_lock_acquisitions.load(Ordering::Relaxed);
+        TransferMetricsSnapshot {
+            bytes_uploaded: self.bytes_uploaded.load(Ordering::Relaxed),
+            bytes_downloaded: self.bytes_downloaded.load(Ordering::Relaxed),
+            jobs_completed: self.jobs_completed.load(Ordering::Relaxed),
+            jobs_failed: self.jobs_failed.load(Ordering::Relaxed),
+            average_client_lock_wait_ms: if acquisitions == 0 {
+                0.0
+            } else {
+                self.client_lock_wait_nanos.load(Ordering::Relaxed) as f64
+                    / acquisitions as f64
+                    / 1_000_000.0
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RequestMetrics;
+
+    #[test]
+    fn latency_average_counts_finished_requests() {
+        let metrics = RequestMetrics::default();
+        metrics.request_started();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        metrics.request_finished(false);
+        metrics.record_cache_lookup(true);
+        metrics.record_cache_lookup(false);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.requests, 1);
+        assert_eq!(snapshot.in_flight, 0);
+        assert!(snapshot.average_latency_ms >= 5.0);
+        assert_eq!(snapshot.cache_hit_rate, 0.5);
+    }
+}
@@ -1,6 +1,12 @@
 pub mod client;
+pub mod disk_cache;
+pub mod metrics;
+pub mod pricing;
+pub mod provider;
+#[cfg(test)]
+pub(crate) mod test_server;
 
-pub use client::{S3ClientManager, BucketInfo, FolderContent, S3Object, list_buckets, get_bucket_region, format_size};
+pub use client::{S3ClientManager, BucketInfo, FolderContent, S3Object, list_buckets, get_bucket_region, resolve_bucket_region, discover_bucket_region, client_for_bucket, load_accelerate_status, region_from_redirect, is_region_mismatch, retry_in_bucket_region, RegionRedirectPolicy, RegionRetry, encode_copy_source, epoch_millis, format_size, with_operation_timeout};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
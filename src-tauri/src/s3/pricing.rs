@@ -0,0 +1,228 @@
+use crate::error::{AppError, Result};
+use crate::s3::provider::ProviderKind;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+const PRICING_FILE: &str = "pricing.json";
+const BYTES_PER_GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Prices used for transfer estimates. The defaults are public list prices for
+/// the cheapest tier and can be overridden per provider.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PriceTable {
+    /// GET/HEAD requests, per 1,000
+    pub get_per_1000: f64,
+    /// PUT/COPY/POST/LIST requests, per 1,000
+    pub put_per_1000: f64,
+    /// Data transferred out to the internet, per GB
+    pub egress_per_gb: f64,
+    /// Data transferred in, per GB
+    pub ingress_per_gb: f64,
+}
+
+impl PriceTable {
+    pub fn default_for(provider: ProviderKind) -> Self {
+        match provider {
+            ProviderKind::Aws => PriceTable {
+                get_per_1000: 0.0004,
+                put_per_1000: 0.005,
+                egress_per_gb: 0.09,
+                ingress_per_gb: 0.0,
+            },
+            ProviderKind::GcsInterop => PriceTable {
+                get_per_1000: 0.0004,
+                put_per_1000: 0.005,
+                egress_per_gb: 0.12,
+                ingress_per_gb: 0.0,
+            },
+            ProviderKind::CloudflareR2 => PriceTable {
+                get_per_1000: 0.00036,
+                put_per_1000: 0.0045,
+                egress_per_gb: 0.0,
+                ingress_per_gb: 0.0,
+            },
+            ProviderKind::BackblazeB2 => PriceTable {
+                get_per_1000: 0.0004,
+                put_per_1000: 0.0,
+                egress_per_gb: 0.01,
+                ingress_per_gb: 0.0,
+            },
+            ProviderKind::Linode => PriceTable {
+                get_per_1000: 0.0,
+                put_per_1000: 0.0,
+                egress_per_gb: 0.005,
+                ingress_per_gb: 0.0,
+            },
+            // Wasabi doesn't bill requests or egress; MinIO and unknown endpoints
+            // are usually self-hosted
+            ProviderKind::Wasabi | ProviderKind::Minio | ProviderKind::Unknown => PriceTable {
+                get_per_1000: 0.0,
+                put_per_1000: 0.0,
+                egress_per_gb: 0.0,
+                ingress_per_gb: 0.0,
+            },
+        }
+    }
+
+    /// Every price must be a finite, non-negative number
+    pub fn validate(&self) -> Result<()> {
+        let prices = [
+            ("get_per_1000", self.get_per_1000),
+            ("put_per_1000", self.put_per_1000),
+            ("egress_per_gb", self.egress_per_gb),
+            ("ingress_per_gb", self.ingress_per_gb),
+        ];
+        match prices.iter().find(|(_, price)| !price.is_finite() || *price < 0.0) {
+            Some((name, price)) => Err(AppError::InvalidContent(format!(
+                "{} must be a non-negative number, got {}",
+                name, price
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+const ALL_PROVIDERS: [ProviderKind; 8] = [
+    ProviderKind::Aws,
+    ProviderKind::Minio,
+    ProviderKind::Wasabi,
+    ProviderKind::BackblazeB2,
+    ProviderKind::CloudflareR2,
+    ProviderKind::Linode,
+    ProviderKind::GcsInterop,
+    ProviderKind::Unknown,
+];
+
+fn load_overrides(config_dir: &Path) -> HashMap<ProviderKind, PriceTable> {
+    let path = config_dir.join(PRICING_FILE);
+    match std::fs::read_to_string(&path) {
+        Ok(content) => parse_overrides(&content).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable {}: {}", path.display(), e);
+            HashMap::new()
+        }),
+        Err(_) => HashMap::new(),
+    }
+}
+
+/// Parse pricing.json entry by entry, so one unknown provider or bad price
+/// drops only that override
+fn parse_overrides(content: &str) -> Result<HashMap<ProviderKind, PriceTable>> {
+    let entries: HashMap<String, serde_json::Value> = serde_json::from_str(content)?;
+    let mut overrides = HashMap::new();
+    for (provider, table) in entries {
+        let parsed = serde_json::from_value::<ProviderKind>(serde_json::Value::String(provider.clone()))
+            .map_err(AppError::from)
+            .and_then(|kind| {
+                let table: PriceTable = serde_json::from_value(table)?;
+                table.validate()?;
+                Ok((kind, table))
+            });
+        match parsed {
+            Ok((kind, table)) => {
+                overrides.insert(kind, table);
+            }
+            Err(e) => log::warn!("Ignoring price override for '{}' in {}: {}", provider, PRICING_FILE, e),
+        }
+    }
+    Ok(overrides)
+}
+
+/// Effective price table for every provider, with user overrides applied
+pub fn load_price_tables(config_dir: &Path) -> HashMap<ProviderKind, PriceTable> {
+    let mut tables: HashMap<ProviderKind, PriceTable> = ALL_PROVIDERS
+        .iter()
+        .map(|provider| (*provider, PriceTable::default_for(*provider)))
+        .collect();
+    tables.extend(load_overrides(config_dir));
+    tables
+}
+
+pub fn price_table_for(config_dir: &Path, provider: ProviderKind) -> PriceTable {
+    load_overrides(config_dir)
+        .remove(&provider)
+        .unwrap_or_else(|| PriceTable::default_for(provider))
+}
+
+/// Store a provider's prices, or drop the override when `table` is `None`
+pub fn save_price_override(
+    config_dir: &Path,
+    provider: ProviderKind,
+    table: Option<PriceTable>,
+) -> Result<()> {
+    if let Some(table) = &table {
+        table.validate()?;
+    }
+    let mut overrides = load_overrides(config_dir);
+    match table {
+        Some(table) => overrides.insert(provider, table),
+        None => overrides.remove(&provider),
+    };
+    std::fs::write(
+        config_dir.join(PRICING_FILE),
+        serde_json::to_string_pretty(&overrides)?,
+    )?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CostBreakdown {
+    pub request_cost: f64,
+    pub transfer_cost: f64,
+    pub total_cost: f64,
+}
+
+/// Estimate the cost of requests plus data moved at the given per-GB price
+pub fn estimate_cost(
+    get_requests: u64,
+    put_requests: u64,
+    bytes: u64,
+    per_gb: f64,
+    prices: &PriceTable,
+) -> CostBreakdown {
+    let request_cost = get_requests as f64 / 1000.0 * prices.get_per_1000
+        + put_requests as f64 / 1000.0 * prices.put_per_1000;
+    let transfer_cost = bytes as f64 / BYTES_PER_GB * per_gb;
+    CostBreakdown {
+        request_cost,
+        transfer_cost,
+        total_cost: request_cost + transfer_cost,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{estimate_cost, parse_overrides, PriceTable};
+    use crate::s3::provider::ProviderKind;
+
+    #[test]
+    fn estimate_cost_combines_requests_and_transfer() {
+        let prices = PriceTable::default_for(ProviderKind::Aws);
+        let cost = estimate_cost(10_000, 10, 100 * 1024 * 1024 * 1024, prices.egress_per_gb, &prices);
+        assert!((cost.request_cost - (0.004 + 0.00005)).abs() < 1e-9);
+        assert!((cost.transfer_cost - 9.0).abs() < 1e-9);
+        assert!((cost.total_cost - cost.request_cost - cost.transfer_cost).abs() < 1e-12);
+    }
+
+    #[test]
+    fn bad_overrides_are_skipped_one_at_a_time() {
+        let overrides = parse_overrides(
+            r#"{
+                "aws": {"get_per_1000": 0.001, "put_per_1000": 0.01, "egress_per_gb": 0.05, "ingress_per_gb": 0.0},
+                "wasabi": {"get_per_1000": -1.0, "put_per_1000": 0.0, "egress_per_gb": 0.0, "ingress_per_gb": 0.0},
+                "nowhere": {"get_per_1000": 0.0, "put_per_1000": 0.0, "egress_per_gb": 0.0, "ingress_per_gb": 0.0},
+                "minio": {"get_per_1000": "free"}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(overrides.len(), 1);
+        assert_eq!(overrides[&ProviderKind::Aws].egress_per_gb, 0.05);
+
+        let mut table = PriceTable::default_for(ProviderKind::Aws);
+        assert!(table.validate().is_ok());
+        table.put_per_1000 = f64::NAN;
+        assert!(table.validate().is_err());
+        table.put_per_1000 = f64::INFINITY;
+        assert!(table.validate().is_err());
+    }
+}
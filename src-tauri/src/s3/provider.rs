@@ -0,0 +1,377 @@
+use crate::s3::client::normalize_endpoint_url;
+use aws_config::{BehaviorVersion, Region};
+use aws_sdk_s3::config::http::HttpResponse;
+use aws_sdk_s3::config::{RequestChecksumCalculation, ResponseChecksumValidation};
+use aws_sdk_s3::error::SdkError;
+use aws_sdk_s3::Client;
+use serde::{Deserialize, Serialize};
+
+/// Known S3-compatible providers that need provider-specific client behavior.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+    Aws,
+    Minio,
+    Wasabi,
+    BackblazeB2,
+    CloudflareR2,
+    Linode,
+    GcsInterop,
+    Unknown,
+}
+
+/// Behavior adjustments the S3 client builder can apply for a provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderQuirks {
+    /// Address buckets as `endpoint/bucket` instead of `bucket.endpoint`.
+    pub force_path_style: bool,
+    /// Don't call GetBucketLocation; use the profile region instead.
+    pub skip_bucket_location: bool,
+    /// Only send/validate checksums when the operation requires them.
+    pub relaxed_checksums: bool,
+    /// Provider only accepts SigV2. The SDK signs with SigV4 only, so this is
+    /// surfaced as a warning rather than changing the signer.
+    #[serde(default)]
+    pub legacy_sigv2: bool,
+}
+
+impl ProviderKind {
+    pub fn quirks(self) -> ProviderQuirks {
+        match self {
+            ProviderKind::Aws => ProviderQuirks {
+                force_path_style: false,
+                skip_bucket_location: false,
+                relaxed_checksums: false,
+                legacy_sigv2: false,
+            },
+            ProviderKind::Minio | ProviderKind::Wasabi => ProviderQuirks {
+                force_path_style: true,
+                skip_bucket_location: false,
+                relaxed_checksums: true,
+                legacy_sigv2: false,
+            },
+            // Linode, R2, B2 and GCS either reject GetBucketLocation or return a
+            // value that isn't a usable signing region (e.g. R2's "auto").
+            ProviderKind::BackblazeB2
+            | ProviderKind::CloudflareR2
+            | ProviderKind::Linode
+            | ProviderKind::GcsInterop
+            | ProviderKind::Unknown => ProviderQuirks {
+                force_path_style: true,
+                skip_bucket_location: true,
+                relaxed_checksums: true,
+                legacy_sigv2: false,
+            },
+        }
+    }
+}
+
+/// Apply a provider's quirks to an S3 config builder.
+pub(crate) fn apply_provider_quirks(
+    mut builder: aws_sdk_s3::config::Builder,
+    quirks: &ProviderQuirks,
+) -> aws_sdk_s3::config::Builder {
+    builder = builder.force_path_style(quirks.force_path_style);
+
+    if quirks.relaxed_checksums {
+        builder = builder
+            .request_checksum_calculation(RequestChecksumCalculation::WhenRequired)
+            .response_checksum_validation(ResponseChecksumValidation::WhenRequired);
+    }
+
+    if quirks.legacy_sigv2 {
+        log::warn!("Endpoint is marked as SigV2-only, but requests are always signed with SigV4");
+    }
+
+    builder
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderDetection {
+    pub provider: ProviderKind,
+    /// How the provider was identified: "hostname", "probe" or "none".
+    pub detected_by: String,
+    pub server_header: Option<String>,
+    pub quirks: ProviderQuirks,
+}
+
+/// Extract the lowercase hostname (without port) from an endpoint URL.
+pub(crate) fn endpoint_host(endpoint_url: &str) -> String {
+    let normalized = normalize_endpoint_url(endpoint_url);
+    let without_scheme = normalized
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(&normalized);
+    let authority = without_scheme.split('/').next().unwrap_or_default();
+    let host = authority.rsplit_once('@').map(|(_, h)| h).unwrap_or(authority);
+
+    let host = if host.starts_with('[') {
+        // IPv6 literal, keep the brackets and drop the port.
+        host.split_once(']')
+            .map(|(h, _)| format!("{}]", h))
+            .unwrap_or_else(|| host.to_string())
+    } else {
+        host.split(':').next().unwrap_or_default().to_string()
+    };
+
+    host.to_ascii_lowercase()
+}
+
+/// Classify a provider purely from the endpoint hostname.
+pub fn classify_host(host: &str) -> ProviderKind {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    let matches_domain = |domain: &str| host == domain || host.ends_with(&format!(".{}", domain));
+
+    if matches_domain("amazonaws.com") || matches_domain("amazonaws.com.cn") {
+        ProviderKind::Aws
+    } else if matches_domain("wasabisys.com") {
+        ProviderKind::Wasabi
+    } else if matches_domain("backblazeb2.com") {
+        ProviderKind::BackblazeB2
+    } else if matches_domain("r2.cloudflarestorage.com") {
+        ProviderKind::CloudflareR2
+    } else if matches_domain("linodeobjects.com") {
+        ProviderKind::Linode
+    } else if matches_domain("storage.googleapis.com") {
+        ProviderKind::GcsInterop
+    } else if host.split(['.', '-']).any(|label| label == "minio") {
+        ProviderKind::Minio
+    } else {
+        ProviderKind::Unknown
+    }
+}
+
+/// Classify a provider from the `Server` response header of a probe request.
+/// `cloudflare` only says the endpoint sits behind Cloudflare's proxy, not that
+/// it is R2, so R2 is recognised by its host alone.
+pub fn classify_server_header(server: &str) -> ProviderKind {
+    let server = server.to_ascii_lowercase();
+
+    if server.contains("amazons3") {
+        ProviderKind::Aws
+    } else if server.contains("minio") {
+        ProviderKind::Minio
+    } else if server.contains("wasabi") {
+        ProviderKind::Wasabi
+    } else if server.contains("uploadserver") {
+        ProviderKind::GcsInterop
+    } else {
+        ProviderKind::Unknown
+    }
+}
+
+/// Shown when an endpoint answers with a web page or other content S3 never sends
+pub const NOT_S3_ENDPOINT_MESSAGE: &str = "This doesn't look like an S3 API endpoint: it answered with a web page instead of an S3 response. Check that the URL is the provider's S3 API endpoint (e.g., https://us-east-1.linodeobjects.com) and not its website or console.";
+
+/// Whether a response is something other than what an S3 API sends. S3 replies
+/// with XML or an empty body; HTML pages and other text (JSON, plain-text
+/// errors) come from websites, consoles or proxies in front of the real API.
+pub fn is_non_s3_response(content_type: Option<&str>, body: &[u8]) -> bool {
+    let content_type = content_type.unwrap_or_default().to_ascii_lowercase();
+    if content_type.contains("html") {
+        return true;
+    }
+
+    let body = String::from_utf8_lossy(body);
+    let body = body.trim_start_matches('\u{feff}').trim_start();
+    if body.is_empty() {
+        return false;
+    }
+    let start: String = body.chars().take(64).collect::<String>().to_ascii_lowercase();
+    start.starts_with("<!doctype html") || start.starts_with("<html") || !start.starts_with('<')
+}
+
+/// Whether a failed request got a response that didn't come from an S3 API
+pub fn is_non_s3_error<E>(err: &SdkError<E, HttpResponse>) -> bool {
+    err.raw_response().is_some_and(|response| {
+        is_non_s3_response(
+            response.headers().get("content-type"),
+            response.body().bytes().unwrap_or_default(),
+        )
+    })
+}
+
+/// What a throwaway ListBuckets against an endpoint got back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointCheck {
+    /// Whether the endpoint answered at all
+    pub reachable: bool,
+    /// False when it answered with HTML or other content S3 never sends
+    pub speaks_s3: bool,
+    pub server_header: Option<String>,
+    pub message: String,
+}
+
+/// Send an unauthenticated-equivalent ListBuckets (with throwaway credentials) and
+/// report whether the response came from an S3 API. The request is expected to
+/// be rejected; only the shape of the rejection is of interest.
+pub async fn check_endpoint(endpoint_url: &str) -> EndpointCheck {
+    let credentials = aws_credential_types::Credentials::new(
+        "brows3-probe",
+        "brows3-probe",
+        None,
+        None,
+        "provider_probe",
+    );
+    let config = aws_sdk_s3::config::Builder::new()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .credentials_provider(credentials)
+        .endpoint_url(normalize_endpoint_url(endpoint_url))
+        .force_path_style(true)
+        .build();
+    let client = Client::from_conf(config);
+
+    let result = tokio::time::timeout(
+        std::time::Duration::from_secs(10),
+        client.list_buckets().send(),
+    )
+    .await;
+
+    match result {
+        Ok(Err(err)) => {
+            let server_header = err
+                .raw_response()
+                .and_then(|response| response.headers().get("server"))
+                .map(|value| value.to_string());
+            if err.raw_response().is_none() {
+                EndpointCheck {
+                    reachable: false,
+                    speaks_s3: false,
+                    server_header,
+                    message: format!("Could not reach the endpoint: {}", err),
+                }
+            } else if is_non_s3_error(&err) {
+                EndpointCheck {
+                    reachable: true,
+                    speaks_s3: false,
+                    server_header,
+                    message: NOT_S3_ENDPOINT_MESSAGE.to_string(),
+                }
+            } else {
+                EndpointCheck {
+                    reachable: true,
+                    speaks_s3: true,
+                    server_header,
+                    message: "The endpoint answered like an S3 API".to_string(),
+                }
+            }
+        }
+        Ok(Ok(_)) => EndpointCheck {
+            reachable: true,
+            speaks_s3: true,
+            server_header: None,
+            message: "The endpoint answered like an S3 API".to_string(),
+        },
+        Err(_) => {
+            log::warn!("Endpoint probe timed out for '{}'", endpoint_url);
+            EndpointCheck {
+                reachable: false,
+                speaks_s3: false,
+                server_header: None,
+                message: "The endpoint did not answer within 10 seconds".to_string(),
+            }
+        }
+    }
+}
+
+/// The `Server` header an endpoint rejects a throwaway ListBuckets with
+pub async fn probe_server_header(endpoint_url: &str) -> Option<String> {
+    check_endpoint(endpoint_url).await.server_header
+}
+
+/// Detect the provider behind an endpoint, first by hostname and then by probing it.
+pub async fn detect_provider(endpoint_url: &str) -> ProviderDetection {
+    let by_host = classify_host(&endpoint_host(endpoint_url));
+    if by_host != ProviderKind::Unknown {
+        return ProviderDetection {
+            provider: by_host,
+            detected_by: "hostname".to_string(),
+            server_header: None,
+            quirks: by_host.quirks(),
+        };
+    }
+
+    let server_header = probe_server_header(endpoint_url).await;
+    let by_probe = server_header
+        .as_deref()
+        .map(classify_server_header)
+        .unwrap_or(ProviderKind::Unknown);
+
+    ProviderDetection {
+        provider: by_probe,
+        detected_by: if by_probe == ProviderKind::Unknown {
+            "none".to_string()
+        } else {
+            "probe".to_string()
+        },
+        server_header,
+        quirks: by_probe.quirks(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{classify_host, classify_server_header, endpoint_host, is_non_s3_response, ProviderKind};
+
+    #[test]
+    fn endpoint_host_strips_scheme_port_and_path() {
+        assert_eq!(endpoint_host("http://localhost:9000/"), "localhost");
+        assert_eq!(
+            endpoint_host("US-East-1.LinodeObjects.com"),
+            "us-east-1.linodeobjects.com"
+        );
+        assert_eq!(endpoint_host("https://[::1]:9000"), "[::1]");
+    }
+
+    #[test]
+    fn classify_host_recognizes_known_providers() {
+        assert_eq!(classify_host("s3.us-west-2.amazonaws.com"), ProviderKind::Aws);
+        assert_eq!(classify_host("s3.eu-central-1.wasabisys.com"), ProviderKind::Wasabi);
+        assert_eq!(
+            classify_host("s3.us-west-004.backblazeb2.com"),
+            ProviderKind::BackblazeB2
+        );
+        assert_eq!(
+            classify_host("abc123.r2.cloudflarestorage.com"),
+            ProviderKind::CloudflareR2
+        );
+        assert_eq!(classify_host("us-east-1.linodeobjects.com"), ProviderKind::Linode);
+        assert_eq!(classify_host("storage.googleapis.com"), ProviderKind::GcsInterop);
+        assert_eq!(classify_host("minio.internal"), ProviderKind::Minio);
+        assert_eq!(classify_host("localhost"), ProviderKind::Unknown);
+    }
+
+    #[test]
+    fn classify_host_does_not_match_lookalike_domains() {
+        assert_eq!(classify_host("notamazonaws.com"), ProviderKind::Unknown);
+        assert_eq!(classify_host("terminator.example.com"), ProviderKind::Unknown);
+    }
+
+    #[test]
+    fn unknown_provider_keeps_conservative_custom_endpoint_defaults() {
+        let quirks = ProviderKind::Unknown.quirks();
+        assert!(quirks.force_path_style);
+        assert!(quirks.skip_bucket_location);
+        assert!(quirks.relaxed_checksums);
+        assert!(!ProviderKind::Aws.quirks().force_path_style);
+    }
+
+    #[test]
+    fn classify_server_header_recognizes_known_servers() {
+        assert_eq!(classify_server_header("AmazonS3"), ProviderKind::Aws);
+        assert_eq!(classify_server_header("MinIO"), ProviderKind::Minio);
+        assert_eq!(classify_server_header("cloudflare"), ProviderKind::Unknown);
+        assert_eq!(classify_server_header("nginx"), ProviderKind::Unknown);
+    }
+
+    #[test]
+    fn html_and_non_xml_responses_are_not_s3() {
+        let s3_error = b"<?xml version=\"1.0\"?><Error><Code>InvalidAccessKeyId</Code></Error>";
+        assert!(!is_non_s3_response(Some("application/xml"), s3_error));
+        assert!(!is_non_s3_response(None, b""));
+        assert!(is_non_s3_response(Some("text/html; charset=utf-8"), b""));
+        assert!(is_non_s3_response(None, b"\n  <!DOCTYPE html><html></html>"));
+        assert!(is_non_s3_response(Some("application/json"), br#"{"error":"not found"}"#));
+    }
+}
@@ -0,0 +1,149 @@
+//! A local HTTP server standing in for S3 in tests
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::Notify;
+
+pub(crate) struct TestS3 {
+    pub endpoint: String,
+    requests: Arc<Mutex<Vec<String>>>,
+    holds: Arc<Mutex<Vec<Hold>>>,
+}
+
+/// Requests the server keeps waiting before answering them, from [`TestS3::hold`]
+#[derive(Clone)]
+pub(crate) struct Hold {
+    method: String,
+    target: String,
+    received: Arc<Notify>,
+    release: Arc<Notify>,
+}
+
+impl Hold {
+    /// Wait for a matching request to arrive
+    pub async fn received(&self) {
+        self.received.notified().await;
+    }
+
+    /// Answer the held request. A hold that is never released stalls it for good.
+    pub fn release(&self) {
+        self.release.notify_one();
+    }
+}
+
+impl TestS3 {
+    /// Answer every request with `respond(method, target)`, which returns the
+    /// status code and body. HEAD responses only carry the body's length.
+    pub async fn start<F>(respond: F) -> Self
+    where
+        F: Fn(&str, &str) -> (u16, String) + Send + Sync + 'static,
+    {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let requests: Arc<Mutex<Vec<String>>> = Default::default();
+        let holds: Arc<Mutex<Vec<Hold>>> = Default::default();
+        let respond = Arc::new(respond);
+
+        let (recorded, held) = (requests.clone(), holds.clone());
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let (respond, recorded, held) = (respond.clone(), recorded.clone(), held.clone());
+                tokio::spawn(async move {
+                    let mut stream = BufReader::new(stream);
+                    loop {
+                        let mut request_line = String::new();
+                        if stream.read_line(&mut request_line).await.unwrap_or(0) == 0 {
+                            return;
+                        }
+                        let mut content_length = 0usize;
+                        loop {
+                            let mut header = String::new();
+                            stream.read_line(&mut header).await.unwrap();
+                            if header.trim().is_empty() {
+                                break;
+                            }
+                            if let Some((name, value)) = header.split_once(':') {
+                                if name.eq_ignore_ascii_case("content-length") {
+                                    content_length = value.trim().parse().unwrap();
+                                }
+                            }
+                        }
+                        let mut request_body = vec![0u8; content_length];
+                        stream.read_exact(&mut request_body).await.unwrap();
+
+                        let request: Vec<&str> = request_line.split_whitespace().collect();
+                        let (method, target) = (request[0], request[1]);
+                        recorded.lock().unwrap().push(format!("{} {}", method, target));
+
+                        let hold = held
+                            .lock()
+                            .unwrap()
+                            .iter()
+                            .find(|hold| hold.method == method && target.contains(&hold.target))
+                            .cloned();
+                        if let Some(hold) = hold {
+                            hold.received.notify_one();
+                            hold.release.notified().await;
+                        }
+
+                        let (status, body) = respond(method, target);
+                        let mut response = format!(
+                            "HTTP/1.1 {} Test\r\nContent-Type: application/xml\r\nContent-Length: {}\r\n\r\n",
+                            status,
+                            body.len()
+                        );
+                        if method != "HEAD" {
+                            response.push_str(&body);
+                        }
+                        stream.get_mut().write_all(response.as_bytes()).await.unwrap();
+                    }
+                });
+            }
+        });
+
+        Self {
+            endpoint,
+            requests,
+            holds,
+        }
+    }
+
+    /// Keep `method` requests whose target contains `target` waiting, once their
+    /// body has been read, until the returned hold is released
+    pub fn hold(&self, method: &str, target: &str) -> Hold {
+        let hold = Hold {
+            method: method.to_string(),
+            target: target.to_string(),
+            received: Default::default(),
+            release: Default::default(),
+        };
+        self.holds.lock().unwrap().push(hold.clone());
+        hold
+    }
+
+    pub fn client(&self) -> aws_sdk_s3::Client {
+        let config = aws_sdk_s3::Config::builder()
+            .behavior_version_latest()
+            .region(aws_config::Region::new("us-east-1"))
+            .credentials_provider(aws_credential_types::Credentials::new("AKID", "SECRET", None, None, "test"))
+            .endpoint_url(&self.endpoint)
+            .force_path_style(true)
+            .retry_config(aws_sdk_s3::config::retry::RetryConfig::disabled())
+            .build();
+        aws_sdk_s3::Client::from_conf(config)
+    }
+
+    /// `METHOD /path?query` of every request received so far
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Decoded value of a query parameter in a request target
+pub(crate) fn query_param(target: &str, name: &str) -> Option<String> {
+    let (_, query) = target.split_once('?')?;
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        (key == name).then(|| urlencoding::decode(value).map(|value| value.into_owned()).unwrap_or_default())
+    })
+}
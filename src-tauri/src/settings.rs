@@ -0,0 +1,166 @@
+use crate::error::Result;
+use crate::s3::client::{DEFAULT_DISK_CACHE_THRESHOLD, DEFAULT_LIST_PAGE_SIZE};
+use crate::s3::{RegionRedirectPolicy, S3ClientManager};
+use crate::transfer::manager::DEFAULT_MAX_CONCURRENCY;
+use crate::transfer::TransferManager;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+const SETTINGS_FILE: &str = "settings.json";
+const SETTINGS_BACKUP_FILE: &str = "settings.json.bak";
+
+pub type SettingsState = Arc<RwLock<SettingsManager>>;
+
+/// Application-wide preferences, saved to settings.json. Fields missing from the
+/// file (for example ones added by a later version) take their defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub transfer_concurrency: usize,
+    pub check_free_space: bool,
+    pub list_page_size: i32,
+    pub region_redirect: RegionRedirectPolicy,
+    pub disk_cache_threshold: usize,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            transfer_concurrency: DEFAULT_MAX_CONCURRENCY,
+            check_free_space: true,
+            list_page_size: DEFAULT_LIST_PAGE_SIZE,
+            region_redirect: RegionRedirectPolicy::default(),
+            disk_cache_threshold: DEFAULT_DISK_CACHE_THRESHOLD,
+        }
+    }
+}
+
+impl Settings {
+    /// Push these settings into the managers that use them, replacing each value
+    /// with the one the manager actually applied after clamping.
+    pub fn apply_to(&mut self, s3: &mut S3ClientManager, transfers: &TransferManager) {
+        self.transfer_concurrency = transfers.set_max_concurrency(self.transfer_concurrency);
+        transfers.set_check_free_space(self.check_free_space);
+        self.list_page_size = s3.set_list_page_size(self.list_page_size);
+        self.region_redirect =
+            s3.set_redirect_policy(self.region_redirect.max_attempts, self.region_redirect.backoff_ms);
+        self.disk_cache_threshold = s3.set_disk_cache_threshold(self.disk_cache_threshold);
+    }
+}
+
+pub struct SettingsManager {
+    config_dir: PathBuf,
+    settings: Settings,
+}
+
+impl SettingsManager {
+    /// Load settings.json from `config_dir`. A missing or unreadable file gives
+    /// the defaults; one that doesn't parse is backed up to settings.json.bak
+    /// first, since the next save replaces it.
+    pub fn new(config_dir: PathBuf) -> Self {
+        let path = config_dir.join(SETTINGS_FILE);
+        let settings = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::error!("Failed to parse {:?}, using default settings: {}", path, e);
+                let backup_path = config_dir.join(SETTINGS_BACKUP_FILE);
+                match std::fs::write(&backup_path, &content) {
+                    Ok(()) => log::warn!("Backed up unreadable settings file to {:?}", backup_path),
+                    Err(e) => log::error!("Failed to back up settings to {:?}: {}", backup_path, e),
+                }
+                Settings::default()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Settings::default(),
+            Err(e) => {
+                log::error!("Failed to read {:?}, using default settings: {}", path, e);
+                Settings::default()
+            }
+        };
+        Self { config_dir, settings }
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Replace the settings and save them. Returns what was saved.
+    pub fn replace(&mut self, settings: Settings) -> Result<Settings> {
+        self.settings = settings;
+        self.save()?;
+        Ok(self.settings.clone())
+    }
+
+    /// Change some settings in place and save them
+    pub fn modify(&mut self, change: impl FnOnce(&mut Settings)) -> Result<Settings> {
+        let mut settings = self.settings.clone();
+        change(&mut settings);
+        self.replace(settings)
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = self.config_dir.join(SETTINGS_FILE);
+        let temp_path = path.with_extension("tmp");
+
+        std::fs::write(&temp_path, serde_json::to_string_pretty(&self.settings)?)?;
+
+        #[cfg(unix)]
+        {
+            // On Unix, rename replaces the destination atomically.
+            std::fs::rename(&temp_path, &path)?;
+        }
+
+        #[cfg(windows)]
+        {
+            // Windows rename does not replace an existing destination file.
+            if path.exists() {
+                std::fs::remove_file(&path)?;
+            }
+            std::fs::rename(&temp_path, &path)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Settings, SettingsManager, SETTINGS_BACKUP_FILE, SETTINGS_FILE};
+    use crate::s3::S3ClientManager;
+    use crate::transfer::TransferManager;
+
+    #[test]
+    fn saved_settings_load_back_and_missing_fields_default() {
+        let dir = std::env::temp_dir().join(format!("brows3-settings-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        std::fs::write(dir.join(SETTINGS_FILE), r#"{"list_page_size": 500}"#).unwrap();
+        let mut manager = SettingsManager::new(dir.clone());
+        assert_eq!(manager.settings().list_page_size, 500);
+        assert_eq!(manager.settings().transfer_concurrency, Settings::default().transfer_concurrency);
+
+        manager.modify(|settings| settings.check_free_space = false).unwrap();
+        let reloaded = SettingsManager::new(dir.clone());
+        assert_eq!(reloaded.settings(), manager.settings());
+
+        // A file that doesn't parse is kept aside before defaults replace it
+        std::fs::write(dir.join(SETTINGS_FILE), "{ not json").unwrap();
+        let recovered = SettingsManager::new(dir.clone());
+        assert_eq!(recovered.settings(), &Settings::default());
+        assert_eq!(std::fs::read_to_string(dir.join(SETTINGS_BACKUP_FILE)).unwrap(), "{ not json");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn applying_settings_keeps_the_clamped_values() {
+        let mut settings = Settings {
+            transfer_concurrency: 500,
+            list_page_size: 0,
+            ..Settings::default()
+        };
+        settings.apply_to(&mut S3ClientManager::new(), &TransferManager::new());
+        assert_eq!(settings.transfer_concurrency, crate::transfer::manager::MAX_CONCURRENCY);
+        assert_eq!(settings.list_page_size, 1);
+    }
+}
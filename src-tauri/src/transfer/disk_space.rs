@@ -0,0 +1,55 @@
+use crate::error::{AppError, Result};
+use std::path::Path;
+
+/// The closest ancestor of `path` that exists, which is where the space for a
+/// not-yet-created download will come from
+fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|ancestor| ancestor.exists())
+}
+
+/// Fail with `InsufficientSpace` when the volume holding `path` can't take
+/// `needed` more bytes. A file already at `path` is about to be overwritten, so
+/// its size counts as free.
+pub fn ensure_free_space(path: &Path, needed: u64) -> Result<()> {
+    if needed == 0 {
+        return Ok(());
+    }
+    let Some(existing) = existing_ancestor(path) else {
+        return Ok(());
+    };
+
+    let available = match fs4::available_space(existing) {
+        Ok(available) => available,
+        Err(e) => {
+            // Not knowing is no reason to refuse the download
+            log::warn!("Could not read free space for {}: {}", existing.display(), e);
+            return Ok(());
+        }
+    };
+    let reclaimed = std::fs::metadata(path)
+        .ok()
+        .filter(|meta| meta.is_file())
+        .map(|meta| meta.len())
+        .unwrap_or(0);
+
+    if needed > available.saturating_add(reclaimed) {
+        return Err(AppError::InsufficientSpace { needed, available });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ensure_free_space;
+    use crate::error::AppError;
+
+    #[test]
+    fn refuses_downloads_larger_than_the_volume() {
+        let target = std::env::temp_dir().join("brows3-free-space").join("missing").join("file.bin");
+        assert!(ensure_free_space(&target, 1).is_ok());
+        assert!(matches!(
+            ensure_free_space(&target, u64::MAX),
+            Err(AppError::InsufficientSpace { needed: u64::MAX, .. })
+        ));
+    }
+}
@@ -0,0 +1,181 @@
+/// Junk that is almost never meant to be uploaded with a folder
+pub const DEFAULT_EXCLUDES: &[&str] = &[
+    ".git",
+    ".svn",
+    ".hg",
+    "node_modules",
+    "__pycache__",
+    ".DS_Store",
+    "Thumbs.db",
+    "desktop.ini",
+];
+
+/// Glob patterns matched against paths relative to the folder being transferred.
+///
+/// A pattern without a `/` matches any single path component, so `node_modules`
+/// skips every file below any `node_modules` directory and `*.tmp` skips temp files
+/// at any depth. A pattern containing a `/` is matched against the whole relative
+/// path and against each of its parent directories. `*` and `?` never cross a `/`;
+/// `**` does.
+#[derive(Debug, Clone, Default)]
+pub struct ExcludeMatcher {
+    component_patterns: Vec<String>,
+    path_patterns: Vec<String>,
+}
+
+impl ExcludeMatcher {
+    pub fn new<I, S>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut matcher = Self::default();
+        for pattern in patterns {
+            let pattern = pattern.as_ref().trim().replace('\\', "/");
+            let pattern = pattern.trim_start_matches('/').trim_end_matches('/');
+            if pattern.is_empty() {
+                continue;
+            }
+            if pattern.contains('/') {
+                matcher.path_patterns.push(pattern.to_string());
+            } else {
+                matcher.component_patterns.push(pattern.to_string());
+            }
+        }
+        matcher
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.component_patterns.is_empty() && self.path_patterns.is_empty()
+    }
+
+    /// Whether a `/`-separated relative path is excluded
+    pub fn is_excluded(&self, relative_path: &str) -> bool {
+        let components: Vec<&str> = relative_path.split('/').filter(|c| !c.is_empty()).collect();
+
+        if components.iter().any(|component| {
+            self.component_patterns
+                .iter()
+                .any(|pattern| glob_match(pattern, component))
+        }) {
+            return true;
+        }
+
+        (1..=components.len()).any(|depth| {
+            let path = components[..depth].join("/");
+            self.path_patterns.iter().any(|pattern| glob_match(pattern, &path))
+        })
+    }
+}
+
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    glob_match_from(&pattern, &text)
+}
+
+fn glob_match_from(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            // `**/` may also match zero directories
+            let mut rest = &pattern[2..];
+            if rest.first() == Some(&'/') && glob_match_from(&rest[1..], text) {
+                return true;
+            }
+            while rest.first() == Some(&'*') {
+                rest = &rest[1..];
+            }
+            (0..=text.len()).any(|skip| glob_match_from(rest, &text[skip..]))
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            for skip in 0..=text.len() {
+                if glob_match_from(rest, &text[skip..]) {
+                    return true;
+                }
+                if text.get(skip) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some('?') => {
+            matches!(text.first(), Some(c) if *c != '/') && glob_match_from(&pattern[1..], &text[1..])
+        }
+        Some(expected) => {
+            text.first() == Some(expected) && glob_match_from(&pattern[1..], &text[1..])
+        }
+    }
+}
+
+/// Whether a walked entry is hidden: a leading dot on any platform, plus the
+/// hidden or system attribute on Windows
+pub fn is_hidden(entry: &walkdir::DirEntry) -> bool {
+    if entry.file_name().to_string_lossy().starts_with('.') {
+        return true;
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+        if let Ok(metadata) = entry.metadata() {
+            return metadata.file_attributes() & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM) != 0;
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_hidden, ExcludeMatcher, DEFAULT_EXCLUDES};
+
+    #[test]
+    fn default_excludes_skip_junk_at_any_depth() {
+        let matcher = ExcludeMatcher::new(DEFAULT_EXCLUDES);
+        assert!(matcher.is_excluded("project/.git/config"));
+        assert!(matcher.is_excluded("web/node_modules/react/index.js"));
+        assert!(matcher.is_excluded("photos/.DS_Store"));
+        assert!(!matcher.is_excluded("src/main.rs"));
+        assert!(!matcher.is_excluded("docs/.gitignore"));
+    }
+
+    #[test]
+    fn path_patterns_match_whole_paths_and_parent_directories() {
+        let matcher = ExcludeMatcher::new(["*.tmp", "build/**", "docs/drafts", "**/cache/*.bin"]);
+        assert!(matcher.is_excluded("a/b/file.tmp"));
+        assert!(matcher.is_excluded("build/out/app.js"));
+        assert!(matcher.is_excluded("docs/drafts/notes.md"));
+        assert!(matcher.is_excluded("cache/x.bin"));
+        assert!(matcher.is_excluded("deep/cache/x.bin"));
+        assert!(!matcher.is_excluded("src/build/app.js"));
+        assert!(!matcher.is_excluded("docs/final.md"));
+    }
+
+    #[test]
+    fn dotfiles_and_dot_directories_are_hidden() {
+        let root = std::env::temp_dir().join(format!("brows3-hidden-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join(".config")).unwrap();
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(root.join(".env"), b"x").unwrap();
+        std::fs::write(root.join("docs/readme.md"), b"x").unwrap();
+
+        let hidden = |name: &str| {
+            walkdir::WalkDir::new(&root)
+                .into_iter()
+                .filter_map(|entry| entry.ok())
+                .find(|entry| entry.file_name() == name)
+                .map(|entry| is_hidden(&entry))
+                .unwrap()
+        };
+        assert!(hidden(".env"));
+        assert!(hidden(".config"));
+        assert!(!hidden("docs"));
+        assert!(!hidden("readme.md"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
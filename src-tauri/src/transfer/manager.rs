@@ -1,18 +1,31 @@
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use tokio::sync::{Mutex, Notify, RwLock};
 use tauri::{AppHandle, Emitter};
 use crate::credentials::Profile;
+use crate::s3::metrics::{TransferMetrics, TransferMetricsSnapshot};
 use crate::s3::S3ClientManager;
+use super::multipart::{self, CompletedPartRecord, MultipartState, MultipartStore};
 use super::{TransferJob, TransferStatus, TransferType, TransferEvent};
+use aws_sdk_s3::error::ProvideErrorMetadata;
 use aws_sdk_s3::primitives::ByteStream;
-use tokio::io::AsyncWriteExt;
+use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 use tokio::fs::File;
 
 
 
+/// Transfers run at once until the user picks another limit
+pub const DEFAULT_MAX_CONCURRENCY: usize = 5;
+/// Upper bound on concurrent transfers
+pub const MAX_CONCURRENCY: usize = 20;
+
+/// Redirects followed when importing from a URL
+const URL_IMPORT_MAX_REDIRECTS: usize = 10;
+
 // Define a safe shared state for the manager
+#[derive(Clone)]
 pub struct TransferManager {
     jobs: Arc<RwLock<HashMap<String, TransferJob>>>,
     queue: Arc<Mutex<Vec<String>>>, // List of Job IDs
@@ -21,6 +34,9 @@ pub struct TransferManager {
     active_count: Arc<AtomicUsize>,
     slot_notify: Arc<Notify>,
     app_handle: Arc<RwLock<Option<AppHandle>>>,
+    metrics: Arc<TransferMetrics>,
+    multipart: Arc<Mutex<MultipartStore>>,
+    check_free_space: Arc<AtomicBool>,
 }
 
 struct ActiveSlotGuard {
@@ -41,13 +57,90 @@ impl TransferManager {
             jobs: Arc::new(RwLock::new(HashMap::new())),
             queue: Arc::new(Mutex::new(Vec::new())),
             abort_handles: Arc::new(RwLock::new(HashMap::new())),
-            max_concurrency: Arc::new(AtomicUsize::new(5)),
+            max_concurrency: Arc::new(AtomicUsize::new(DEFAULT_MAX_CONCURRENCY)),
             active_count: Arc::new(AtomicUsize::new(0)),
             slot_notify: Arc::new(Notify::new()),
             app_handle: Arc::new(RwLock::new(None)),
+            metrics: Arc::new(TransferMetrics::default()),
+            multipart: Arc::new(Mutex::new(MultipartStore::default())),
+            check_free_space: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    /// Load the state of multipart uploads interrupted by the last shutdown
+    pub async fn load_multipart_state(&self, config_dir: &std::path::Path) {
+        *self.multipart.lock().await = MultipartStore::load(config_dir);
+    }
+
+    /// Multipart uploads that were interrupted and can be continued
+    pub async fn resumable_uploads(&self) -> Vec<MultipartState> {
+        self.multipart
+            .lock()
+            .await
+            .list()
+            .into_iter()
+            .filter(|state| !state.is_url_import())
+            .collect()
+    }
+
+    /// Unfinished uploads old enough to be considered abandoned, plus any URL
+    /// import, since those can't be resumed
+    pub async fn stale_uploads(&self) -> Vec<MultipartState> {
+        let cutoff = (chrono::Utc::now() - multipart::STALE_UPLOAD_AGE).timestamp_millis();
+        let store = self.multipart.lock().await;
+        let mut stale = store.older_than(cutoff);
+        stale.extend(
+            store
+                .list()
+                .into_iter()
+                .filter(|state| state.is_url_import() && state.started_at >= cutoff),
+        );
+        stale
+    }
+
+    /// Abort an unfinished upload on S3 and forget it. The state is kept if S3
+    /// couldn't be reached, so a later startup tries again.
+    pub async fn abort_upload(&self, state: &MultipartState, client: &aws_sdk_s3::Client) -> crate::error::Result<()> {
+        let result = client
+            .abort_multipart_upload()
+            .bucket(&state.bucket)
+            .key(&state.key)
+            .upload_id(&state.upload_id)
+            .send()
+            .await;
+        match result {
+            Ok(_) => {}
+            Err(err) if err.as_service_error().map(|e| e.is_no_such_upload()).unwrap_or(false) => {}
+            Err(err) => return Err(crate::error::AppError::S3Error(err.to_string())),
+        }
+        self.forget_upload(state).await;
+        Ok(())
+    }
+
+    pub async fn forget_upload(&self, state: &MultipartState) {
+        self.update_multipart(|store| {
+            store.remove(&state.bucket, &state.key, &state.local_path);
+        })
+        .await;
+    }
+
+    /// Change the saved multipart uploads, writing the file after the lock is released
+    async fn update_multipart(&self, update: impl FnOnce(&mut MultipartStore)) {
+        let snapshot = {
+            let mut store = self.multipart.lock().await;
+            update(&mut store);
+            store.snapshot()
+        };
+        if let Some(snapshot) = snapshot {
+            snapshot.write().await;
         }
     }
 
+    /// Transfer counters since startup
+    pub fn metrics(&self) -> TransferMetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
     pub async fn set_app_handle(&self, app_handle: AppHandle) {
         let mut handle = self.app_handle.write().await;
         *handle = Some(app_handle);
@@ -71,12 +164,24 @@ impl TransferManager {
         self.emit_update(&job).await;
     }
 
-    pub fn set_max_concurrency(&self, max: usize) {
-        let clamped = max.clamp(1, 20);
+    /// Set how many transfers run at once, clamped to `1..=MAX_CONCURRENCY`.
+    /// Returns the value actually applied.
+    pub fn set_max_concurrency(&self, max: usize) -> usize {
+        let clamped = max.clamp(1, MAX_CONCURRENCY);
         self.max_concurrency.store(clamped, Ordering::Release);
         self.slot_notify.notify_waiters();
+        clamped
     }
     
+    /// Whether downloads check the target volume has room before writing
+    pub fn set_check_free_space(&self, enabled: bool) {
+        self.check_free_space.store(enabled, Ordering::Release);
+    }
+
+    pub fn checks_free_space(&self) -> bool {
+        self.check_free_space.load(Ordering::Acquire)
+    }
+
     pub async fn get_job(&self, id: &str) -> Option<TransferJob> {
         let jobs = self.jobs.read().await;
         jobs.get(id).cloned()
@@ -122,7 +227,74 @@ impl TransferManager {
         false
     }
     
-    /// Remove a specific transfer job from history
+    /// Cancel every pending or running job in a folder-transfer group in one pass.
+    /// Returns the jobs that were cancelled.
+    pub async fn cancel_group(&self, group_id: &str) -> Vec<TransferJob> {
+        let cancelled: Vec<TransferJob> = {
+            let mut jobs = self.jobs.write().await;
+            jobs.values_mut()
+                .filter(|job| job.parent_group_id.as_deref() == Some(group_id))
+                .filter(|job| matches!(job.status, TransferStatus::Pending | TransferStatus::InProgress))
+                .map(|job| {
+                    job.status = TransferStatus::Cancelled;
+                    job.finished_at = Some(chrono::Utc::now().timestamp_millis());
+                    job.clone()
+                })
+                .collect()
+        };
+        if cancelled.is_empty() {
+            return cancelled;
+        }
+
+        let ids: std::collections::HashSet<&str> = cancelled.iter().map(|job| job.id.as_str()).collect();
+        {
+            let mut queue = self.queue.lock().await;
+            queue.retain(|job_id| !ids.contains(job_id.as_str()));
+        }
+        {
+            let mut handles = self.abort_handles.write().await;
+            for id in &ids {
+                if let Some(handle) = handles.remove(*id) {
+                    handle.abort();
+                }
+            }
+        }
+        log::info!("Cancelled {} jobs in transfer group {}", cancelled.len(), group_id);
+
+        for job in &cancelled {
+            self.emit_update(job).await;
+        }
+        cancelled
+    }
+
+    /// Relabel every job in a group. Renamed jobs are re-sent as `transfer-added`,
+    /// which the frontend upserts. Returns how many jobs were renamed.
+    pub async fn rename_group(&self, group_id: &str, name: &str) -> usize {
+        let renamed: Vec<TransferJob> = {
+            let mut jobs = self.jobs.write().await;
+            jobs.values_mut()
+                .filter(|job| job.parent_group_id.as_deref() == Some(group_id))
+                .map(|job| {
+                    job.group_name = Some(name.to_string());
+                    job.clone()
+                })
+                .collect()
+        };
+
+        if let Some(app) = self.app_handle.read().await.as_ref() {
+            for job in &renamed {
+                let _ = app.emit("transfer-added", job);
+            }
+        }
+        renamed.len()
+    }
+
+    /// Saved multipart state for an upload job, if it was uploading in parts
+    pub async fn multipart_state_for(&self, job: &TransferJob) -> Option<MultipartState> {
+        self.multipart.lock().await.get(&job.bucket, &job.key, &job.local_path)
+    }
+
+    /// Remove a specific transfer job from history, emitting `transfer-removed`
     pub async fn remove_job(&self, id: &str) -> bool {
         {
             let mut queue = self.queue.lock().await;
@@ -135,18 +307,30 @@ impl TransferManager {
         }
         drop(handles);
 
-        let mut jobs = self.jobs.write().await;
-        jobs.remove(id).is_some()
+        let removed = self.jobs.write().await.remove(id).is_some();
+        if removed {
+            self.emit_removed(&[id.to_string()]).await;
+        }
+        removed
     }
     
-    /// Clear all completed/failed/cancelled transfers
+    /// Clear all completed/failed/cancelled transfers, emitting `transfer-removed`
+    /// for each
     pub async fn clear_completed(&self) -> usize {
-        let mut jobs = self.jobs.write().await;
-        let initial_count = jobs.len();
-        jobs.retain(|_, job| {
-            matches!(job.status, TransferStatus::Pending | TransferStatus::InProgress)
-        });
-        initial_count - jobs.len()
+        let removed: Vec<String> = {
+            let mut jobs = self.jobs.write().await;
+            let finished: Vec<String> = jobs
+                .iter()
+                .filter(|(_, job)| !matches!(job.status, TransferStatus::Pending | TransferStatus::InProgress))
+                .map(|(id, _)| id.clone())
+                .collect();
+            for id in &finished {
+                jobs.remove(id);
+            }
+            finished
+        };
+        self.emit_removed(&removed).await;
+        removed.len()
     }
     
     /// Retry a failed transfer
@@ -170,6 +354,7 @@ impl TransferManager {
                     new_job.parent_group_id = job.parent_group_id.clone();
                     new_job.group_name = job.group_name.clone();
                     new_job.is_group_root = job.is_group_root;
+                    new_job.source_url = job.source_url.clone();
                     
                     let new_id = new_job.id.clone();
                     drop(jobs);
@@ -184,6 +369,43 @@ impl TransferManager {
         None
     }
 
+    /// Retry every failed (and optionally cancelled) transfer in one pass.
+    /// The original jobs are replaced by their retries so repeated calls don't duplicate work;
+    /// each replaced job is announced with `transfer-removed`.
+    pub async fn retry_all_failed(&self, include_cancelled: bool) -> usize {
+        let retryable_ids: Vec<String> = {
+            let jobs = self.jobs.read().await;
+            let mut retryable: Vec<&TransferJob> = jobs
+                .values()
+                .filter(|job| match job.status {
+                    TransferStatus::Failed(_) => true,
+                    TransferStatus::Cancelled => include_cancelled,
+                    _ => false,
+                })
+                .collect();
+            // Requeue in the order the jobs were originally created
+            retryable.sort_by_key(|job| job.created_at);
+            retryable.into_iter().map(|job| job.id.clone()).collect()
+        };
+
+        let mut retried = 0;
+        for id in retryable_ids {
+            if self.retry_job(&id).await.is_some() {
+                self.remove_job(&id).await;
+                retried += 1;
+            }
+        }
+        retried
+    }
+
+    async fn emit_removed(&self, ids: &[String]) {
+        if let Some(app) = self.app_handle.read().await.as_ref() {
+            for id in ids {
+                let _ = app.emit("transfer-removed", id);
+            }
+        }
+    }
+
     async fn emit_update(&self, job: &TransferJob) {
         if let Some(app) = self.app_handle.read().await.as_ref() {
             let event = TransferEvent {
@@ -263,11 +485,18 @@ impl TransferManager {
                                 // Double check if it was cancelled while we were working
                                 if let Some(current_job) = manager_inner.get_job(&id_inner).await {
                                     if !matches!(current_job.status, TransferStatus::Cancelled) {
+                                        manager_inner.metrics.record_completed(
+                                            matches!(current_job.transfer_type, TransferType::Upload),
+                                            current_job.total_bytes,
+                                        );
                                         manager_inner.update_job_status(&id_inner, TransferStatus::Completed).await;
                                     }
                                 }
                             },
-                            Err(e) => manager_inner.update_job_status(&id_inner, TransferStatus::Failed(e.to_string())).await,
+                            Err(e) => {
+                                manager_inner.metrics.record_failed();
+                                manager_inner.update_job_status(&id_inner, TransferStatus::Failed(e.to_string())).await
+                            }
                         }
                     }
                     
@@ -291,7 +520,9 @@ impl TransferManager {
                 // If final status, set finished_at
                 match status {
                     TransferStatus::Completed | TransferStatus::Failed(_) | TransferStatus::Cancelled => {
-                        job.finished_at = Some(chrono::Utc::now().timestamp_millis());
+                        let now = chrono::Utc::now().timestamp_millis();
+                        job.finished_at = Some(now);
+                        job.finish_throughput(now);
                     }
                     _ => {}
                 }
@@ -319,6 +550,7 @@ impl TransferManager {
             let mut jobs = self.jobs.write().await;
             if let Some(job) = jobs.get_mut(id) {
                 job.processed_bytes = processed;
+                job.record_throughput(chrono::Utc::now().timestamp_millis());
             }
         }
         if let Some(job) = self.get_job(id).await {
@@ -326,61 +558,86 @@ impl TransferManager {
         }
     }
 
+    /// Start (or, with bytes already done, restart) timing a job's throughput
+    async fn start_job_clock(&self, id: &str, processed: u64) {
+        let mut jobs = self.jobs.write().await;
+        if let Some(job) = jobs.get_mut(id) {
+            job.processed_bytes = processed;
+            job.start_throughput_clock(chrono::Utc::now().timestamp_millis());
+        }
+    }
+
     async fn execute_job(&self, job: &TransferJob, s3_manager: Arc<RwLock<S3ClientManager>>, profile: &Profile) -> crate::error::Result<()> {
-        let resolved_region = {
-            let s3 = s3_manager.read().await;
-            s3.get_bucket_region(&job.bucket)
-        }.or(job.bucket_region.clone());
+        self.start_job_clock(&job.id, 0).await;
+        let resolved_region = self.resolve_job_region(job, &s3_manager, profile).await;
 
+        let region = resolved_region
+            .clone()
+            .or_else(|| profile.region.clone())
+            .unwrap_or_else(|| "us-east-1".to_string());
+        crate::s3::load_accelerate_status(&s3_manager, profile, &job.bucket, &region).await?;
         let client = {
+            let lock_started = std::time::Instant::now();
             let mut s3 = s3_manager.write().await;
-            let c = if let Some(ref region) = resolved_region {
-                s3.get_client_for_region(profile, region).await?
-            } else {
-                s3.get_client(profile).await?
-            };
-            c.clone()
+            self.metrics.record_client_lock_wait(lock_started);
+            s3.get_client_for_bucket(profile, &job.bucket, &region).await?.clone()
         };
 
-        let detect_region = async {
-            let retry_client = {
-                let mut s3 = s3_manager.write().await;
-                s3.get_client(profile).await?.clone()
-            };
+        if let Some(url) = job.source_url.as_deref() {
+            if let Err(err) = self.import_from_url(job, &client, profile, url).await {
+                // Fetch failures have nothing to do with the bucket's region
+                if !matches!(err, crate::error::AppError::S3Error(_)) {
+                    return Err(err);
+                }
+                log::warn!("URL import failed, attempting region discovery: {}", err);
 
-            let new_region = crate::s3::get_bucket_region(&retry_client, &job.bucket).await.ok();
-            if let Some(ref region) = new_region {
-                let mut s3 = s3_manager.write().await;
-                s3.set_bucket_region(&job.bucket, region.clone());
+                if let Some(retry_client) = self.rediscover_job_region(job, &s3_manager, profile, None).await? {
+                    self.import_from_url(job, &retry_client, profile, url).await?;
+                } else {
+                    return Err(err);
+                }
             }
-            Ok::<Option<String>, crate::error::AppError>(new_region)
-        };
-        
+            return Ok(());
+        }
+
         match job.transfer_type {
+            TransferType::Upload if std::fs::metadata(&job.local_path).map(|m| m.len()).unwrap_or(0) >= multipart::MULTIPART_THRESHOLD => {
+                if let Err(err) = self.upload_multipart(job, &client, profile).await {
+                    log::warn!("multipart upload failed, attempting region discovery: {}", err);
+
+                    if let Some(retry_client) = self.rediscover_job_region(job, &s3_manager, profile, None).await? {
+                        self.upload_multipart(job, &retry_client, profile).await?;
+                    } else {
+                        return Err(err);
+                    }
+                }
+            }
             TransferType::Upload => {
+                 // A fixed Content-Length makes S3 reject a body cut short by
+                 // cancellation instead of storing a truncated object
+                 let file_size = tokio::fs::metadata(&job.local_path).await?.len() as i64;
                  let body = ByteStream::from_path(&job.local_path).await
                     .map_err(|e| crate::error::AppError::IoError(e.to_string()))?;
 
                  if let Err(err) = client.put_object()
                     .bucket(&job.bucket)
                     .key(&job.key)
+                    .content_length(file_size)
                     .body(body)
                     .send()
                     .await
                  {
                     log::warn!("upload transfer failed, attempting region discovery: {}", err);
 
-                    if let Some(new_region) = detect_region.await? {
-                        let retry_client = {
-                            let mut s3 = s3_manager.write().await;
-                            s3.get_client_for_region(profile, &new_region).await?.clone()
-                        };
+                    let redirect_region = crate::s3::region_from_redirect(&err);
+                    if let Some(retry_client) = self.rediscover_job_region(job, &s3_manager, profile, redirect_region).await? {
                         let retry_body = ByteStream::from_path(&job.local_path).await
                             .map_err(|e| crate::error::AppError::IoError(e.to_string()))?;
 
                         retry_client.put_object()
                             .bucket(&job.bucket)
                             .key(&job.key)
+                            .content_length(file_size)
                             .body(retry_body)
                             .send()
                             .await
@@ -406,12 +663,8 @@ impl TransferManager {
                     Err(err) => {
                         log::warn!("download transfer failed, attempting region discovery: {}", err);
 
-                        if let Some(new_region) = detect_region.await? {
-                            let retry_client = {
-                                let mut s3 = s3_manager.write().await;
-                                s3.get_client_for_region(profile, &new_region).await?.clone()
-                            };
-
+                        let redirect_region = crate::s3::region_from_redirect(&err);
+                        if let Some(retry_client) = self.rediscover_job_region(job, &s3_manager, profile, redirect_region).await? {
                             retry_client.get_object()
                                 .bucket(&job.bucket)
                                 .key(&job.key)
@@ -424,6 +677,14 @@ impl TransferManager {
                     }
                 };
 
+                if self.checks_free_space() {
+                    let needed = output
+                        .content_length()
+                        .map(|length| length.max(0) as u64)
+                        .unwrap_or(job.total_bytes);
+                    super::disk_space::ensure_free_space(std::path::Path::new(&job.local_path), needed)?;
+                }
+
                 if let Some(parent) = std::path::Path::new(&job.local_path).parent() {
                     tokio::fs::create_dir_all(parent).await
                         .map_err(|e| crate::error::AppError::IoError(e.to_string()))?;
@@ -459,4 +720,525 @@ impl TransferManager {
         
         Ok(())
     }
+
+    /// The region to send a job's requests to: the one cached for its bucket, else
+    /// the one it was queued with, else one resolved now, so a bucket outside the
+    /// profile's default region doesn't cost every transfer a failed first
+    /// request. Whatever is found is kept on the job and in the client manager.
+    async fn resolve_job_region(&self, job: &TransferJob, s3_manager: &Arc<RwLock<S3ClientManager>>, profile: &Profile) -> Option<String> {
+        let cached = s3_manager.read().await.get_bucket_region(&profile.id, &job.bucket);
+        let region = match cached.or_else(|| job.bucket_region.clone()) {
+            Some(region) => region,
+            None => {
+                let client = s3_manager.write().await.get_client(profile).await.ok()?.clone();
+                match crate::s3::resolve_bucket_region(&client, profile, &job.bucket).await {
+                    Ok(region) => {
+                        s3_manager.write().await.set_bucket_region(&profile.id, &job.bucket, region.clone());
+                        region
+                    }
+                    Err(e) => {
+                        log::debug!("Could not resolve region of '{}' before transfer: {}", job.bucket, e);
+                        return None;
+                    }
+                }
+            }
+        };
+        self.set_job_region(&job.id, &region).await;
+        Some(region)
+    }
+
+    /// Discover a job's bucket region after a failed request, preferring the
+    /// region the error redirected to, and return a client for it. `None` when
+    /// the region can't be found.
+    async fn rediscover_job_region(
+        &self,
+        job: &TransferJob,
+        s3_manager: &Arc<RwLock<S3ClientManager>>,
+        profile: &Profile,
+        redirect_region: Option<String>,
+    ) -> crate::error::Result<Option<aws_sdk_s3::Client>> {
+        let discovery_client = s3_manager.write().await.get_client(profile).await?.clone();
+        let Some(region) = crate::s3::discover_bucket_region(&discovery_client, profile, &job.bucket, redirect_region).await else {
+            return Ok(None);
+        };
+
+        s3_manager.write().await.set_bucket_region(&profile.id, &job.bucket, region.clone());
+        let client = crate::s3::client_for_bucket(s3_manager, profile, &job.bucket, &region).await?;
+        self.set_job_region(&job.id, &region).await;
+        Ok(Some(client))
+    }
+
+    async fn set_job_region(&self, id: &str, region: &str) {
+        let mut jobs = self.jobs.write().await;
+        if let Some(job) = jobs.get_mut(id) {
+            job.bucket_region = Some(region.to_string());
+        }
+    }
+
+    /// Upload a large file in parts, persisting each finished part so the upload
+    /// continues from the last one after a failure, cancellation or restart
+    async fn upload_multipart(&self, job: &TransferJob, client: &aws_sdk_s3::Client, profile: &Profile) -> crate::error::Result<()> {
+        let metadata = tokio::fs::metadata(&job.local_path).await?;
+        let file_size = metadata.len();
+        let file_modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|age| age.as_secs() as i64)
+            .unwrap_or(0);
+
+        let saved = self.multipart.lock().await.get(&job.bucket, &job.key, &job.local_path);
+        let resumed = match saved {
+            Some(state) if state.profile_id == profile.id && state.matches_file(file_size, file_modified) => {
+                self.reconcile_parts(client, state).await?
+            }
+            Some(state) => {
+                log::info!("Local file changed since upload of '{}' started, starting over", job.key);
+                let _ = self.abort_upload(&state, client).await;
+                None
+            }
+            None => None,
+        };
+
+        let state = match resumed {
+            Some(state) => {
+                log::info!(
+                    "Resuming upload of '{}' with {} of {} parts done",
+                    job.key,
+                    state.parts.len(),
+                    state.part_count()
+                );
+                state
+            }
+            None => self.start_multipart_upload(job, client, profile, file_size, file_modified, None).await?,
+        };
+
+        let done: std::collections::HashSet<i32> = state.parts.iter().map(|part| part.part_number).collect();
+        let mut parts = state.parts.clone();
+        let mut uploaded = state.uploaded_bytes();
+        self.start_job_clock(&job.id, uploaded).await;
+        self.update_job_progress(&job.id, uploaded).await;
+
+        let mut file = File::open(&job.local_path).await?;
+        for part_number in 1..=state.part_count() {
+            if done.contains(&part_number) {
+                continue;
+            }
+            let offset = (part_number as u64 - 1) * state.part_size;
+            let length = state.part_size.min(file_size - offset);
+            let mut buffer = vec![0u8; length as usize];
+            file.seek(std::io::SeekFrom::Start(offset)).await?;
+            file.read_exact(&mut buffer).await?;
+
+            let output = client
+                .upload_part()
+                .bucket(&job.bucket)
+                .key(&job.key)
+                .upload_id(&state.upload_id)
+                .part_number(part_number)
+                .body(ByteStream::from(buffer))
+                .send()
+                .await
+                .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?;
+
+            let part = CompletedPartRecord {
+                part_number,
+                e_tag: output.e_tag().unwrap_or_default().to_string(),
+            };
+            self.update_multipart(|store| store.record_part(&job.bucket, &job.key, &job.local_path, part.clone()))
+                .await;
+            parts.push(part);
+            uploaded += length;
+            self.update_job_progress(&job.id, uploaded).await;
+        }
+
+        parts.sort_by_key(|part| part.part_number);
+        let completed = CompletedMultipartUpload::builder()
+            .set_parts(Some(
+                parts
+                    .iter()
+                    .map(|part| {
+                        CompletedPart::builder()
+                            .part_number(part.part_number)
+                            .e_tag(&part.e_tag)
+                            .build()
+                    })
+                    .collect(),
+            ))
+            .build();
+        client
+            .complete_multipart_upload()
+            .bucket(&job.bucket)
+            .key(&job.key)
+            .upload_id(&state.upload_id)
+            .multipart_upload(completed)
+            .send()
+            .await
+            .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?;
+
+        self.forget_upload(&state).await;
+        Ok(())
+    }
+
+    /// Stream an HTTP(S) response body into S3, following redirects. A body that
+    /// fits in one part goes up with a single PutObject; anything larger becomes a
+    /// multipart upload whose state is saved like a file upload's, so cancelling
+    /// the job aborts it. An interrupted import can't be resumed and starts over.
+    async fn import_from_url(&self, job: &TransferJob, client: &aws_sdk_s3::Client, profile: &Profile, url: &str) -> crate::error::Result<()> {
+        if let Some(state) = self.multipart_state_for(job).await {
+            let _ = self.abort_upload(&state, client).await;
+        }
+
+        let result = self.stream_url_to_s3(job, client, profile, url).await;
+        if result.is_err() {
+            if let Some(state) = self.multipart_state_for(job).await {
+                if let Err(e) = self.abort_upload(&state, client).await {
+                    log::warn!("Could not abort failed import of '{}': {}", job.key, e);
+                }
+            }
+        }
+        result
+    }
+
+    async fn stream_url_to_s3(&self, job: &TransferJob, client: &aws_sdk_s3::Client, profile: &Profile, url: &str) -> crate::error::Result<()> {
+        let http = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::limited(URL_IMPORT_MAX_REDIRECTS))
+            .build()
+            .map_err(|e| crate::error::AppError::ConnectionFailed(e.to_string()))?;
+        let mut response = http
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| crate::error::AppError::ConnectionFailed(format!("Fetching '{}' failed: {}", url, e)))?;
+
+        let content_length = response.content_length();
+        if let Some(length) = content_length {
+            self.update_job_total_size(&job.id, length).await;
+        }
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        // Without a Content-Length the minimum part size applies, which caps the
+        // import at 10,000 parts of it
+        let part_size = multipart::part_size_for(content_length.unwrap_or(0)) as usize;
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut upload: Option<MultipartState> = None;
+        let mut parts: Vec<CompletedPartRecord> = Vec::new();
+        let mut received: u64 = 0;
+        let mut last_update = std::time::Instant::now();
+
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| crate::error::AppError::ConnectionFailed(format!("Reading '{}' failed: {}", url, e)))?
+        {
+            received += chunk.len() as u64;
+            buffer.extend_from_slice(&chunk);
+
+            while buffer.len() >= part_size {
+                let rest = buffer.split_off(part_size);
+                let body = std::mem::replace(&mut buffer, rest);
+                let state = match upload.take() {
+                    Some(state) => state,
+                    None => {
+                        self.start_multipart_upload(job, client, profile, content_length.unwrap_or(0), 0, content_type.as_deref())
+                            .await?
+                    }
+                };
+                parts.push(self.upload_import_part(job, client, &state, parts.len() as i32 + 1, body).await?);
+                upload = Some(state);
+            }
+
+            if last_update.elapsed() >= std::time::Duration::from_millis(100) {
+                self.update_job_progress(&job.id, received).await;
+                last_update = std::time::Instant::now();
+            }
+        }
+
+        if let Some(length) = content_length {
+            if received != length {
+                return Err(crate::error::AppError::ConnectionFailed(format!(
+                    "'{}' ended after {} of {} bytes",
+                    url, received, length
+                )));
+            }
+        }
+
+        match upload {
+            None => {
+                client
+                    .put_object()
+                    .bucket(&job.bucket)
+                    .key(&job.key)
+                    .content_length(buffer.len() as i64)
+                    .set_content_type(content_type)
+                    .body(ByteStream::from(buffer))
+                    .send()
+                    .await
+                    .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?;
+            }
+            Some(state) => {
+                if !buffer.is_empty() {
+                    parts.push(self.upload_import_part(job, client, &state, parts.len() as i32 + 1, buffer).await?);
+                }
+                let completed = CompletedMultipartUpload::builder()
+                    .set_parts(Some(
+                        parts
+                            .iter()
+                            .map(|part| {
+                                CompletedPart::builder()
+                                    .part_number(part.part_number)
+                                    .e_tag(&part.e_tag)
+                                    .build()
+                            })
+                            .collect(),
+                    ))
+                    .build();
+                client
+                    .complete_multipart_upload()
+                    .bucket(&job.bucket)
+                    .key(&job.key)
+                    .upload_id(&state.upload_id)
+                    .multipart_upload(completed)
+                    .send()
+                    .await
+                    .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?;
+                self.forget_upload(&state).await;
+            }
+        }
+
+        self.update_job_progress(&job.id, received).await;
+        if content_length.is_none() {
+            self.update_job_total_size(&job.id, received).await;
+        }
+        Ok(())
+    }
+
+    async fn upload_import_part(
+        &self,
+        job: &TransferJob,
+        client: &aws_sdk_s3::Client,
+        state: &MultipartState,
+        part_number: i32,
+        body: Vec<u8>,
+    ) -> crate::error::Result<CompletedPartRecord> {
+        let output = client
+            .upload_part()
+            .bucket(&job.bucket)
+            .key(&job.key)
+            .upload_id(&state.upload_id)
+            .part_number(part_number)
+            .body(ByteStream::from(body))
+            .send()
+            .await
+            .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?;
+        let part = CompletedPartRecord {
+            part_number,
+            e_tag: output.e_tag().unwrap_or_default().to_string(),
+        };
+        self.update_multipart(|store| store.record_part(&job.bucket, &job.key, &job.local_path, part.clone()))
+            .await;
+        Ok(part)
+    }
+
+    /// Create a multipart upload and save its state. This runs as its own task so
+    /// cancelling the job can't drop it between S3 creating the upload and the
+    /// state being saved, which would leave an upload nothing knows to abort. If
+    /// the job was cancelled meanwhile, the new upload is aborted right away.
+    async fn start_multipart_upload(
+        &self,
+        job: &TransferJob,
+        client: &aws_sdk_s3::Client,
+        profile: &Profile,
+        file_size: u64,
+        file_modified: i64,
+        content_type: Option<&str>,
+    ) -> crate::error::Result<MultipartState> {
+        let manager = self.clone();
+        let client = client.clone();
+        let job = job.clone();
+        let profile_id = profile.id.clone();
+        let content_type = content_type.map(str::to_string);
+
+        let task = tokio::spawn(async move {
+            let created = client
+                .create_multipart_upload()
+                .bucket(&job.bucket)
+                .key(&job.key)
+                .set_content_type(content_type)
+                .send()
+                .await
+                .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?;
+            let state = MultipartState {
+                profile_id,
+                bucket: job.bucket.clone(),
+                bucket_region: job.bucket_region.clone(),
+                key: job.key.clone(),
+                local_path: job.local_path.clone(),
+                file_size,
+                file_modified,
+                upload_id: created.upload_id().unwrap_or_default().to_string(),
+                part_size: multipart::part_size_for(file_size),
+                parts: Vec::new(),
+                started_at: chrono::Utc::now().timestamp_millis(),
+            };
+            manager.update_multipart(|store| store.put(state.clone())).await;
+
+            let cancelled = matches!(
+                manager.get_job(&job.id).await.map(|job| job.status),
+                Some(TransferStatus::Cancelled)
+            );
+            if cancelled {
+                log::info!("Upload of '{}' was cancelled while starting, aborting it", job.key);
+                manager.abort_upload(&state, &client).await?;
+                return Err(crate::error::AppError::Cancelled(format!("Upload of '{}'", job.key)));
+            }
+            Ok(state)
+        });
+
+        task.await
+            .map_err(|e| crate::error::AppError::S3Error(format!("Starting multipart upload failed: {}", e)))?
+    }
+
+    /// Replace the saved part list with what S3 actually holds for the upload.
+    /// Returns `None` when the upload no longer exists and has to start over.
+    async fn reconcile_parts(&self, client: &aws_sdk_s3::Client, mut state: MultipartState) -> crate::error::Result<Option<MultipartState>> {
+        let mut parts = Vec::new();
+        let mut marker: Option<String> = None;
+        loop {
+            let result = client
+                .list_parts()
+                .bucket(&state.bucket)
+                .key(&state.key)
+                .upload_id(&state.upload_id)
+                .set_part_number_marker(marker.take())
+                .send()
+                .await;
+            let output = match result {
+                Ok(output) => output,
+                Err(err) if err.as_service_error().and_then(|e| e.code()) == Some("NoSuchUpload") => {
+                    log::info!("Saved upload of '{}' no longer exists, starting over", state.key);
+                    self.forget_upload(&state).await;
+                    return Ok(None);
+                }
+                Err(err) => return Err(crate::error::AppError::S3Error(err.to_string())),
+            };
+
+            parts.extend(output.parts().iter().filter_map(|part| {
+                Some(CompletedPartRecord {
+                    part_number: part.part_number()?,
+                    e_tag: part.e_tag()?.to_string(),
+                })
+            }));
+
+            match output.next_part_number_marker() {
+                Some(next) if output.is_truncated().unwrap_or(false) => marker = Some(next.to_string()),
+                _ => break,
+            }
+        }
+
+        state.parts = parts;
+        self.update_multipart(|store| store.put(state.clone())).await;
+        Ok(Some(state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TransferManager;
+    use crate::credentials::{CredentialType, Profile};
+    use crate::s3::test_server::TestS3;
+    use crate::transfer::{TransferJob, TransferType};
+    use std::sync::Arc;
+
+    /// Just enough of S3 to start and abort a multipart upload
+    async fn multipart_server() -> TestS3 {
+        TestS3::start(|method, target| match method {
+            "POST" if target.contains("uploads") => (
+                200,
+                "<InitiateMultipartUploadResult><Bucket>b</Bucket><Key>k</Key>\
+                 <UploadId>up-1</UploadId></InitiateMultipartUploadResult>"
+                    .to_string(),
+            ),
+            "DELETE" => (204, String::new()),
+            _ => (200, String::new()),
+        })
+        .await
+    }
+
+    fn aborted(server: &TestS3) -> bool {
+        server
+            .requests()
+            .iter()
+            .any(|request| request.starts_with("DELETE /b/k?") && request.contains("uploadId=up-1"))
+    }
+
+    fn upload_job(name: &str) -> TransferJob {
+        let path = std::env::temp_dir().join(format!("brows3-{}-{}", name, std::process::id()));
+        std::fs::write(&path, vec![7u8; 4096]).unwrap();
+        TransferJob::new(TransferType::Upload, "b".into(), None, "k".into(), path, 4096)
+    }
+
+    fn spawn_upload(
+        manager: &Arc<TransferManager>,
+        job: &TransferJob,
+        client: &aws_sdk_s3::Client,
+    ) -> tokio::task::JoinHandle<crate::error::Result<()>> {
+        let (manager, job, client) = (manager.clone(), job.clone(), client.clone());
+        let profile = Profile::new("test".into(), CredentialType::Environment, None);
+        tokio::spawn(async move { manager.upload_multipart(&job, &client, &profile).await })
+    }
+
+    #[tokio::test]
+    async fn cancelling_mid_part_leaves_an_upload_that_can_be_aborted() {
+        let server = multipart_server().await;
+        // The part is sent but never answered, as if the network hung
+        let part = server.hold("PUT", "uploadId=up-1");
+        let client = server.client();
+        let manager = Arc::new(TransferManager::new());
+        let job = upload_job("cancel-mid-part");
+        manager.add_job(job.clone()).await;
+
+        let task = spawn_upload(&manager, &job, &client);
+        part.received().await;
+        assert!(manager.cancel_job(&job.id).await);
+        task.abort();
+
+        let state = manager.multipart_state_for(&job).await.expect("upload is tracked");
+        manager.abort_upload(&state, &client).await.unwrap();
+        assert!(aborted(&server));
+        assert!(manager.multipart_state_for(&job).await.is_none());
+        let _ = std::fs::remove_file(&job.local_path);
+    }
+
+    #[tokio::test]
+    async fn cancelling_while_the_upload_is_created_aborts_it() {
+        let server = multipart_server().await;
+        let create = server.hold("POST", "uploads");
+        let client = server.client();
+        let manager = Arc::new(TransferManager::new());
+        let job = upload_job("cancel-while-creating");
+        manager.add_job(job.clone()).await;
+
+        let task = spawn_upload(&manager, &job, &client);
+        create.received().await;
+        assert!(manager.cancel_job(&job.id).await);
+        task.abort();
+        create.release();
+
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+        // The state is saved when the upload is created and dropped once it's aborted
+        while !(aborted(&server) && manager.multipart_state_for(&job).await.is_none())
+            && tokio::time::Instant::now() < deadline
+        {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert!(aborted(&server));
+        assert!(manager.multipart_state_for(&job).await.is_none());
+        let _ = std::fs::remove_file(&job.local_path);
+    }
 }
@@ -1,4 +1,7 @@
+pub mod disk_space;
+pub mod exclude;
 pub mod manager;
+pub mod multipart;
 
 pub use manager::TransferManager;
 
@@ -40,6 +43,41 @@ pub struct TransferJob {
     pub parent_group_id: Option<String>,
     pub group_name: Option<String>,
     pub is_group_root: bool,
+    /// For uploads streamed from an HTTP(S) URL instead of a local file; the URL
+    /// is also shown as `local_path`
+    #[serde(default)]
+    pub source_url: Option<String>,
+    /// When the current run of the job began, in ms; reset by retries
+    #[serde(default)]
+    pub started_at: Option<i64>,
+    /// Bytes per second over the run, updated as it goes
+    #[serde(default)]
+    pub average_throughput: Option<f64>,
+    /// Fastest bytes per second over any one-second window of the run
+    #[serde(default)]
+    pub peak_throughput: Option<f64>,
+    /// (ms, processed bytes) the average is measured from
+    #[serde(skip)]
+    throughput_start: Option<(i64, u64)>,
+    /// (ms, processed bytes) the current peak window is measured from
+    #[serde(skip)]
+    throughput_window: Option<(i64, u64)>,
+}
+
+/// Shortest span a peak throughput is measured over, so one fast chunk doesn't
+/// count as the peak
+const PEAK_THROUGHPUT_WINDOW_MS: i64 = 1000;
+
+fn faster(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Bytes per second, or `None` for a span too short to measure
+fn bytes_per_second(bytes: u64, elapsed_ms: i64) -> Option<f64> {
+    (elapsed_ms > 0).then(|| bytes as f64 * 1000.0 / elapsed_ms as f64)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,12 +113,88 @@ impl TransferJob {
             parent_group_id: None,
             group_name: None,
             is_group_root: false,
+            source_url: None,
+            started_at: None,
+            average_throughput: None,
+            peak_throughput: None,
+            throughput_start: None,
+            throughput_window: None,
         }
     }
 
+    /// An upload that streams `url` into `bucket`/`key` without a local copy
+    pub fn url_import(bucket: String, bucket_region: Option<String>, key: String, url: String) -> Self {
+        let mut job = Self::new(TransferType::Upload, bucket, bucket_region, key, PathBuf::from(&url), 0);
+        job.source_url = Some(url);
+        job
+    }
+
     pub fn with_group(mut self, group_id: String, name: String) -> Self {
         self.parent_group_id = Some(group_id);
         self.group_name = Some(name);
         self
     }
+
+    /// Measure throughput from `processed_bytes` at `now_ms`. Also used when a
+    /// resumed upload skips its finished parts, so those don't count as speed.
+    fn start_throughput_clock(&mut self, now_ms: i64) {
+        self.started_at.get_or_insert(now_ms);
+        self.throughput_start = Some((now_ms, self.processed_bytes));
+        self.throughput_window = Some((now_ms, self.processed_bytes));
+    }
+
+    /// Update the average and peak after `processed_bytes` changed
+    fn record_throughput(&mut self, now_ms: i64) {
+        let Some((start_ms, start_bytes)) = self.throughput_start else {
+            return;
+        };
+        if let Some(average) = bytes_per_second(self.processed_bytes.saturating_sub(start_bytes), now_ms - start_ms) {
+            self.average_throughput = Some(average);
+        }
+
+        if let Some((window_ms, window_bytes)) = self.throughput_window {
+            if now_ms - window_ms >= PEAK_THROUGHPUT_WINDOW_MS {
+                let rate = bytes_per_second(self.processed_bytes.saturating_sub(window_bytes), now_ms - window_ms);
+                self.peak_throughput = faster(self.peak_throughput, rate);
+                self.throughput_window = Some((now_ms, self.processed_bytes));
+            }
+        }
+    }
+
+    /// Settle the figures when the run ends. A run shorter than one window has no
+    /// peak of its own, so its average stands in.
+    fn finish_throughput(&mut self, now_ms: i64) {
+        self.record_throughput(now_ms);
+        self.peak_throughput = faster(self.peak_throughput, self.average_throughput);
+        self.throughput_start = None;
+        self.throughput_window = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TransferJob, TransferType};
+    use std::path::PathBuf;
+
+    #[test]
+    fn throughput_ignores_resumed_bytes_and_tracks_peak_window() {
+        let mut job = TransferJob::new(TransferType::Upload, "b".into(), None, "k".into(), PathBuf::from("/tmp/k"), 10_000);
+        job.processed_bytes = 4_000;
+        job.start_throughput_clock(0);
+
+        job.processed_bytes = 5_000;
+        job.record_throughput(500);
+        assert_eq!(job.average_throughput, Some(2_000.0));
+        assert_eq!(job.peak_throughput, None);
+
+        job.processed_bytes = 8_000;
+        job.record_throughput(1_000);
+        assert_eq!(job.peak_throughput, Some(4_000.0));
+
+        job.processed_bytes = 10_000;
+        job.finish_throughput(2_000);
+        assert_eq!(job.average_throughput, Some(3_000.0));
+        assert_eq!(job.peak_throughput, Some(4_000.0));
+        assert_eq!(job.started_at, Some(0));
+    }
 }
@@ -0,0 +1,418 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const MULTIPART_FILE: &str = "multipart_uploads.json";
+
+const MIN_PART_SIZE: u64 = 16 * 1024 * 1024;
+/// Files at least this large are uploaded in parts, so an interrupted upload can
+/// pick up where it stopped and a cancelled one is aborted rather than committed
+pub const MULTIPART_THRESHOLD: u64 = MIN_PART_SIZE;
+const MAX_PARTS: u64 = 10_000;
+
+/// Uploads left unfinished for this long are aborted at startup
+pub const STALE_UPLOAD_AGE: chrono::Duration = chrono::Duration::days(7);
+
+/// Part size for a file, growing past the minimum so no upload needs more than
+/// S3's 10,000 parts
+pub fn part_size_for(file_size: u64) -> u64 {
+    MIN_PART_SIZE.max(file_size.div_ceil(MAX_PARTS))
+}
+
+/// Largest object a single CopyObject request can copy
+pub const MAX_COPY_OBJECT_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+const COPY_PART_CONCURRENCY: usize = 4;
+
+/// Inclusive byte ranges of the parts an object of `size` bytes is copied in
+fn copy_part_ranges(size: u64) -> Vec<(u64, u64)> {
+    let part_size = part_size_for(size);
+    (0..size.div_ceil(part_size))
+        .map(|part| {
+            let start = part * part_size;
+            (start, (start + part_size).min(size) - 1)
+        })
+        .collect()
+}
+
+/// Server-side copy of an object too large for CopyObject, part by part with
+/// UploadPartCopy. A multipart upload starts out bare, so the copy is given the
+/// source's headers, user metadata, encryption and tags from its `head` and tag
+/// set. `storage_class` is the class the copy is written in. The upload is
+/// aborted if any part fails.
+pub async fn copy_in_parts(
+    client: &aws_sdk_s3::Client,
+    bucket: &str,
+    source_key: &str,
+    head: &aws_sdk_s3::operation::head_object::HeadObjectOutput,
+    destination_key: &str,
+    storage_class: Option<aws_sdk_s3::types::StorageClass>,
+) -> crate::error::Result<()> {
+    use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+    use futures::StreamExt;
+
+    let tagging = match client.get_object_tagging().bucket(bucket).key(source_key).send().await {
+        Ok(output) if !output.tag_set().is_empty() => Some(
+            output
+                .tag_set()
+                .iter()
+                .map(|tag| format!("{}={}", urlencoding::encode(tag.key()), urlencoding::encode(tag.value())))
+                .collect::<Vec<_>>()
+                .join("&"),
+        ),
+        Ok(_) => None,
+        Err(e) => {
+            log::warn!("Could not read the tags of '{}', copying without them: {}", source_key, e);
+            None
+        }
+    };
+
+    let upload = client
+        .create_multipart_upload()
+        .bucket(bucket)
+        .key(destination_key)
+        .set_content_type(head.content_type().map(str::to_string))
+        .set_cache_control(head.cache_control().map(str::to_string))
+        .set_content_encoding(head.content_encoding().map(str::to_string))
+        .set_content_disposition(head.content_disposition().map(str::to_string))
+        .set_content_language(head.content_language().map(str::to_string))
+        .set_metadata(head.metadata().cloned())
+        .set_server_side_encryption(head.server_side_encryption().cloned())
+        .set_ssekms_key_id(head.ssekms_key_id().map(str::to_string))
+        .set_bucket_key_enabled(head.bucket_key_enabled())
+        .set_storage_class(storage_class)
+        .set_tagging(tagging)
+        .send()
+        .await
+        .map_err(|e| crate::error::AppError::S3Error(e.to_string()))?;
+    let upload_id = upload
+        .upload_id()
+        .ok_or_else(|| crate::error::AppError::S3Error("Multipart copy started without an upload id".into()))?;
+
+    let size = head.content_length().unwrap_or(0).max(0) as u64;
+    let copy_source = crate::s3::encode_copy_source(bucket, source_key);
+    let copied: Vec<crate::error::Result<CompletedPart>> = futures::stream::iter(copy_part_ranges(size).into_iter().enumerate())
+        .map(|(index, (start, end))| {
+            let copy_source = &copy_source;
+            async move {
+                let part_number = index as i32 + 1;
+                let output = client
+                    .upload_part_copy()
+                    .bucket(bucket)
+                    .key(destination_key)
+                    .upload_id(upload_id)
+                    .part_number(part_number)
+                    .copy_source(copy_source)
+                    .copy_source_range(format!("bytes={}-{}", start, end))
+                    .send()
+                    .await
+                    .map_err(|e| crate::error::AppError::S3Error(format!("Copying part {} failed: {}", part_number, e)))?;
+                let e_tag = output.copy_part_result().and_then(|result| result.e_tag()).unwrap_or_default();
+                Ok(CompletedPart::builder().part_number(part_number).e_tag(e_tag).build())
+            }
+        })
+        .buffer_unordered(COPY_PART_CONCURRENCY)
+        .collect()
+        .await;
+
+    let completed = match copied.into_iter().collect::<crate::error::Result<Vec<_>>>() {
+        Ok(mut parts) => {
+            parts.sort_by_key(|part| part.part_number());
+            client
+                .complete_multipart_upload()
+                .bucket(bucket)
+                .key(destination_key)
+                .upload_id(upload_id)
+                .multipart_upload(CompletedMultipartUpload::builder().set_parts(Some(parts)).build())
+                .send()
+                .await
+                .map(|_| ())
+                .map_err(|e| crate::error::AppError::S3Error(e.to_string()))
+        }
+        Err(e) => Err(e),
+    };
+
+    if completed.is_err() {
+        if let Err(e) = client
+            .abort_multipart_upload()
+            .bucket(bucket)
+            .key(destination_key)
+            .upload_id(upload_id)
+            .send()
+            .await
+        {
+            log::warn!("Could not abort the multipart copy to '{}': {}", destination_key, e);
+        }
+    }
+    completed
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletedPartRecord {
+    pub part_number: i32,
+    pub e_tag: String,
+}
+
+/// What is needed to continue a multipart upload after a restart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultipartState {
+    pub profile_id: String,
+    pub bucket: String,
+    pub bucket_region: Option<String>,
+    pub key: String,
+    pub local_path: String,
+    pub file_size: u64,
+    /// Local file's modification time (seconds since the epoch); a changed file
+    /// can't be resumed
+    pub file_modified: i64,
+    pub upload_id: String,
+    pub part_size: u64,
+    pub parts: Vec<CompletedPartRecord>,
+    pub started_at: i64, // Timestamp (ms)
+}
+
+impl MultipartState {
+    pub fn part_count(&self) -> i32 {
+        self.file_size.div_ceil(self.part_size).max(1) as i32
+    }
+
+    pub fn uploaded_bytes(&self) -> u64 {
+        self.parts
+            .iter()
+            .map(|part| {
+                let offset = (part.part_number as u64 - 1) * self.part_size;
+                self.part_size.min(self.file_size.saturating_sub(offset))
+            })
+            .sum()
+    }
+
+    /// Uploads streamed from a URL keep the URL as `local_path`. They can't be
+    /// resumed, only aborted.
+    pub fn is_url_import(&self) -> bool {
+        self.local_path.starts_with("http://") || self.local_path.starts_with("https://")
+    }
+
+    /// Whether this state still describes `local_path` as it is on disk now
+    pub fn matches_file(&self, file_size: u64, file_modified: i64) -> bool {
+        self.file_size == file_size && self.file_modified == file_modified
+    }
+}
+
+fn upload_id_key(bucket: &str, key: &str, local_path: &str) -> String {
+    format!("{}\n{}\n{}", bucket, key, local_path)
+}
+
+/// In-progress multipart uploads, persisted in the config directory after every
+/// part so they survive a restart
+#[derive(Debug, Default)]
+pub struct MultipartStore {
+    path: Option<PathBuf>,
+    uploads: HashMap<String, MultipartState>,
+    /// Bumped for every snapshot so a slow write can't replace a newer one
+    version: u64,
+    written: Arc<std::sync::Mutex<u64>>,
+}
+
+/// The store's contents at one point, written to disk by [`StoreSnapshot::write`]
+/// once the store's lock has been released
+pub struct StoreSnapshot {
+    path: PathBuf,
+    json: String,
+    version: u64,
+    written: Arc<std::sync::Mutex<u64>>,
+}
+
+impl StoreSnapshot {
+    pub async fn write(self) {
+        let result = tokio::task::spawn_blocking(move || self.write_blocking())
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|result| result.map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            log::warn!("Could not save multipart upload state: {}", e);
+        }
+    }
+
+    fn write_blocking(&self) -> std::io::Result<()> {
+        let mut written = self.written.lock().unwrap_or_else(|e| e.into_inner());
+        if *written >= self.version {
+            return Ok(());
+        }
+
+        let temp_path = self.path.with_extension("tmp");
+        std::fs::write(&temp_path, &self.json)?;
+
+        #[cfg(windows)]
+        {
+            // Windows rename does not replace an existing destination file.
+            if self.path.exists() {
+                std::fs::remove_file(&self.path)?;
+            }
+        }
+        std::fs::rename(&temp_path, &self.path)?;
+
+        *written = self.version;
+        Ok(())
+    }
+}
+
+impl MultipartStore {
+    pub fn load(config_dir: &Path) -> Self {
+        let path = config_dir.join(MULTIPART_FILE);
+        let uploads = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::warn!("Ignoring unreadable {}: {}", path.display(), e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        Self {
+            path: Some(path),
+            uploads,
+            ..Self::default()
+        }
+    }
+
+    /// Capture the uploads for saving. `None` when the store isn't backed by a file.
+    pub fn snapshot(&mut self) -> Option<StoreSnapshot> {
+        let path = self.path.clone()?;
+        let json = match serde_json::to_string_pretty(&self.uploads) {
+            Ok(json) => json,
+            Err(e) => {
+                log::warn!("Could not save multipart upload state: {}", e);
+                return None;
+            }
+        };
+        self.version += 1;
+        Some(StoreSnapshot {
+            path,
+            json,
+            version: self.version,
+            written: self.written.clone(),
+        })
+    }
+
+    pub fn get(&self, bucket: &str, key: &str, local_path: &str) -> Option<MultipartState> {
+        self.uploads.get(&upload_id_key(bucket, key, local_path)).cloned()
+    }
+
+    pub fn list(&self) -> Vec<MultipartState> {
+        let mut uploads: Vec<MultipartState> = self.uploads.values().cloned().collect();
+        uploads.sort_by_key(|state| std::cmp::Reverse(state.started_at));
+        uploads
+    }
+
+    pub fn put(&mut self, state: MultipartState) {
+        self.uploads.insert(
+            upload_id_key(&state.bucket, &state.key, &state.local_path),
+            state,
+        );
+    }
+
+    pub fn record_part(&mut self, bucket: &str, key: &str, local_path: &str, part: CompletedPartRecord) {
+        if let Some(state) = self.uploads.get_mut(&upload_id_key(bucket, key, local_path)) {
+            state.parts.retain(|existing| existing.part_number != part.part_number);
+            state.parts.push(part);
+        }
+    }
+
+    pub fn remove(&mut self, bucket: &str, key: &str, local_path: &str) -> Option<MultipartState> {
+        self.uploads.remove(&upload_id_key(bucket, key, local_path))
+    }
+
+    /// Uploads started before `cutoff_ms`
+    pub fn older_than(&self, cutoff_ms: i64) -> Vec<MultipartState> {
+        self.uploads
+            .values()
+            .filter(|state| state.started_at < cutoff_ms)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        copy_part_ranges, part_size_for, CompletedPartRecord, MultipartState, MultipartStore, MIN_PART_SIZE,
+    };
+
+    #[test]
+    fn part_size_keeps_uploads_within_part_limit() {
+        assert_eq!(part_size_for(100 * 1024 * 1024), MIN_PART_SIZE);
+        let huge = 5 * 1024 * 1024 * 1024 * 1024u64;
+        assert!(huge.div_ceil(part_size_for(huge)) <= 10_000);
+    }
+
+    #[test]
+    fn copy_parts_cover_the_object_without_gaps() {
+        let size = 6 * 1024 * 1024 * 1024u64 + 7;
+        let ranges = copy_part_ranges(size);
+        assert_eq!(ranges.first(), Some(&(0, MIN_PART_SIZE - 1)));
+        assert_eq!(ranges.last().map(|range| range.1), Some(size - 1));
+        assert!(ranges.windows(2).all(|pair| pair[1].0 == pair[0].1 + 1));
+        assert!(ranges.len() <= 10_000);
+    }
+
+    #[test]
+    fn uploaded_bytes_accounts_for_short_last_part() {
+        let state = MultipartState {
+            profile_id: "p".into(),
+            bucket: "b".into(),
+            bucket_region: None,
+            key: "k".into(),
+            local_path: "/tmp/k".into(),
+            file_size: 25,
+            file_modified: 0,
+            upload_id: "u".into(),
+            part_size: 10,
+            parts: vec![
+                CompletedPartRecord { part_number: 1, e_tag: "a".into() },
+                CompletedPartRecord { part_number: 3, e_tag: "c".into() },
+            ],
+            started_at: 0,
+        };
+        assert_eq!(state.part_count(), 3);
+        assert_eq!(state.uploaded_bytes(), 15);
+        assert!(!state.is_url_import());
+
+        let imported = MultipartState {
+            local_path: "https://example.com/k".into(),
+            ..state
+        };
+        assert!(imported.is_url_import());
+    }
+
+    #[tokio::test]
+    async fn an_older_snapshot_never_replaces_a_newer_one() {
+        let dir = std::env::temp_dir().join(format!("brows3-multipart-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let state = MultipartState {
+            profile_id: "p".into(),
+            bucket: "b".into(),
+            bucket_region: None,
+            key: "k".into(),
+            local_path: "/tmp/k".into(),
+            file_size: 25,
+            file_modified: 0,
+            upload_id: "u".into(),
+            part_size: 10,
+            parts: Vec::new(),
+            started_at: 0,
+        };
+        let mut store = MultipartStore::load(&dir);
+        store.put(state.clone());
+        let older = store.snapshot().unwrap();
+        store.record_part("b", "k", "/tmp/k", CompletedPartRecord { part_number: 1, e_tag: "a".into() });
+        let newer = store.snapshot().unwrap();
+
+        newer.write().await;
+        older.write().await;
+
+        let reloaded = MultipartStore::load(&dir);
+        assert_eq!(reloaded.get("b", "k", "/tmp/k").map(|state| state.parts.len()), Some(1));
+        assert!(!dir.join("multipart_uploads.tmp").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}